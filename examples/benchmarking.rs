@@ -12,8 +12,9 @@ use embassy_time::{Delay, Instant};
 use embedded_hal_bus::spi::ExclusiveDevice;
 use {defmt_rtt as _, panic_probe as _};
 
-use embedded_graphics::pixelcolor::{Rgb666, RgbColor};
+use embedded_graphics::pixelcolor::Rgb666;
 
+use ili9488_rs::bench::{BenchReport, CountingInterface};
 use ili9488_rs::{Ili9488, Orientation, Rgb111, Rgb111Mode, Rgb666Mode};
 
 // #[embassy_executor::main]
@@ -50,7 +51,7 @@ fn main() -> ! {
     let spi_device = ExclusiveDevice::new_no_delay(spi, cs).unwrap();
     let dc = Output::new(p.PA1, Level::Low, embassy_stm32::gpio::Speed::VeryHigh);
 
-    let spi_interface = SPIInterface::new(spi_device, dc);
+    let spi_interface = CountingInterface::new(SPIInterface::new(spi_device, dc));
     let reset_pin = Output::new(p.PA11, Level::Low, embassy_stm32::gpio::Speed::VeryHigh);
     let mut delay = Delay;
 
@@ -68,22 +69,41 @@ fn main() -> ! {
     info!("Time taken to do a full screen clear:");
 
     // Render
-    let start = Instant::now().as_millis();
-    display.clear_screen(Rgb666::RED).unwrap();
-    let end = Instant::now().as_millis();
-    info!("(rgb 6-6-6): {} ms", end - start);
+    let start = Instant::now();
+    let ((), report) = display
+        // Not an Rgb111 color, which would be filled in 3 bpp
+        .bench(|display| display.clear_screen(Rgb666::new(63, 20, 0)))
+        .unwrap();
+    log_report("rgb 6-6-6", report, start);
 
     let mut display = display.change_pixel_format(Rgb111Mode).unwrap();
 
-    let start = Instant::now().as_millis();
-    display.clear_screen(Rgb111::GREEN).unwrap();
-    let end = Instant::now().as_millis();
-    info!("(rgb 1-1-1): {} ms", end - start);
+    let start = Instant::now();
+    let ((), report) = display
+        .bench(|display| display.clear_screen(Rgb111::GREEN))
+        .unwrap();
+    log_report("rgb 1-1-1", report, start);
 
-    let start = Instant::now().as_millis();
-    display.clear_screen_fast(Rgb111::BLUE).unwrap();
-    let end = Instant::now().as_millis();
-    info!("(rgb 1-1-1) fast version: {} ms", end - start);
+    let start = Instant::now();
+    let ((), report) = display
+        .bench(|display| display.clear_screen_fast(Rgb111::BLUE))
+        .unwrap();
+    log_report("rgb 1-1-1 fast version", report, start);
 
-    loop {}
+    loop {
+        cortex_m::asm::wfi();
+    }
+}
+
+fn log_report(name: &str, report: BenchReport, start: Instant) {
+    let elapsed_us = start.elapsed().as_micros();
+    info!(
+        "({}): {} ms, {} bytes in {} transactions, {} bytes/s, {} pixels/s",
+        name,
+        elapsed_us / 1000,
+        report.bytes,
+        report.transactions,
+        report.bytes_per_second(elapsed_us),
+        report.pixels_per_second(elapsed_us),
+    );
 }
@@ -21,7 +21,7 @@ use embedded_graphics::{
     text::{Alignment, Text},
 };
 
-use ili9488_rs::{Ili9488, Orientation, Rgb111, Rgb666Mode};
+use ili9488_rs::{Ili9488, Orientation, Rgb666Mode};
 
 #[embassy_executor::main]
 async fn main(_spawner: Spawner) {
@@ -91,6 +91,14 @@ async fn main(_spawner: Spawner) {
     // Backend for the buffer
     const BUFFER_WIDTH: usize = (FONT_WIDTH + 6) * 3;
     let mut buffer_data = [Rgb666::CSS_DODGER_BLUE; BUFFER_WIDTH * FONT_HEIGHT];
+
+    // The counter is always drawn to the same area, so its window is only set once
+    let area = Rectangle::new(
+        display.bounding_box().center()
+            - Point::new(BUFFER_WIDTH as i32 / 2, FONT_HEIGHT as i32 / 2),
+        Size::new(BUFFER_WIDTH as u32, FONT_HEIGHT as u32),
+    );
+    display.set_draw_region(area).unwrap();
     loop {
         // Setup Frame buffer
         let mut fbuf = FrameBuf::new(&mut buffer_data, BUFFER_WIDTH, FONT_HEIGHT);
@@ -98,11 +106,6 @@ async fn main(_spawner: Spawner) {
             i = 0;
             fbuf.clear(Rgb666::CSS_DODGER_BLUE).unwrap();
         }
-        let area = Rectangle::new(
-            display.bounding_box().center()
-                - Point::new(BUFFER_WIDTH as i32 / 2, FONT_HEIGHT as i32 / 2),
-            fbuf.size(),
-        );
 
         // Render text in frame buffer
         let mut buffer = itoa::Buffer::new();
@@ -116,7 +119,7 @@ async fn main(_spawner: Spawner) {
 
         // Render frame buffer
         let start = Instant::now().as_millis();
-        display.fill_contiguous(&area, buffer_data).unwrap();
+        display.rewrite(buffer_data).unwrap();
         let end = Instant::now().as_millis();
         info!("text render time: {} ms", end - start);
 
@@ -127,5 +127,7 @@ fn main() -> ! {
     .draw(&mut display)
     .unwrap();
 
-    loop {}
+    loop {
+        cortex_m::asm::wfi();
+    }
 }
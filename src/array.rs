@@ -0,0 +1,154 @@
+use embedded_graphics_core::prelude::{Dimensions, DrawTarget, Pixel};
+use embedded_graphics_core::primitives::Rectangle;
+
+/// Several displays driven together, e.g. two panels on one SPI bus for signage.
+///
+/// Every display has its own `SpiDevice` (chip select) and DC pin on the shared bus, see
+/// [Shared SPI bus](crate#shared-spi-bus). Drawing to the array itself broadcasts the same
+/// content to all displays, while [DisplayArray::get_mut] addresses a single one.
+///
+/// ```ignore
+/// let bus = RefCell::new(spi);
+/// let left = SPIInterface::new(RefCellDevice::new(&bus, cs_left, NoDelay)?, dc_left);
+/// let right = SPIInterface::new(RefCellDevice::new(&bus, cs_right, NoDelay)?, dc_right);
+/// let mut displays = DisplayArray::new([
+///     Ili9488::new(left, reset_left, &mut delay, Orientation::Landscape, Rgb666Mode)?,
+///     Ili9488::new(right, reset_right, &mut delay, Orientation::Landscape, Rgb666Mode)?,
+/// ]);
+///
+/// // Same logo on both panels
+/// logo.draw(&mut displays)?;
+/// // Different text on each
+/// Text::new("Left", point, style).draw(displays.get_mut(0).unwrap())?;
+/// Text::new("Right", point, style).draw(displays.get_mut(1).unwrap())?;
+/// ```
+pub struct DisplayArray<D, const N: usize> {
+    displays: [D; N],
+}
+
+impl<D, const N: usize> DisplayArray<D, N> {
+    pub fn new(displays: [D; N]) -> Self {
+        Self { displays }
+    }
+
+    /// The display at `index`, to draw to it alone
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut D> {
+        self.displays.get_mut(index)
+    }
+
+    /// All displays, to draw to them alone
+    pub fn displays_mut(&mut self) -> &mut [D; N] {
+        &mut self.displays
+    }
+
+    /// Run `f` on every display in turn, stopping at the first error.
+    ///
+    /// ```ignore
+    /// displays.broadcast(|display| display.brightness(128).map(drop))?;
+    /// ```
+    pub fn broadcast<E, F>(&mut self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(&mut D) -> Result<(), E>,
+    {
+        self.displays.iter_mut().try_for_each(&mut f)
+    }
+
+    /// Give back the displays
+    pub fn release(self) -> [D; N] {
+        self.displays
+    }
+}
+
+/// Number of pixels buffered before they are sent to every display
+const BROADCAST_CHUNK: usize = 64;
+
+/// Draws the same content to all displays
+impl<D, const N: usize> DrawTarget for DisplayArray<D, N>
+where
+    D: DrawTarget,
+{
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        // The pixels can only be iterated once, so hand them on in chunks
+        let mut pixels = pixels.into_iter().peekable();
+        while let Some(&first) = pixels.peek() {
+            let mut chunk = [first; BROADCAST_CHUNK];
+            let mut len = 0;
+            for (slot, pixel) in chunk.iter_mut().zip(pixels.by_ref()) {
+                *slot = pixel;
+                len += 1;
+            }
+            self.broadcast(|display| display.draw_iter(chunk[..len].iter().copied()))?;
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.broadcast(|display| display.fill_solid(area, color))
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.broadcast(|display| display.clear(color))
+    }
+}
+
+/// The area shared by all displays
+impl<D, const N: usize> Dimensions for DisplayArray<D, N>
+where
+    D: Dimensions,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.displays
+            .iter()
+            .map(|display| display.bounding_box())
+            .reduce(|a, b| a.intersection(&b))
+            .unwrap_or(Rectangle::zero())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sim;
+    use crate::{ModeState, Orientation, Rgb666Mode};
+    use embedded_graphics_core::pixelcolor::{Rgb666, Rgb888, RgbColor};
+    use embedded_graphics_core::prelude::{Point, Size};
+
+    #[test]
+    fn broadcast_and_independent() {
+        let mut displays = DisplayArray::new([
+            sim::display_with(Orientation::Landscape, Rgb666Mode),
+            sim::display_with(Orientation::Portrait, Rgb666Mode),
+        ]);
+        assert_eq!(
+            displays.bounding_box(),
+            Rectangle::new(Point::zero(), Size::new(320, 320))
+        );
+
+        let pixels = (0..100).map(|x| Pixel(Point::new(x, 5), Rgb666::RED));
+        displays.draw_iter(pixels).unwrap();
+        displays
+            .get_mut(1)
+            .unwrap()
+            .draw_iter([Pixel(Point::new(1, 1), Rgb666::BLUE)])
+            .unwrap();
+        displays
+            .broadcast(|display| display.invert_mode(ModeState::On).map(drop))
+            .unwrap();
+
+        for display in displays.displays_mut() {
+            assert_eq!(display.interface().pixel(0, 5), Some(Rgb888::RED));
+            assert_eq!(display.interface().pixel(99, 5), Some(Rgb888::RED));
+            assert!(display.is_inverted());
+        }
+        let [left, right] = displays.release();
+        assert_eq!(left.interface().pixel(1, 1), Some(Rgb888::BLACK));
+        assert_eq!(right.interface().pixel(1, 1), Some(Rgb888::BLUE));
+    }
+}
@@ -0,0 +1,217 @@
+//! Traffic statistics for benchmarking on real hardware
+//!
+//! Wrap the display interface in a [CountingInterface] and run the operations to measure
+//! with [Ili9488::bench]. Together with a timer this shows how buffer sizes, pixel formats
+//! and drawing methods affect the time spent on the bus.
+//!
+//! ```ignore
+//! let mut display = Ili9488::new(
+//!     CountingInterface::new(spi_interface),
+//!     reset,
+//!     &mut delay,
+//!     Orientation::Landscape,
+//!     Rgb666Mode,
+//! )?;
+//! let start = Instant::now();
+//! let ((), report) = display.bench(|display| display.clear_screen(Rgb666::RED))?;
+//! let elapsed_us = start.elapsed().as_micros();
+//! info!("{} bytes/s, {:?}", report.bytes_per_second(elapsed_us), report);
+//! ```
+//!
+//! Enable the `bench` feature to use it.
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+use crate::dcs::Instruction;
+use crate::{Ili9488, Result};
+
+/// Traffic of the operations run by [Ili9488::bench]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BenchReport {
+    /// Bytes sent, commands and parameters included
+    pub bytes: usize,
+    /// Calls to `send_commands` and `send_data`
+    pub transactions: usize,
+    /// Pixels in the windows written with Memory Write
+    pub pixels: usize,
+}
+
+impl BenchReport {
+    /// Bytes sent per second, when the operations took `elapsed_us` microseconds
+    pub fn bytes_per_second(&self, elapsed_us: u64) -> u64 {
+        per_second(self.bytes, elapsed_us)
+    }
+
+    /// Pixels written per second, when the operations took `elapsed_us` microseconds
+    pub fn pixels_per_second(&self, elapsed_us: u64) -> u64 {
+        per_second(self.pixels, elapsed_us)
+    }
+}
+
+fn per_second(count: usize, elapsed_us: u64) -> u64 {
+    (count as u64 * 1_000_000)
+        .checked_div(elapsed_us)
+        .unwrap_or(0)
+}
+
+/// Display interface adapter that counts the traffic going through it
+pub struct CountingInterface<DI> {
+    interface: DI,
+    report: BenchReport,
+    /// Last command sent, to find the window in its parameters
+    command: Option<u8>,
+    columns: usize,
+    pages: usize,
+}
+
+impl<DI: WriteOnlyDataCommand> CountingInterface<DI> {
+    pub fn new(interface: DI) -> Self {
+        Self {
+            interface,
+            report: BenchReport::default(),
+            command: None,
+            columns: 0,
+            pages: 0,
+        }
+    }
+
+    /// Traffic since the interface was created or last reset
+    pub fn report(&self) -> BenchReport {
+        self.report
+    }
+
+    /// Return the traffic so far and start counting from zero
+    pub fn reset(&mut self) -> BenchReport {
+        core::mem::take(&mut self.report)
+    }
+
+    /// Give back the wrapped interface
+    pub fn release(self) -> DI {
+        self.interface
+    }
+
+    /// Send `format` with `send`, adding its bytes to the report
+    fn counted(
+        bytes: &mut usize,
+        format: DataFormat<'_>,
+        send: impl FnOnce(DataFormat<'_>) -> core::result::Result<(), DisplayError>,
+    ) -> core::result::Result<(), DisplayError> {
+        match format {
+            DataFormat::U8(data) => {
+                *bytes += data.len();
+                send(DataFormat::U8(data))
+            }
+            DataFormat::U16(data) => {
+                *bytes += data.len() * 2;
+                send(DataFormat::U16(data))
+            }
+            DataFormat::U16BE(data) => {
+                *bytes += data.len() * 2;
+                send(DataFormat::U16BE(data))
+            }
+            DataFormat::U16LE(data) => {
+                *bytes += data.len() * 2;
+                send(DataFormat::U16LE(data))
+            }
+            DataFormat::U8Iter(data) => {
+                send(DataFormat::U8Iter(&mut data.inspect(|_| *bytes += 1)))
+            }
+            DataFormat::U16BEIter(data) => {
+                send(DataFormat::U16BEIter(&mut data.inspect(|_| *bytes += 2)))
+            }
+            DataFormat::U16LEIter(data) => {
+                send(DataFormat::U16LEIter(&mut data.inspect(|_| *bytes += 2)))
+            }
+            format => send(format),
+        }
+    }
+
+    /// Remember the size of the window from its address parameters
+    fn track_window(&mut self, data: &[u8]) {
+        let [start_high, start_low, end_high, end_low] = data else {
+            return;
+        };
+        let start = usize::from(u16::from_be_bytes([*start_high, *start_low]));
+        let end = usize::from(u16::from_be_bytes([*end_high, *end_low]));
+        let size = (end + 1).saturating_sub(start);
+        match self.command {
+            Some(c) if c == Instruction::ColumnAddressSet as u8 => self.columns = size,
+            Some(c) if c == Instruction::PageAddressSet as u8 => self.pages = size,
+            _ => {}
+        }
+    }
+}
+
+impl<DI: WriteOnlyDataCommand> WriteOnlyDataCommand for CountingInterface<DI> {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> core::result::Result<(), DisplayError> {
+        if let DataFormat::U8(&[command]) = cmd {
+            self.command = Some(command);
+            if command == Instruction::MemoryWrite as u8 {
+                self.report.pixels += self.columns * self.pages;
+            }
+        }
+        self.report.transactions += 1;
+        Self::counted(&mut self.report.bytes, cmd, |cmd| {
+            self.interface.send_commands(cmd)
+        })
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> core::result::Result<(), DisplayError> {
+        if let DataFormat::U8(data) = buf {
+            self.track_window(data);
+        }
+        self.report.transactions += 1;
+        Self::counted(&mut self.report.bytes, buf, |buf| {
+            self.interface.send_data(buf)
+        })
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<CountingInterface<IFACE>, RESET, PixelFormat, BURST, DELAY>
+where
+    IFACE: WriteOnlyDataCommand,
+{
+    /// Run `f` and report the traffic it caused
+    pub fn bench<R, F>(&mut self, f: F) -> Result<(R, BenchReport)>
+    where
+        F: FnOnce(&mut Self) -> Result<R>,
+    {
+        self.interface.reset();
+        let result = f(self)?;
+        Ok((result, self.interface.reset()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, MockInterface};
+    use crate::{Orientation, Rgb111, Rgb111Mode, Rgb666Mode};
+    use embedded_graphics_core::pixelcolor::{Rgb666, RgbColor};
+
+    #[test]
+    fn counts_traffic() {
+        let mut display = mock::display_on(
+            CountingInterface::new(MockInterface::new()),
+            Orientation::Portrait,
+            Rgb666Mode,
+        );
+
+        let ((), report) = display
+            .bench(|display| display.draw_raw_slice(0, 0, 9, 9, &[Rgb666::RED; 100]))
+            .unwrap();
+        assert_eq!(report.pixels, 100);
+        // Window and memory write commands with their parameters, then 3 bytes per pixel
+        assert_eq!(report.bytes, 3 + 2 * 4 + 100 * 3);
+        assert_eq!(report.bytes_per_second(500_000), 622);
+
+        let mut display = display.change_pixel_format(Rgb111Mode).unwrap();
+        let ((), report) = display
+            .bench(|display| display.clear_screen(Rgb111::BLUE))
+            .unwrap();
+        assert_eq!(report.pixels, 320 * 480);
+        assert_eq!(report.bytes, 3 + 2 * 4 + 320 * 480 / 2);
+        assert_eq!(display.interface().report(), BenchReport::default());
+    }
+}
@@ -0,0 +1,116 @@
+//! Drawing images block by block as an external streaming decoder outputs them
+//!
+//! This module does not decode anything itself. Streaming image decoders, e.g. JPEG decoders
+//! like TJpgDec, only keep one block of pixels (for JPEG an MCU of 8x8 or 16x16) in memory
+//! and hand every decoded block to an output callback. [BlockWriter::write_block] writes
+//! such a block to its own window, so photos can be shown from an SD card on MCUs with
+//! little RAM, without a frame buffer.
+//!
+//! ```ignore
+//! let mut writer = display.block_writer(Point::new(0, 0));
+//! decoder.decompress(&mut file, |rect, rgb| {
+//!     writer.write_block(rect.top_left, rect.size, rgb)
+//! })?;
+//! ```
+use display_interface::{DisplayError, WriteOnlyDataCommand};
+use embedded_graphics_core::pixelcolor::Rgb666;
+use embedded_graphics_core::prelude::{Dimensions, Point, Size};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::{Ili9488, NoDelay, Result, Rgb666Mode};
+
+/// Writes decoded image blocks to the display, obtained with [Ili9488::block_writer]
+pub struct BlockWriter<'a, IFACE, RESET, const BURST: usize = 64, DELAY = NoDelay> {
+    display: &'a mut Ili9488<IFACE, RESET, Rgb666Mode, BURST, DELAY>,
+    origin: Point,
+}
+
+impl<IFACE, RESET, const BURST: usize, DELAY> Ili9488<IFACE, RESET, Rgb666Mode, BURST, DELAY>
+where
+    IFACE: WriteOnlyDataCommand,
+{
+    /// Start drawing a decoded image with its top left corner at `origin`, see [BlockWriter]
+    pub fn block_writer(&mut self, origin: Point) -> BlockWriter<'_, IFACE, RESET, BURST, DELAY> {
+        BlockWriter {
+            display: self,
+            origin,
+        }
+    }
+}
+
+impl<IFACE, RESET, const BURST: usize, DELAY> BlockWriter<'_, IFACE, RESET, BURST, DELAY>
+where
+    IFACE: WriteOnlyDataCommand,
+{
+    /// Write a decoded block of `size` pixels at `offset` in the image.
+    ///
+    /// `rgb` holds 3 bytes per pixel in RGB888, row by row, as output by the decoder. The
+    /// low two bits of every channel are dropped and parts of the block that are off screen
+    /// are skipped.
+    pub fn write_block(&mut self, offset: Point, size: Size, rgb: &[u8]) -> Result {
+        let width = size.width as usize;
+        if rgb.len() < width * size.height as usize * 3 {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        let block = Rectangle::new(self.origin + offset, size);
+        let visible = block.intersection(&self.display.bounding_box());
+        if visible.bottom_right().is_none() {
+            return Ok(());
+        }
+        let skip = visible.top_left - block.top_left;
+        let visible_width = visible.size.width as usize;
+        let pixels = (0..visible.size.height as usize)
+            .flat_map(|row| {
+                let start = ((skip.y as usize + row) * width + skip.x as usize) * 3;
+                rgb[start..start + visible_width * 3].chunks_exact(3)
+            })
+            .map(|c| Rgb666::new(c[0] >> 2, c[1] >> 2, c[2] >> 2));
+
+        let mut writer = self.display.begin_write(visible)?;
+        writer.push(pixels)?;
+        writer.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::sim;
+    use crate::{Orientation, Rgb666Mode};
+    use display_interface::DisplayError;
+    use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+    use embedded_graphics_core::prelude::{Point, Size};
+    use std::vec::Vec;
+
+    #[test]
+    fn blocks_in_own_windows() {
+        let mut display = sim::display_with(Orientation::Landscape, Rgb666Mode);
+        // 8x8 block, red left half, blue right half
+        let block: Vec<u8> = (0..64)
+            .flat_map(|i| match i % 8 < 4 {
+                true => [0xff, 0, 0],
+                false => [0, 0, 0xff],
+            })
+            .collect();
+
+        let mut writer = display.block_writer(Point::new(468, 0));
+        writer
+            .write_block(Point::new(0, 0), Size::new(8, 8), &block)
+            .unwrap();
+        // Only the red half of the second block is on screen
+        writer
+            .write_block(Point::new(8, 8), Size::new(8, 8), &block)
+            .unwrap();
+        assert!(matches!(
+            writer.write_block(Point::zero(), Size::new(8, 8), &block[..100]),
+            Err(DisplayError::OutOfBoundsError)
+        ));
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(468, 0), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(475, 7), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(476, 8), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(479, 15), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(476, 16), Some(Rgb888::BLACK));
+    }
+}
@@ -0,0 +1,328 @@
+//! Display Command Set (DCS) commands of the ILI9488
+//!
+//! Every standard command is a typed struct implementing [DcsCommand], which knows the
+//! instruction byte and how to lay out its parameters. They can be sent with
+//! [Ili9488::write_command](crate::Ili9488::write_command) to compose custom sequences:
+//!
+//! ```ignore
+//! use ili9488_rs::dcs::{SetColumnAddress, SetPageAddress, WriteMemoryStart};
+//!
+//! display.write_command(SetColumnAddress::new(0, 9))?;
+//! display.write_command(SetPageAddress::new(0, 9))?;
+//! display.write_command(WriteMemoryStart)?;
+//! ```
+//!
+//! Manufacturer commands without a typed struct are listed in [Instruction] and can be sent
+//! with [Ili9488::write_raw](crate::Ili9488::write_raw).
+
+/// Maximum number of parameter bytes of a [DcsCommand]
+pub const MAX_PARAMS: usize = 16;
+
+/// A command with its parameters
+pub trait DcsCommand {
+    /// The instruction byte
+    fn instruction(&self) -> u8;
+
+    /// Write the parameters to the start of `buffer`, which holds [MAX_PARAMS] bytes,
+    /// and return how many were written
+    fn fill_params_buf(&self, buffer: &mut [u8]) -> usize;
+}
+
+/// Instruction bytes of all commands used by the driver
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum Instruction {
+    Nop = 0x00,
+    SoftwareReset = 0x01,
+    ReadDisplayId = 0x04,
+    ReadDisplayStatus = 0x09,
+    ReadSelfDiagnostic = 0x0f,
+    SleepModeOn = 0x10,
+    SleepModeOff = 0x11,
+    InvertOff = 0x20,
+    InvertOn = 0x21,
+    DisplayOff = 0x28,
+    DisplayOn = 0x29,
+    ColumnAddressSet = 0x2a,
+    PageAddressSet = 0x2b,
+    MemoryWrite = 0x2c,
+    MemoryRead = 0x2e,
+    VerticalScrollDefine = 0x33,
+    TearingEffectLineOff = 0x34,
+    TearingEffectLineOn = 0x35,
+    MemoryAccessControl = 0x36,
+    VerticalScrollAddr = 0x37,
+    IdleModeOff = 0x38,
+    IdleModeOn = 0x39,
+    PixelFormatSet = 0x3a,
+    MemoryWriteContinue = 0x3c,
+    MemoryReadContinue = 0x3e,
+    SetBrightness = 0x51,
+    WriteCtrlDisplay = 0x53,
+    ContentAdaptiveBrightness = 0x55,
+    InterfaceModeControl = 0xb0,
+    NormalModeFrameRate = 0xb1,
+    IdleModeFrameRate = 0xb2,
+    DisplayInversionControl = 0xb4,
+    BlankingPorchControl = 0xb5,
+    DisplayFunctionControl = 0xb6,
+    EntryModeSet = 0xb7,
+    PowerControl1 = 0xc0,
+    PowerControl2 = 0xc1,
+    VCOMControl = 0xc5,
+    ReadId4 = 0xd3,
+    PositiveGammaControl = 0xe0,
+    NegativeGammaControl = 0xe1,
+    AdjustControl3 = 0xf7,
+}
+
+macro_rules! parameterless {
+    ($($(#[$meta:meta])* $name:ident = $instruction:ident,)*) => {
+        $(
+            $(#[$meta])*
+            #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+            #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+            pub struct $name;
+
+            impl DcsCommand for $name {
+                fn instruction(&self) -> u8 {
+                    Instruction::$instruction as u8
+                }
+
+                fn fill_params_buf(&self, _buffer: &mut [u8]) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+parameterless! {
+    /// No operation, also ends a memory write
+    Nop = Nop,
+    /// Reset all registers to their defaults, wait 5 ms before the next command
+    SoftReset = SoftwareReset,
+    /// Enter sleep mode, wait 5 ms before the next command
+    EnterSleepMode = SleepModeOn,
+    /// Leave sleep mode, wait 120 ms before the next command
+    ExitSleepMode = SleepModeOff,
+    /// Turn display inversion off
+    ExitInvertMode = InvertOff,
+    /// Turn display inversion on
+    EnterInvertMode = InvertOn,
+    /// Stop showing the frame memory
+    SetDisplayOff = DisplayOff,
+    /// Show the frame memory
+    SetDisplayOn = DisplayOn,
+    /// Start writing pixels at the top left corner of the address window
+    WriteMemoryStart = MemoryWrite,
+    /// Continue writing pixels where the last write stopped
+    WriteMemoryContinue = MemoryWriteContinue,
+    /// Start reading pixels at the top left corner of the address window
+    ReadMemoryStart = MemoryRead,
+    /// Continue reading pixels where the last read stopped
+    ReadMemoryContinue = MemoryReadContinue,
+    /// Leave idle mode
+    ExitIdleMode = IdleModeOff,
+    /// Enter idle mode, which shows 8 colors
+    EnterIdleMode = IdleModeOn,
+}
+
+/// Write `values` as big endian words to `buffer`
+fn fill_u16s(buffer: &mut [u8], values: &[u16]) -> usize {
+    for (chunk, value) in buffer.chunks_exact_mut(2).zip(values) {
+        chunk.copy_from_slice(&value.to_be_bytes());
+    }
+    2 * values.len()
+}
+
+/// Set the first and last column of the address window
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetColumnAddress {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl SetColumnAddress {
+    pub const fn new(start: u16, end: u16) -> Self {
+        Self { start, end }
+    }
+}
+
+impl DcsCommand for SetColumnAddress {
+    fn instruction(&self) -> u8 {
+        Instruction::ColumnAddressSet as u8
+    }
+
+    fn fill_params_buf(&self, buffer: &mut [u8]) -> usize {
+        fill_u16s(buffer, &[self.start, self.end])
+    }
+}
+
+/// Set the first and last page (row) of the address window
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetPageAddress {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl SetPageAddress {
+    pub const fn new(start: u16, end: u16) -> Self {
+        Self { start, end }
+    }
+}
+
+impl DcsCommand for SetPageAddress {
+    fn instruction(&self) -> u8 {
+        Instruction::PageAddressSet as u8
+    }
+
+    fn fill_params_buf(&self, buffer: &mut [u8]) -> usize {
+        fill_u16s(buffer, &[self.start, self.end])
+    }
+}
+
+/// Define the fixed top, scrolling and fixed bottom areas for vertical scrolling
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetScrollArea {
+    pub fixed_top_lines: u16,
+    pub scroll_lines: u16,
+    pub fixed_bottom_lines: u16,
+}
+
+impl SetScrollArea {
+    pub const fn new(fixed_top_lines: u16, scroll_lines: u16, fixed_bottom_lines: u16) -> Self {
+        Self {
+            fixed_top_lines,
+            scroll_lines,
+            fixed_bottom_lines,
+        }
+    }
+}
+
+impl DcsCommand for SetScrollArea {
+    fn instruction(&self) -> u8 {
+        Instruction::VerticalScrollDefine as u8
+    }
+
+    fn fill_params_buf(&self, buffer: &mut [u8]) -> usize {
+        fill_u16s(
+            buffer,
+            &[
+                self.fixed_top_lines,
+                self.scroll_lines,
+                self.fixed_bottom_lines,
+            ],
+        )
+    }
+}
+
+/// Set the line of the frame memory shown at the top of the scroll area
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetScrollStart(pub u16);
+
+impl DcsCommand for SetScrollStart {
+    fn instruction(&self) -> u8 {
+        Instruction::VerticalScrollAddr as u8
+    }
+
+    fn fill_params_buf(&self, buffer: &mut [u8]) -> usize {
+        fill_u16s(buffer, &[self.0])
+    }
+}
+
+/// Set the memory access control (MADCTL) bits, see [Mode](crate::Mode)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetAddressMode(pub u8);
+
+impl DcsCommand for SetAddressMode {
+    fn instruction(&self) -> u8 {
+        Instruction::MemoryAccessControl as u8
+    }
+
+    fn fill_params_buf(&self, buffer: &mut [u8]) -> usize {
+        buffer[0] = self.0;
+        1
+    }
+}
+
+/// Set the interface pixel format, see [Ili9488PixelFormat::DATA](crate::Ili9488PixelFormat::DATA)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetPixelFormat(pub u8);
+
+impl DcsCommand for SetPixelFormat {
+    fn instruction(&self) -> u8 {
+        Instruction::PixelFormatSet as u8
+    }
+
+    fn fill_params_buf(&self, buffer: &mut [u8]) -> usize {
+        buffer[0] = self.0;
+        1
+    }
+}
+
+/// Set the display brightness, 0 is the darkest
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetDisplayBrightness(pub u8);
+
+impl DcsCommand for SetDisplayBrightness {
+    fn instruction(&self) -> u8 {
+        Instruction::SetBrightness as u8
+    }
+
+    fn fill_params_buf(&self, buffer: &mut [u8]) -> usize {
+        buffer[0] = self.0;
+        1
+    }
+}
+
+/// Write CTRL Display: brightness control block (BCTRL, 0x20), display dimming (DD, 0x08)
+/// and backlight (BL, 0x04)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WriteCtrlDisplay(pub u8);
+
+impl DcsCommand for WriteCtrlDisplay {
+    fn instruction(&self) -> u8 {
+        Instruction::WriteCtrlDisplay as u8
+    }
+
+    fn fill_params_buf(&self, buffer: &mut [u8]) -> usize {
+        buffer[0] = self.0;
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode<C: DcsCommand>(command: C) -> (u8, std::vec::Vec<u8>) {
+        let mut buffer = [0; MAX_PARAMS];
+        let len = command.fill_params_buf(&mut buffer);
+        (command.instruction(), buffer[..len].to_vec())
+    }
+
+    #[test]
+    fn parameter_layout() {
+        assert_eq!(encode(SetDisplayOn), (0x29, std::vec![]));
+        assert_eq!(
+            encode(SetColumnAddress::new(0x0102, 0x013f)),
+            (0x2a, std::vec![0x01, 0x02, 0x01, 0x3f])
+        );
+        assert_eq!(
+            encode(SetScrollArea::new(10, 460, 10)),
+            (0x33, std::vec![0, 10, 0x01, 0xcc, 0, 10])
+        );
+        assert_eq!(encode(SetScrollStart(300)), (0x37, std::vec![0x01, 0x2c]));
+        assert_eq!(encode(SetAddressMode(0x48)), (0x36, std::vec![0x48]));
+    }
+}
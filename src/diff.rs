@@ -0,0 +1,215 @@
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_graphics_core::prelude::{Dimensions, OriginDimensions, Point};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::dcs::WriteMemoryStart;
+use crate::{Ili9488, Ili9488FrameBuf, Result, Rgb666Mode};
+
+/// What a [DiffFlusher] remembers of the previous frame
+enum Shadow<'a> {
+    /// A copy of the frame, 3 bytes per pixel
+    Frame(&'a mut [u8]),
+    /// A hash of every row
+    RowHashes(&'a mut [u32]),
+}
+
+/// Flushes [Ili9488FrameBuf]s by only sending what changed since the previous flush,
+/// for mostly static screens like dashboards.
+///
+/// With a copy of the previous frame only the changed span of every row is sent. With row
+/// hashes, 4 bytes per row instead of 3 per pixel, changed rows are sent whole. A change
+/// that leaves the hash of its row the same is missed, which is very unlikely.
+///
+/// ```ignore
+/// static mut FRAME: Ili9488FrameBuf<240, 100> = Ili9488FrameBuf::new();
+/// let mut hashes = [0; 100];
+/// let mut flusher = DiffFlusher::with_row_hashes(&mut hashes);
+/// loop {
+///     draw_dashboard(&mut frame)?;
+///     flusher.flush(&mut display, &frame, 0, 0)?;
+/// }
+/// ```
+pub struct DiffFlusher<'a> {
+    shadow: Shadow<'a>,
+    /// The shadow matches what is on the display
+    valid: bool,
+}
+
+impl<'a> DiffFlusher<'a> {
+    /// Keep a copy of the previous frame in `shadow`, which must hold 3 bytes per pixel
+    pub fn new(shadow: &'a mut [u8]) -> Self {
+        Self {
+            shadow: Shadow::Frame(shadow),
+            valid: false,
+        }
+    }
+
+    /// Keep a hash of every row of the previous frame in `hashes`, which must hold one per row
+    pub fn with_row_hashes(hashes: &'a mut [u32]) -> Self {
+        Self {
+            shadow: Shadow::RowHashes(hashes),
+            valid: false,
+        }
+    }
+
+    /// Send the whole frame on the next flush, e.g. after drawing over it on the display
+    pub fn invalidate(&mut self) {
+        self.valid = false;
+    }
+
+    /// Write what changed in `frame` to the display with its top left corner at (x, y),
+    /// returns the number of rows sent.
+    ///
+    /// The first flush, and the first after [DiffFlusher::invalidate], sends everything.
+    /// Fails with [DisplayError::OutOfBoundsError] when the frame is not on screen or the
+    /// shadow is too small for it.
+    pub fn flush<IFACE, RESET, const BURST: usize, DELAY, const W: usize, const H: usize>(
+        &mut self,
+        display: &mut Ili9488<IFACE, RESET, Rgb666Mode, BURST, DELAY>,
+        frame: &Ili9488FrameBuf<W, H>,
+        x: u16,
+        y: u16,
+    ) -> Result<usize>
+    where
+        IFACE: WriteOnlyDataCommand,
+    {
+        let area = Rectangle::new(Point::new(x.into(), y.into()), frame.size());
+        if display.bounding_box().intersection(&area) != area {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        let fits = match &self.shadow {
+            Shadow::Frame(shadow) => shadow.len() >= W * H * 3,
+            Shadow::RowHashes(hashes) => hashes.len() >= H,
+        };
+        if !fits {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+
+        let mut sent = 0;
+        for (row, bytes) in frame.as_bytes().chunks_exact(W * 3).enumerate() {
+            let span = match &mut self.shadow {
+                Shadow::Frame(shadow) => {
+                    let previous = &mut shadow[row * W * 3..(row + 1) * W * 3];
+                    let span = match self.valid {
+                        true => changed_span(previous, bytes),
+                        false => Some((0, W - 1)),
+                    };
+                    previous.copy_from_slice(bytes);
+                    span
+                }
+                Shadow::RowHashes(hashes) => {
+                    let hash = fnv1a(bytes);
+                    let changed = !self.valid || hashes[row] != hash;
+                    hashes[row] = hash;
+                    changed.then_some((0, W - 1))
+                }
+            };
+            let Some((x0, x1)) = span else {
+                continue;
+            };
+            let (left, top) = (x + x0 as u16, y + row as u16);
+            let result = display
+                .set_window(left, top, x + x1 as u16, top)
+                .and_then(|_| display.write_command(WriteMemoryStart))
+                .and_then(|_| {
+                    display
+                        .interface
+                        .send_data(DataFormat::U8(&bytes[x0 * 3..(x1 + 1) * 3]))
+                });
+            if let Err(error) = result {
+                // The shadow is ahead of the display now
+                self.valid = false;
+                return Err(error);
+            }
+            sent += 1;
+        }
+        self.valid = true;
+        Ok(sent)
+    }
+}
+
+/// First and last pixel that differ between two rows of 3 bytes per pixel
+fn changed_span(previous: &[u8], current: &[u8]) -> Option<(usize, usize)> {
+    let mut pixels = previous.chunks_exact(3).zip(current.chunks_exact(3));
+    let first = pixels.position(|(a, b)| a != b)?;
+    let last = pixels
+        .rposition(|(a, b)| a != b)
+        .map_or(first, |i| first + 1 + i);
+    Some((first, last))
+}
+
+/// 32-bit FNV-1a hash
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, &byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use embedded_graphics_core::pixelcolor::{Rgb666, RgbColor};
+    use embedded_graphics_core::prelude::{DrawTarget, Pixel};
+    use std::vec;
+
+    #[test]
+    fn sends_changed_spans() {
+        let mut display = mock::display();
+        let mut frame = Ili9488FrameBuf::<8, 4>::new();
+        let mut shadow = [0; 8 * 4 * 3];
+        let mut flusher = DiffFlusher::new(&mut shadow);
+        assert!(matches!(flusher.flush(&mut display, &frame, 10, 20), Ok(4)));
+        assert!(matches!(flusher.flush(&mut display, &frame, 10, 20), Ok(0)));
+
+        frame
+            .draw_iter([
+                Pixel(Point::new(3, 1), Rgb666::RED),
+                Pixel(Point::new(5, 1), Rgb666::BLUE),
+            ])
+            .unwrap();
+        display.interface.clear();
+        assert!(matches!(flusher.flush(&mut display, &frame, 10, 20), Ok(1)));
+        assert_eq!(
+            display.interface.commands(),
+            [
+                (0x2a, vec![0, 13, 0, 15]),
+                (0x2b, vec![0, 21, 0, 21]),
+                (0x2c, vec![0xfc, 0, 0, 0, 0, 0, 0, 0, 0xfc]),
+            ]
+        );
+
+        flusher.invalidate();
+        assert!(matches!(flusher.flush(&mut display, &frame, 10, 20), Ok(4)));
+        assert!(matches!(
+            flusher.flush(&mut display, &frame, 315, 0),
+            Err(DisplayError::OutOfBoundsError)
+        ));
+    }
+
+    #[test]
+    fn sends_changed_rows() {
+        let mut display = mock::display();
+        let mut frame = Ili9488FrameBuf::<8, 4>::new();
+        let mut hashes = [0; 4];
+        let mut flusher = DiffFlusher::with_row_hashes(&mut hashes);
+        assert!(matches!(flusher.flush(&mut display, &frame, 0, 0), Ok(4)));
+
+        frame
+            .draw_iter([Pixel(Point::new(7, 3), Rgb666::GREEN)])
+            .unwrap();
+        display.interface.clear();
+        assert!(matches!(flusher.flush(&mut display, &frame, 0, 0), Ok(1)));
+        // Same window as the last row of the first flush
+        assert_eq!(
+            display.interface.commands(),
+            [(0x2c, [vec![0; 21], vec![0, 0xfc, 0]].concat())]
+        );
+
+        let mut small = [0; 3];
+        assert!(matches!(
+            DiffFlusher::with_row_hashes(&mut small).flush(&mut display, &frame, 0, 0),
+            Err(DisplayError::OutOfBoundsError)
+        ));
+    }
+}
@@ -0,0 +1,104 @@
+use display_interface::{DisplayError, WriteOnlyDataCommand};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::dcs::WriteMemoryStart;
+use crate::{Ili9488, InterfaceRef, Result, Rgb666Mode};
+
+/// Display interfaces that can send a whole buffer without copying it, e.g. with DMA
+///
+/// The driver hands every buffer over in one piece and never splits it into chunks, so the
+/// implementation can start a single transfer straight from it.
+pub trait DmaDataCommand: WriteOnlyDataCommand {
+    /// Send all of `buffer` as data and give it back once the transfer is done
+    fn send_buffer(&mut self, buffer: &'static mut [u8]) -> Result<&'static mut [u8], BufferError>;
+}
+
+impl<IFACE: DmaDataCommand> DmaDataCommand for InterfaceRef<'_, IFACE> {
+    fn send_buffer(&mut self, buffer: &'static mut [u8]) -> Result<&'static mut [u8], BufferError> {
+        self.0.send_buffer(buffer)
+    }
+}
+
+/// Error of a buffer transfer, giving the buffer back
+#[derive(Debug)]
+pub struct BufferError {
+    /// What went wrong
+    pub error: DisplayError,
+    /// The buffer that was passed in
+    pub buffer: &'static mut [u8],
+}
+
+impl<IFACE, RESET, const BURST: usize, DELAY> Ili9488<IFACE, RESET, Rgb666Mode, BURST, DELAY>
+where
+    IFACE: DmaDataCommand,
+{
+    /// Write `buffer` to `area`, 3 bytes per pixel in rows like
+    /// [Ili9488FrameBuf::as_bytes](crate::Ili9488FrameBuf::as_bytes).
+    ///
+    /// The buffer is passed to [DmaDataCommand::send_buffer] whole, after the window and
+    /// memory write command, and returned when the transfer is done. Fails with
+    /// [DisplayError::OutOfBoundsError] when its length does not match `area`.
+    ///
+    /// ```ignore
+    /// static mut FRAME: Ili9488FrameBuf<480, 320> = Ili9488FrameBuf::new();
+    ///
+    /// let frame = unsafe { &mut *core::ptr::addr_of_mut!(FRAME) }.into_wire();
+    /// let frame = display.write_buffer(display.bounding_box(), frame)?;
+    /// ```
+    pub fn write_buffer(
+        &mut self,
+        area: Rectangle,
+        buffer: &'static mut [u8],
+    ) -> Result<&'static mut [u8], BufferError> {
+        let len = area.size.width as usize * area.size.height as usize * 3;
+        if buffer.len() != len {
+            return Err(BufferError {
+                error: DisplayError::OutOfBoundsError,
+                buffer,
+            });
+        }
+        let started = self
+            .set_region_window(&area)
+            .and_then(|_| self.write_command(WriteMemoryStart));
+        match started {
+            Ok(()) => self.interface.send_buffer(buffer),
+            Err(error) => Err(BufferError { error, buffer }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, Transaction};
+    use crate::Ili9488FrameBuf;
+    use embedded_graphics_core::pixelcolor::{Rgb666, RgbColor};
+    use embedded_graphics_core::prelude::{DrawTarget, Point, Size};
+    use std::boxed::Box;
+
+    #[test]
+    fn write_buffer_whole() {
+        let mut display = mock::display();
+        display.interface.clear();
+
+        let frame = Box::leak(Box::new(Ili9488FrameBuf::<40, 30>::new()));
+        frame.clear(Rgb666::RED).unwrap();
+        let area = Rectangle::new(Point::new(10, 10), Size::new(40, 30));
+        let buffer = display.write_buffer(area, frame.into_wire()).unwrap();
+        assert_eq!(buffer.len(), 40 * 30 * 3);
+
+        // One transfer with all of it, right after the memory write command
+        let transactions = display.interface.transactions();
+        assert_eq!(transactions[4], Transaction::Command(std::vec![0x2c]));
+        assert_eq!(
+            transactions[6..],
+            [Transaction::Data([0xfc, 0, 0].repeat(40 * 30))]
+        );
+
+        let error = display
+            .write_buffer(Rectangle::new(Point::zero(), Size::new(40, 29)), buffer)
+            .unwrap_err();
+        assert!(matches!(error.error, DisplayError::OutOfBoundsError));
+        assert_eq!(error.buffer.len(), 40 * 30 * 3);
+    }
+}
@@ -0,0 +1,205 @@
+use display_interface::WriteOnlyDataCommand;
+use embedded_graphics_core::{pixelcolor::Rgb666, prelude::*, primitives::Rectangle};
+
+use crate::dcs::SetPixelFormat;
+use crate::{Ili9488, Ili9488PixelFormat, Result, Rgb111, Rgb111Mode, Rgb666Mode};
+
+/// Pixel formats that can be selected at runtime with [Ili9488Dyn::set_pixel_format]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DynPixelFormat {
+    /// 3 bpp, colors are reduced to [Rgb111] while drawing
+    Rgb111,
+    /// 18 bpp
+    Rgb666,
+}
+
+/// ILI9488 driver whose pixel format is selected at runtime
+///
+/// Unlike [Ili9488::change_pixel_format] the type of the driver doesn't change, so it can be
+/// kept in a `static` or a struct field while switching between 3 bpp and 18 bpp per frame.
+/// All drawing is done with [Rgb666] colors.
+///
+/// ```ignore
+/// let mut display = Ili9488Dyn::new(Ili9488::new(iface, reset, &mut delay, orientation, Rgb666Mode)?);
+///
+/// display.set_pixel_format(DynPixelFormat::Rgb111)?;
+/// display.clear(Rgb666::BLUE)?;
+/// display.set_pixel_format(DynPixelFormat::Rgb666)?;
+/// ```
+pub struct Ili9488Dyn<IFACE, RESET> {
+    display: Ili9488<IFACE, RESET, Rgb666Mode>,
+    pixel_format: DynPixelFormat,
+}
+
+impl<IFACE, RESET> Ili9488Dyn<IFACE, RESET>
+where
+    IFACE: WriteOnlyDataCommand,
+{
+    pub fn new(display: Ili9488<IFACE, RESET, Rgb666Mode>) -> Self {
+        Self {
+            display,
+            pixel_format: DynPixelFormat::Rgb666,
+        }
+    }
+
+    /// The active pixel format
+    pub fn pixel_format(&self) -> DynPixelFormat {
+        self.pixel_format
+    }
+
+    /// Switch the pixel format of the controller
+    pub fn set_pixel_format(&mut self, pixel_format: DynPixelFormat) -> Result {
+        let data = match pixel_format {
+            DynPixelFormat::Rgb111 => Rgb111Mode::DATA,
+            DynPixelFormat::Rgb666 => Rgb666Mode::DATA,
+        };
+        self.display.write_command(SetPixelFormat(data))?;
+        self.pixel_format = pixel_format;
+        Ok(())
+    }
+
+    /// Draw a rectangle on the screen, represented by top-left corner (x0, y0)
+    /// and bottom-right corner (x1, y1), in the active pixel format.
+    pub fn draw_raw_iter<I: IntoIterator<Item = Rgb666>>(
+        &mut self,
+        x0: u16,
+        y0: u16,
+        x1: u16,
+        y1: u16,
+        data: I,
+    ) -> Result {
+        match self.pixel_format {
+            DynPixelFormat::Rgb666 => self.display.draw_raw_iter(x0, y0, x1, y1, data),
+            DynPixelFormat::Rgb111 => self.display.with_view(Rgb111Mode, |display| {
+                display.draw_raw_iter(x0, y0, x1, y1, data.into_iter().map(Rgb111::from))
+            }),
+        }
+    }
+
+    /// Fill entire screen with specfied color
+    pub fn clear_screen(&mut self, color: Rgb666) -> Result {
+        let (width, height) = (self.display.width(), self.display.height());
+        self.draw_raw_iter(
+            0,
+            0,
+            width as u16 - 1,
+            height as u16 - 1,
+            core::iter::repeat_n(color, width * height),
+        )
+    }
+
+    /// Get the inner driver, switching the controller back to 18 bpp
+    pub fn into_inner(mut self) -> Result<Ili9488<IFACE, RESET, Rgb666Mode>> {
+        self.set_pixel_format(DynPixelFormat::Rgb666)?;
+        Ok(self.display)
+    }
+}
+
+impl<IFACE, RESET> core::ops::Deref for Ili9488Dyn<IFACE, RESET> {
+    type Target = Ili9488<IFACE, RESET, Rgb666Mode>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.display
+    }
+}
+
+impl<IFACE, RESET> OriginDimensions for Ili9488Dyn<IFACE, RESET> {
+    fn size(&self) -> Size {
+        self.display.size()
+    }
+}
+
+impl<IFACE, RESET> DrawTarget for Ili9488Dyn<IFACE, RESET>
+where
+    IFACE: WriteOnlyDataCommand,
+{
+    type Error = display_interface::DisplayError;
+
+    type Color = Rgb666;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if self.bounding_box().contains(point) {
+                let x = point.x as u16;
+                let y = point.y as u16;
+                let color = self.display.draw_color(color);
+                self.draw_raw_iter(x, y, x, y, [color])?;
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let drawable_area = area.intersection(&self.bounding_box());
+        let idle_safe_colors = self.display.state.idle_safe_colors;
+        let colors = colors.into_iter().map(|color| {
+            if idle_safe_colors {
+                crate::IdleSafePalette::quantize(color)
+            } else {
+                color
+            }
+        });
+
+        if let Some(drawable_bottom_right) = drawable_area.bottom_right() {
+            let x0 = drawable_area.top_left.x as u16;
+            let y0 = drawable_area.top_left.y as u16;
+            let x1 = drawable_bottom_right.x as u16;
+            let y1 = drawable_bottom_right.y as u16;
+
+            self.draw_raw_iter(
+                x0,
+                y0,
+                x1,
+                y1,
+                area.points()
+                    .zip(colors)
+                    .filter(|(point, _)| drawable_area.contains(*point))
+                    .map(|(_, color)| color),
+            )
+        } else {
+            // No pixels are on screen
+            Ok(())
+        }
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let color = self.display.draw_color(color);
+        self.clear_screen(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use std::vec;
+
+    #[test]
+    fn switches_pixel_format_at_runtime() {
+        let display = mock::display();
+        let mut display = Ili9488Dyn::new(display);
+        display.display.interface.clear();
+
+        display.set_pixel_format(DynPixelFormat::Rgb111).unwrap();
+        display
+            .draw_raw_iter(0, 0, 1, 0, [Rgb666::new(32, 0, 63), Rgb666::new(0, 40, 0)])
+            .unwrap();
+        display.set_pixel_format(DynPixelFormat::Rgb666).unwrap();
+        display
+            .draw_raw_iter(0, 0, 0, 0, [Rgb666::new(32, 0, 63)])
+            .unwrap();
+
+        let commands = display.interface().commands();
+        assert_eq!(commands[0], (0x3a, vec![0x01]));
+        assert_eq!(commands[3], (0x2c, vec![0b101_010]));
+        assert_eq!(commands[4], (0x3a, vec![0x66]));
+        assert_eq!(commands[7], (0x2c, vec![0x80, 0x00, 0xfc]));
+    }
+}
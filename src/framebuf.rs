@@ -0,0 +1,171 @@
+use core::convert::Infallible;
+
+use display_interface::{DataFormat, WriteOnlyDataCommand};
+use embedded_graphics_core::pixelcolor::Rgb666;
+use embedded_graphics_core::prelude::{
+    Dimensions, DrawTarget, OriginDimensions, Pixel, RgbColor, Size,
+};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::dcs::WriteMemoryStart;
+use crate::{Ili9488, Result, Rgb666Mode};
+
+/// Frame buffer of `W` x `H` pixels, stored as they are sent to the display in [Rgb666Mode].
+///
+/// Colors are converted while drawing, so [Ili9488FrameBuf::flush] only copies bytes.
+/// A full screen buffer takes 450 KiB, smaller buffers can be flushed to a part of the screen.
+///
+/// ```ignore
+/// static mut FRAME: Ili9488FrameBuf<480, 320> = Ili9488FrameBuf::new();
+///
+/// Circle::new(Point::new(10, 10), 100)
+///     .into_styled(PrimitiveStyle::with_fill(Rgb666::RED))
+///     .draw(&mut frame)?;
+/// frame.flush(&mut display)?;
+/// ```
+pub struct Ili9488FrameBuf<const W: usize, const H: usize> {
+    data: [[[u8; 3]; W]; H],
+}
+
+impl<const W: usize, const H: usize> Default for Ili9488FrameBuf<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> Ili9488FrameBuf<W, H> {
+    /// A black frame buffer
+    pub const fn new() -> Self {
+        Self {
+            data: [[[0; 3]; W]; H],
+        }
+    }
+
+    /// The pixels in wire format, 3 bytes per pixel in rows of `W` pixels
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data.as_flattened().as_flattened()
+    }
+
+    /// The pixels in wire format as a buffer for [Ili9488::write_buffer], no conversion is
+    /// needed as colors are converted while drawing
+    pub fn into_wire(&'static mut self) -> &'static mut [u8] {
+        self.data.as_flattened_mut().as_flattened_mut()
+    }
+
+    /// Write the frame buffer to the top left corner of the display
+    pub fn flush<IFACE, RESET, const BURST: usize, DELAY>(
+        &self,
+        display: &mut Ili9488<IFACE, RESET, Rgb666Mode, BURST, DELAY>,
+    ) -> Result
+    where
+        IFACE: WriteOnlyDataCommand,
+    {
+        self.flush_at(display, 0, 0)
+    }
+
+    /// Write the frame buffer to the display with its top left corner at (x, y)
+    pub fn flush_at<IFACE, RESET, const BURST: usize, DELAY>(
+        &self,
+        display: &mut Ili9488<IFACE, RESET, Rgb666Mode, BURST, DELAY>,
+        x: u16,
+        y: u16,
+    ) -> Result
+    where
+        IFACE: WriteOnlyDataCommand,
+    {
+        let region = Rectangle::new((x as i32, y as i32).into(), self.size());
+        display.set_region_window(&region)?;
+        display.write_command(WriteMemoryStart)?;
+        display.interface.send_data(DataFormat::U8(self.as_bytes()))
+    }
+}
+
+impl<const W: usize, const H: usize> OriginDimensions for Ili9488FrameBuf<W, H> {
+    fn size(&self) -> Size {
+        Size::new(W as u32, H as u32)
+    }
+}
+
+impl<const W: usize, const H: usize> DrawTarget for Ili9488FrameBuf<W, H> {
+    type Color = Rgb666;
+
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> core::result::Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y)) {
+                if x < W && y < H {
+                    self.data[y][x] = [color.r() << 2, color.g() << 2, color.b() << 2];
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(
+        &mut self,
+        area: &Rectangle,
+        color: Self::Color,
+    ) -> core::result::Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+        let Some(bottom_right) = area.bottom_right() else {
+            return Ok(());
+        };
+        let wire = [color.r() << 2, color.g() << 2, color.b() << 2];
+        let (x0, x1) = (area.top_left.x as usize, bottom_right.x as usize);
+        for row in &mut self.data[area.top_left.y as usize..=bottom_right.y as usize] {
+            row[x0..=x1].fill(wire);
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> core::result::Result<(), Self::Error> {
+        let wire = [color.r() << 2, color.g() << 2, color.b() << 2];
+        self.data.as_flattened_mut().fill(wire);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use embedded_graphics_core::prelude::Point;
+    use std::vec;
+
+    #[test]
+    fn draw_and_flush() {
+        let mut frame = Ili9488FrameBuf::<3, 2>::new();
+        frame.clear(Rgb666::BLUE).unwrap();
+        frame
+            .fill_solid(
+                &Rectangle::new(Point::new(1, 1), Size::new(5, 5)),
+                Rgb666::GREEN,
+            )
+            .unwrap();
+        frame
+            .draw_iter([
+                Pixel(Point::new(0, 0), Rgb666::RED),
+                Pixel(Point::new(3, 0), Rgb666::RED),
+            ])
+            .unwrap();
+
+        let mut display = mock::display();
+        display.interface.clear();
+        frame.flush_at(&mut display, 10, 20).unwrap();
+
+        let commands = display.interface.commands();
+        assert_eq!(commands[0], (0x2a, vec![0, 10, 0, 12]));
+        assert_eq!(commands[1], (0x2b, vec![0, 20, 0, 21]));
+        assert_eq!(
+            commands[2].1,
+            [
+                0xfc, 0, 0, 0, 0, 0xfc, 0, 0, 0xfc, //
+                0, 0, 0xfc, 0, 0xfc, 0, 0, 0xfc, 0,
+            ]
+        );
+    }
+}
@@ -0,0 +1,354 @@
+//! Gamma correction tables
+//!
+//! Positive and Negative Gamma Control take 15 values each, which set the voltages of the gray
+//! levels. Washed out, too dark or tinted panels can often be fixed by picking another table from
+//! [presets] instead of tuning the values by hand. Select one with [Ili9488Init::gamma] or
+//! [Ili9488::set_gamma_preset], it is sent again whenever the display is initialized.
+//!
+//! Boards with a fixed backlight can still be made more readable in sunlight by steepening
+//! the mid tones of the table with [Ili9488::set_display_contrast].
+//!
+//! ```ignore
+//! let display = Ili9488::begin_init(interface, reset, Orientation::Portrait, Rgb666Mode)
+//!     .gamma(presets::VIVID)
+//!     .finish(&mut delay)?;
+//! ```
+use display_interface::WriteOnlyDataCommand;
+
+use crate::dcs::Instruction;
+#[cfg(doc)]
+use crate::Ili9488Init;
+use crate::{Ili9488, Ili9488PixelFormat, InitOp, PanelVariant, Result};
+
+/// Contrast that leaves gamma tables as they are, see [Ili9488::set_display_contrast]
+pub const DEFAULT_CONTRAST: u8 = 128;
+
+/// Parameters of Positive (0xE0) and Negative (0xE1) Gamma Control
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GammaTable {
+    pub positive: [u8; 15],
+    pub negative: [u8; 15],
+}
+
+impl GammaTable {
+    /// The table with the mid tones spread by `contrast` / [DEFAULT_CONTRAST].
+    ///
+    /// The voltages of gray levels 20 and 43, and of 27 and 36, are moved apart from or
+    /// towards each other, the ends of the curve stay as they are. 0 makes the mid tones flat,
+    /// 255 almost doubles their steepness.
+    pub fn with_contrast(self, contrast: u8) -> Self {
+        Self {
+            positive: spread_mid_tones(self.positive, contrast),
+            negative: spread_mid_tones(self.negative, contrast),
+        }
+    }
+}
+
+fn spread_mid_tones(mut table: [u8; 15], contrast: u8) -> [u8; 15] {
+    let spread = |a: u8, b: u8, max: u8| {
+        let center = (i32::from(a) + i32::from(b)) / 2;
+        let scale = |value: u8| {
+            let value = center
+                + (i32::from(value) - center) * i32::from(contrast) / i32::from(DEFAULT_CONTRAST);
+            value.clamp(0, i32::from(max)) as u8
+        };
+        (scale(a), scale(b))
+    };
+    // VP43 and VP20 take 7 bits each
+    (table[6], table[8]) = spread(table[6], table[8], 0x7f);
+    // VP27 and VP36 share a parameter
+    let (vp27, vp36) = spread(table[7] >> 4, table[7] & 0x0f, 0x0f);
+    table[7] = (vp27 << 4) | vp36;
+    table
+}
+
+/// Gamma tables of common initialization code
+pub mod presets {
+    use super::GammaTable;
+
+    /// The table of TFT_eSPI, sent by [PanelVariant::TftEspi](crate::PanelVariant::TftEspi)
+    pub const TFT_ESPI: GammaTable = GammaTable {
+        positive: [
+            0x00, 0x03, 0x09, 0x08, 0x16, 0x0A, 0x3F, 0x78, 0x4C, 0x09, 0x0A, 0x08, 0x16, 0x1A,
+            0x0F,
+        ],
+        negative: [
+            0x00, 0x16, 0x19, 0x03, 0x0F, 0x05, 0x32, 0x45, 0x46, 0x04, 0x0E, 0x0D, 0x35, 0x37,
+            0x0F,
+        ],
+    };
+
+    /// The table of the ILI9488 example code of the datasheet, used by most vendor examples
+    /// like the one of [Profile::Msp3520](crate::profiles::Profile::Msp3520)
+    pub const DATASHEET: GammaTable = GammaTable {
+        positive: [
+            0x00, 0x07, 0x0F, 0x0D, 0x1B, 0x0A, 0x3C, 0x78, 0x4A, 0x07, 0x0E, 0x09, 0x1B, 0x1E,
+            0x0F,
+        ],
+        negative: [
+            0x00, 0x22, 0x24, 0x06, 0x12, 0x07, 0x36, 0x47, 0x47, 0x06, 0x0A, 0x07, 0x30, 0x37,
+            0x0F,
+        ],
+    };
+
+    /// A steeper curve with more saturated mid tones, the table of
+    /// [Profile::BuyDisplayParallel](crate::profiles::Profile::BuyDisplayParallel)
+    pub const VIVID: GammaTable = GammaTable {
+        positive: [
+            0x00, 0x04, 0x0E, 0x08, 0x17, 0x0A, 0x40, 0x79, 0x4D, 0x07, 0x0E, 0x0A, 0x1A, 0x1D,
+            0x0F,
+        ],
+        negative: [
+            0x00, 0x1B, 0x1F, 0x02, 0x10, 0x05, 0x32, 0x34, 0x43, 0x02, 0x0A, 0x09, 0x33, 0x37,
+            0x0F,
+        ],
+    };
+
+    /// Gray levels spread evenly, without the curve of the other tables, a neutral start for
+    /// tuning by hand
+    pub const LINEAR: GammaTable = GammaTable {
+        positive: [
+            0x00, 0x08, 0x10, 0x08, 0x10, 0x08, 0x40, 0x88, 0x48, 0x08, 0x10, 0x08, 0x10, 0x10,
+            0x0F,
+        ],
+        negative: [
+            0x00, 0x08, 0x10, 0x08, 0x10, 0x08, 0x40, 0x88, 0x48, 0x08, 0x10, 0x08, 0x10, 0x10,
+            0x0F,
+        ],
+    };
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+where
+    IFACE: WriteOnlyDataCommand,
+    PixelFormat: Ili9488PixelFormat,
+{
+    /// Send the gamma table `gamma`, e.g. one of [presets], and keep it for the next
+    /// initialization. The contrast of [Ili9488::set_display_contrast] is applied to it.
+    pub fn set_gamma_preset(&mut self, gamma: GammaTable) -> Result<&mut Self> {
+        self.send_gamma(&gamma)?;
+        self.state.gamma = Some(gamma);
+        Ok(self)
+    }
+
+    /// Steepen or flatten the mid tones of the gamma table, see [GammaTable::with_contrast].
+    ///
+    /// Applies to the table set with [Ili9488::set_gamma_preset], otherwise to the one in the
+    /// initialization sequence or board profile. [PanelVariant::NoVendorExt] and sequences
+    /// without Positive and Negative Gamma Control have no table to apply it to, the gamma of
+    /// the panel is left untouched until a preset is set. [DEFAULT_CONTRAST] restores the
+    /// table, and the contrast is kept for the next initialization.
+    pub fn set_display_contrast(&mut self, contrast: u8) -> Result<&mut Self> {
+        self.state.contrast = contrast;
+        if let Some(gamma) = self.base_gamma() {
+            self.send_gamma(&gamma)?;
+        }
+        Ok(self)
+    }
+
+    /// Contrast set with [Ili9488::set_display_contrast]
+    pub fn display_contrast(&self) -> u8 {
+        self.state.contrast
+    }
+
+    /// Send `gamma` with the contrast applied
+    pub(crate) fn send_gamma(&mut self, gamma: &GammaTable) -> Result {
+        let gamma = gamma.with_contrast(self.state.contrast);
+        self.command(Instruction::PositiveGammaControl, &gamma.positive)?;
+        self.command(Instruction::NegativeGammaControl, &gamma.negative)
+    }
+
+    /// The gamma table the panel was initialized with, before the contrast is applied, `None`
+    /// when it keeps its reset defaults
+    pub(crate) fn base_gamma(&self) -> Option<GammaTable> {
+        if self.state.gamma.is_some() {
+            return self.state.gamma;
+        }
+        let profile = match self.state.panel_variant {
+            PanelVariant::Profile(profile) => profile.init_table(),
+            _ => &[],
+        };
+        let sequence = self
+            .state
+            .panel_variant
+            .init_sequence()
+            .iter()
+            .filter_map(|op| match op {
+                InitOp::Command(command) => Some(command),
+                InitOp::Delay(_) => None,
+            });
+        let params = |instruction: Instruction| {
+            let command = profile
+                .iter()
+                .chain(sequence.clone())
+                .find(|command| command.instruction == instruction as u8)?;
+            command.params.try_into().ok()
+        };
+        Some(GammaTable {
+            positive: params(Instruction::PositiveGammaControl)?,
+            negative: params(Instruction::NegativeGammaControl)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockDelay, MockInterface, MockPin};
+    use crate::{Orientation, Rgb666Mode};
+    use std::vec::Vec;
+
+    /// The gamma tables the display got, in order
+    fn gamma_commands<D>(
+        display: &Ili9488<MockInterface, MockPin, Rgb666Mode, 64, D>,
+    ) -> Vec<Vec<u8>> {
+        let commands = display.interface().commands();
+        let gamma = [0xe0, 0xe1];
+        commands
+            .into_iter()
+            .filter(|(command, _)| gamma.contains(command))
+            .map(|(_, params)| params)
+            .collect()
+    }
+
+    #[test]
+    fn gamma_presets() {
+        let mut display = Ili9488::begin_init(
+            MockInterface::new(),
+            MockPin::default(),
+            Orientation::Portrait,
+            Rgb666Mode,
+        )
+        .gamma(presets::LINEAR)
+        .finish(&mut MockDelay::default())
+        .unwrap();
+        // After the table of the initialization sequence
+        assert_eq!(
+            gamma_commands(&display),
+            [
+                presets::TFT_ESPI.positive,
+                presets::TFT_ESPI.negative,
+                presets::LINEAR.positive,
+                presets::LINEAR.negative,
+            ]
+        );
+
+        display.interface.clear();
+        display.set_gamma_preset(presets::VIVID).unwrap();
+        assert_eq!(
+            gamma_commands(&display),
+            [presets::VIVID.positive, presets::VIVID.negative]
+        );
+
+        let mut display = display.with_delay(MockDelay::default());
+        display.interface.clear();
+        display.reinit().unwrap();
+        let gamma: Vec<_> = display
+            .interface()
+            .commands()
+            .into_iter()
+            .filter(|(command, _)| *command == 0xe0)
+            .map(|(_, params)| params)
+            .collect();
+        assert_eq!(gamma.last().unwrap(), &presets::VIVID.positive);
+    }
+
+    #[test]
+    fn display_contrast() {
+        assert_eq!(
+            presets::VIVID.with_contrast(DEFAULT_CONTRAST),
+            presets::VIVID
+        );
+        let flat = presets::TFT_ESPI.with_contrast(0);
+        // VP43 and VP20 meet in the middle, the ends stay
+        assert_eq!(flat.positive[6], flat.positive[8]);
+        assert_eq!(flat.positive[7] >> 4, flat.positive[7] & 0x0f);
+        assert_eq!(flat.positive[..6], presets::TFT_ESPI.positive[..6]);
+        assert_eq!(flat.positive[9..], presets::TFT_ESPI.positive[9..]);
+        let steep = presets::TFT_ESPI.with_contrast(255);
+        assert_eq!(
+            (steep.positive[6], steep.positive[7], steep.positive[8]),
+            (0x3a, 0x78, 0x52)
+        );
+
+        let mut display = Ili9488::begin_init(
+            MockInterface::new(),
+            MockPin::default(),
+            Orientation::Portrait,
+            Rgb666Mode,
+        )
+        .finish(&mut MockDelay::default())
+        .unwrap();
+        display.interface.clear();
+        display.set_display_contrast(255).unwrap();
+        display.set_display_contrast(255).unwrap();
+        // Applied to the default table, without adding up
+        assert_eq!(
+            gamma_commands(&display),
+            [
+                steep.positive,
+                steep.negative,
+                steep.positive,
+                steep.negative
+            ]
+        );
+        assert_eq!(display.display_contrast(), 255);
+
+        display.interface.clear();
+        display.set_gamma_preset(presets::VIVID).unwrap();
+        let mut display = display.with_delay(MockDelay::default());
+        display.reinit().unwrap();
+        let vivid = presets::VIVID.with_contrast(255);
+        assert_eq!(
+            gamma_commands(&display)[..2],
+            [vivid.positive, vivid.negative]
+        );
+        assert_eq!(
+            gamma_commands(&display).last(),
+            Some(&vivid.negative.to_vec())
+        );
+    }
+
+    #[test]
+    fn contrast_of_panel_variants() {
+        const SEQUENCE: &[InitOp] = &[
+            InitOp::Delay(5),
+            InitOp::command(Instruction::PositiveGammaControl, &presets::VIVID.positive),
+            InitOp::command(Instruction::NegativeGammaControl, &presets::VIVID.negative),
+        ];
+        const NO_GAMMA: &[InitOp] = &[InitOp::command(Instruction::PowerControl2, &[0x41])];
+        let init = |variant| {
+            Ili9488::begin_init(
+                MockInterface::new(),
+                MockPin::default(),
+                Orientation::Portrait,
+                Rgb666Mode,
+            )
+            .panel_variant(variant)
+            .finish(&mut MockDelay::default())
+            .unwrap()
+        };
+
+        // The table of a custom sequence
+        let mut display = init(PanelVariant::Sequence(SEQUENCE));
+        display.interface.clear();
+        display.set_display_contrast(255).unwrap();
+        let vivid = presets::VIVID.with_contrast(255);
+        assert_eq!(gamma_commands(&display), [vivid.positive, vivid.negative]);
+
+        // Without a table the panel keeps its gamma
+        for variant in [PanelVariant::NoVendorExt, PanelVariant::Sequence(NO_GAMMA)] {
+            let mut display = init(variant);
+            display.interface.clear();
+            display.set_display_contrast(255).unwrap();
+            assert!(gamma_commands(&display).is_empty());
+            assert_eq!(display.display_contrast(), 255);
+            let mut display = display.with_delay(MockDelay::default());
+            display.interface.clear();
+            display.reinit().unwrap();
+            assert!(gamma_commands(&display).is_empty());
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Golden tests of the bytes sent to the controller
+//!
+//! Every test records a transcript with [MockInterface::transcript] and compares it with a
+//! file in `src/golden`, so changes to what goes over the wire show up in review.
+//! Run `UPDATE_GOLDEN=1 cargo test-host` to write the files after an intended change.
+use std::format;
+use std::string::String;
+
+use embedded_graphics_core::pixelcolor::{Rgb565, Rgb666};
+
+use crate::mock;
+use crate::{Orientation, Rgb111, Rgb111Mode, Rgb565Mode, Rgb666Bus16Mode, Rgb666Mode};
+
+/// Compare `transcript` with the golden file `name`, or write it when `UPDATE_GOLDEN` is set
+fn assert_golden(name: &str, transcript: String) {
+    let path = format!("{}/src/golden/{name}.txt", env!("CARGO_MANIFEST_DIR"));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, &transcript).unwrap();
+        return;
+    }
+    let golden = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        golden == transcript,
+        "{name} differs from {path}:\n{transcript}"
+    );
+}
+
+#[test]
+fn new() {
+    let display = mock::display();
+    assert_golden("new", display.interface.transcript());
+}
+
+#[test]
+fn set_orientation() {
+    let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+    display.interface.clear();
+    for orientation in [
+        Orientation::Landscape,
+        Orientation::PortraitFlipped,
+        Orientation::LandscapeFlipped,
+        Orientation::Portrait,
+    ] {
+        display.set_orientation(orientation).unwrap();
+    }
+    assert_golden("set_orientation", display.interface.transcript());
+}
+
+#[test]
+fn set_window() {
+    let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+    display.interface.clear();
+    display.set_window(0, 0, 319, 479).unwrap();
+    display.set_window(0x0102, 0x0304, 0x0105, 0x0307).unwrap();
+    // Unchanged, nothing is sent
+    display.set_window(0x0102, 0x0304, 0x0105, 0x0307).unwrap();
+    assert_golden("set_window", display.interface.transcript());
+}
+
+#[test]
+fn write_rgb111() {
+    let mut display = mock::display_with(Orientation::Portrait, Rgb111Mode);
+    display.interface.clear();
+    let pixels = [Rgb111::RED, Rgb111::GREEN, Rgb111::BLUE];
+    display.draw_raw_slice(0, 0, 2, 0, &pixels).unwrap();
+    display.draw_raw_iter(0, 1, 2, 1, pixels).unwrap();
+    assert_golden("write_rgb111", display.interface.transcript());
+}
+
+#[test]
+fn write_rgb565() {
+    let mut display = mock::display_with(Orientation::Portrait, Rgb565Mode);
+    display.interface.clear();
+    let pixels = [
+        Rgb565::new(31, 0, 0),
+        Rgb565::new(0, 63, 0),
+        Rgb565::new(1, 2, 3),
+    ];
+    display.draw_raw_slice(0, 0, 2, 0, &pixels).unwrap();
+    display.draw_raw_iter(0, 1, 2, 1, pixels).unwrap();
+    assert_golden("write_rgb565", display.interface.transcript());
+}
+
+#[test]
+fn write_rgb666() {
+    let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+    display.interface.clear();
+    let pixels = [
+        Rgb666::new(63, 0, 0),
+        Rgb666::new(0, 63, 0),
+        Rgb666::new(1, 2, 3),
+    ];
+    display.draw_raw_slice(0, 0, 2, 0, &pixels).unwrap();
+    display.draw_raw_iter(0, 1, 2, 1, pixels).unwrap();
+    assert_golden("write_rgb666", display.interface.transcript());
+}
+
+#[test]
+fn write_rgb666_bus16() {
+    let mut display = mock::display_with(Orientation::Portrait, Rgb666Bus16Mode);
+    display.interface.clear();
+    let pixels = [
+        Rgb666::new(63, 0, 0),
+        Rgb666::new(0, 63, 0),
+        Rgb666::new(1, 2, 3),
+    ];
+    display.draw_raw_slice(0, 0, 2, 0, &pixels).unwrap();
+    display.draw_raw_iter(0, 1, 2, 1, pixels).unwrap();
+    assert_golden("write_rgb666_bus16", display.interface.transcript());
+}
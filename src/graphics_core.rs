@@ -1,36 +1,157 @@
-use crate::{Ili9488, Rgb565Mode, Rgb666Mode};
-use embedded_graphics_core::{
-    pixelcolor::{Rgb565, Rgb666},
-    prelude::*,
-    primitives::Rectangle,
-};
-
-impl<IFACE, RESET, PixelFormat> OriginDimensions for Ili9488<IFACE, RESET, PixelFormat> {
+use core::marker::PhantomData;
+
+use crate::{IdleSafePalette, Ili9488, Ili9488MemoryWrite, Ili9488PixelFormat, WireFormat};
+use embedded_graphics_core::{prelude::*, primitives::Rectangle};
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY> OriginDimensions
+    for Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+{
     fn size(&self) -> Size {
         Size::new(self.width() as u32, self.height() as u32)
     }
 }
 
-impl<IFACE, RESET> DrawTarget for Ili9488<IFACE, RESET, Rgb666Mode>
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+{
+    /// The color to draw for `color`, taking [Ili9488::set_idle_safe_colors] into account
+    pub(crate) fn draw_color<C: RgbColor>(&self, color: C) -> C {
+        if self.state.idle_safe_colors {
+            IdleSafePalette::quantize(color)
+        } else {
+            color
+        }
+    }
+}
+
+/// Adjacent pixels drawn in a straight horizontal or vertical line,
+/// or in raster order over several rows of the same width
+struct Run<C, const N: usize> {
+    start: Point,
+    end: Point,
+    direction: Option<Point>,
+    /// Last column of the rows, once the run continued on a second row
+    right: Option<i32>,
+    len: usize,
+    colors: [C; N],
+}
+
+impl<C: RgbColor, const N: usize> Run<C, N> {
+    fn new() -> Self {
+        Self {
+            start: Point::zero(),
+            end: Point::zero(),
+            direction: None,
+            right: None,
+            len: 0,
+            colors: [C::BLACK; N],
+        }
+    }
+
+    /// Add a pixel to the run, returns `false` if it does not continue the run
+    fn push(&mut self, point: Point, color: C) -> bool {
+        if self.len == 0 {
+            self.start = point;
+        } else {
+            if self.len == N {
+                return false;
+            }
+            let step = point - self.end;
+            let next_row = Point::new(self.start.x, self.end.y + 1);
+            if let Some(right) = self.right {
+                let continues = if self.end.x == right {
+                    point == next_row
+                } else {
+                    step == Point::new(1, 0)
+                };
+                if !continues {
+                    return false;
+                }
+            } else if point == next_row && self.direction == Some(Point::new(1, 0)) {
+                // The first row is complete, following rows must have the same width
+                self.right = Some(self.end.x);
+            } else if step.x.abs() + step.y.abs() != 1 {
+                return false;
+            } else {
+                match self.direction {
+                    Some(direction) if direction != step => return false,
+                    _ => self.direction = Some(step),
+                }
+            }
+        }
+        self.end = point;
+        self.colors[self.len] = color;
+        self.len += 1;
+        true
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
 where
     IFACE: display_interface::WriteOnlyDataCommand,
+    PixelFormat: Ili9488PixelFormat,
+    Self: Ili9488MemoryWrite,
+{
+    /// Write the pixels of `run` with a single window and empty it
+    fn flush_run(
+        &mut self,
+        run: &mut Run<<Self as Ili9488MemoryWrite>::PixelFormat, BURST>,
+    ) -> Result<(), display_interface::DisplayError> {
+        if run.len == 0 {
+            return Ok(());
+        }
+        let len = core::mem::replace(&mut run.len, 0);
+        run.direction = None;
+        let colors = &run.colors[..len];
+        if let Some(right) = run.right.take() {
+            // The last row can be incomplete, the display only fills what is written
+            let (x0, y0) = (run.start.x as u16, run.start.y as u16);
+            return self.draw_raw_slice(x0, y0, right as u16, run.end.y as u16, colors);
+        }
+        let x0 = run.start.x.min(run.end.x) as u16;
+        let y0 = run.start.y.min(run.end.y) as u16;
+        let x1 = run.start.x.max(run.end.x) as u16;
+        let y1 = run.start.y.max(run.end.y) as u16;
+        if run.end.x < run.start.x || run.end.y < run.start.y {
+            // Drawn right to left or bottom to top, the display always fills the window forward
+            self.draw_raw_iter(x0, y0, x1, y1, colors.iter().rev().copied())
+        } else {
+            self.draw_raw_slice(x0, y0, x1, y1, colors)
+        }
+    }
+}
+
+/// Drawing for pixel formats that write whole pixels to the display's memory
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY> DrawTarget
+    for Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+where
+    IFACE: display_interface::WriteOnlyDataCommand,
+    PixelFormat: WireFormat,
 {
     type Error = display_interface::DisplayError;
 
-    type Color = Rgb666;
+    type Color = PixelFormat::Color;
 
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
+        // Horizontal and vertical runs of pixels, like most of a line, and pixels in
+        // raster order, like a glyph, are written with one window instead of one window
+        // per pixel
+        let bounding_box = self.bounding_box();
+        let mut run = Run::<_, BURST>::new();
         for Pixel(point, color) in pixels {
-            if self.bounding_box().contains(point) {
-                let x = point.x as u16;
-                let y = point.y as u16;
-                self.draw_raw_slice(x, y, x, y, &[color])?;
+            if bounding_box.contains(point) {
+                let color = self.draw_color(color);
+                if !run.push(point, color) {
+                    self.flush_run(&mut run)?;
+                    run.push(point, color);
+                }
             }
         }
-        Ok(())
+        self.flush_run(&mut run)
     }
 
     fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
@@ -38,6 +159,14 @@ where
         I: IntoIterator<Item = Self::Color>,
     {
         let drawable_area = area.intersection(&self.bounding_box());
+        let idle_safe_colors = self.state.idle_safe_colors;
+        let colors = colors.into_iter().map(|color| {
+            if idle_safe_colors {
+                IdleSafePalette::quantize(color)
+            } else {
+                color
+            }
+        });
 
         if let Some(drawable_bottom_right) = drawable_area.bottom_right() {
             let x0 = drawable_area.top_left.x as u16;
@@ -81,9 +210,7 @@ where
             let x1 = drawable_bottom_right.x as u16;
             let y1 = drawable_bottom_right.y as u16;
 
-            let data = core::iter::repeat(color)
-                .take((drawable_area.size.width * drawable_area.size.height) as usize);
-            self.draw_raw_iter(x0, y0, x1, y1, data)
+            self.fill_rect(x0, y0, x1, y1, self.draw_color(color))
         } else {
             // No pixels are on screen
             Ok(())
@@ -91,73 +218,216 @@ where
     }
 
     fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
-        self.clear_screen(color)
+        self.clear_screen(self.draw_color(color))
     }
 }
 
-impl<IFACE, RESET> DrawTarget for Ili9488<IFACE, RESET, Rgb565Mode>
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
 where
-    IFACE: display_interface::WriteOnlyDataCommand,
+    Self: DrawTarget,
 {
-    type Error = display_interface::DisplayError;
+    /// Get a [DrawTarget] that accepts colors of type `C` and converts them to the display's
+    /// native color while drawing.
+    ///
+    /// This lets applications written for another color type, e.g. [Rgb565] for an ILI9341,
+    /// draw to an [Rgb666Mode] display without changes.
+    ///
+    /// ```ignore
+    /// let mut target = display.color_converted::<Rgb565>();
+    /// Circle::new(Point::new(10, 10), 20)
+    ///     .into_styled(PrimitiveStyle::with_fill(Rgb565::RED))
+    ///     .draw(&mut target)?;
+    /// ```
+    pub fn color_converted<C>(&mut self) -> ColorConverted<'_, Self, C>
+    where
+        C: PixelColor + Into<<Self as DrawTarget>::Color>,
+    {
+        ColorConverted {
+            target: self,
+            _color: PhantomData,
+        }
+    }
+}
+
+/// [DrawTarget] adapter converting colors of type `C` to the color of the wrapped target,
+/// obtained with [Ili9488::color_converted]
+pub struct ColorConverted<'a, D, C> {
+    target: &'a mut D,
+    _color: PhantomData<C>,
+}
 
-    type Color = Rgb565;
+impl<D, C> Dimensions for ColorConverted<'_, D, C>
+where
+    D: DrawTarget,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<D, C> DrawTarget for ColorConverted<'_, D, C>
+where
+    D: DrawTarget,
+    C: PixelColor + Into<D::Color>,
+{
+    type Color = C;
+
+    type Error = D::Error;
 
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
-        for Pixel(point, color) in pixels {
-            if self.bounding_box().contains(point) {
-                let x = point.x as u16;
-                let y = point.y as u16;
-                self.draw_raw_slice(x, y, x, y, &[color])?;
-            }
-        }
-        Ok(())
+        self.target.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, color)| Pixel(point, color.into())),
+        )
     }
 
     fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
     where
         I: IntoIterator<Item = Self::Color>,
     {
-        let drawable_area = area.intersection(&self.bounding_box());
+        self.target
+            .fill_contiguous(area, colors.into_iter().map(Into::into))
+    }
 
-        if let Some(drawable_bottom_right) = drawable_area.bottom_right() {
-            let x0 = drawable_area.top_left.x as u16;
-            let y0 = drawable_area.top_left.y as u16;
-            let x1 = drawable_bottom_right.x as u16;
-            let y1 = drawable_bottom_right.y as u16;
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.target.fill_solid(area, color.into())
+    }
 
-            if area == &drawable_area {
-                // All pixels are on screen
-                self.draw_raw_iter(
-                    x0,
-                    y0,
-                    x1,
-                    y1,
-                    area.points().zip(colors).map(|(_, color)| color),
-                )
-            } else {
-                // Some pixels are on screen
-                self.draw_raw_iter(
-                    x0,
-                    y0,
-                    x1,
-                    y1,
-                    area.points()
-                        .zip(colors)
-                        .filter(|(point, _)| drawable_area.contains(*point))
-                        .map(|(_, color)| color),
-                )
-            }
-        } else {
-            // No pixels are on screen
-            Ok(())
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.target.clear(color.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock;
+    use crate::sim;
+    use embedded_graphics_core::{
+        pixelcolor::{Rgb565, Rgb666, Rgb888},
+        prelude::*,
+        primitives::Rectangle,
+    };
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    fn color_converted_draws_rgb565_on_rgb666() {
+        let mut display = mock::display();
+        display.interface.clear();
+
+        let mut target = display.color_converted::<Rgb565>();
+        assert_eq!(target.bounding_box().size, Size::new(320, 480));
+        target
+            .fill_solid(
+                &Rectangle::new(Point::new(1, 1), Size::new(2, 1)),
+                Rgb565::RED,
+            )
+            .unwrap();
+
+        assert_eq!(
+            display.interface.commands()[2],
+            (0x2c, vec![0xfc, 0x00, 0x00, 0xfc, 0x00, 0x00])
+        );
+    }
+
+    #[test]
+    fn draw_iter_merges_runs() {
+        let mut display = mock::display();
+        display.interface.clear();
+
+        let pixels = [
+            (1, 1, Rgb666::WHITE),
+            (2, 1, Rgb666::WHITE),
+            (3, 1, Rgb666::WHITE),
+            (5, 4, Rgb666::RED),
+            (5, 3, Rgb666::GREEN),
+            (5, 2, Rgb666::BLUE),
+            (7, 7, Rgb666::WHITE),
+        ];
+        display
+            .draw_iter(
+                pixels
+                    .iter()
+                    .map(|&(x, y, color)| Pixel(Point::new(x, y), color)),
+            )
+            .unwrap();
+
+        let windows: Vec<_> = display
+            .interface
+            .commands()
+            .into_iter()
+            .filter(|(command, _)| *command == 0x2a || *command == 0x2b)
+            .map(|(_, data)| data)
+            .collect();
+        assert_eq!(
+            windows,
+            [
+                vec![0, 1, 0, 3],
+                vec![0, 1, 0, 1],
+                vec![0, 5, 0, 5],
+                vec![0, 2, 0, 4],
+                vec![0, 7, 0, 7],
+                vec![0, 7, 0, 7],
+            ]
+        );
+        // The bottom to top run is written top to bottom
+        assert_eq!(
+            display.interface.commands()[5],
+            (0x2c, vec![0, 0, 0xfc, 0, 0xfc, 0, 0xfc, 0, 0])
+        );
+    }
+
+    #[test]
+    fn draw_iter_merges_raster_order() {
+        let mut display = sim::display();
+
+        // A 3x3 block with the last pixel missing, then a pixel outside of it
+        let colors = [
+            Rgb666::RED,
+            Rgb666::GREEN,
+            Rgb666::BLUE,
+            Rgb666::WHITE,
+            Rgb666::CYAN,
+            Rgb666::MAGENTA,
+            Rgb666::YELLOW,
+            Rgb666::RED,
+        ];
+        let block = Rectangle::new(Point::new(4, 6), Size::new(3, 3));
+        display
+            .draw_iter(
+                block
+                    .points()
+                    .zip(colors)
+                    .map(|(point, color)| Pixel(point, color))
+                    .chain([Pixel(Point::new(4, 10), Rgb666::GREEN)]),
+            )
+            .unwrap();
+
+        let sim = display.interface();
+        let expected = block.points().zip(colors.map(Rgb888::from));
+        for (point, color) in expected {
+            assert_eq!(sim.pixel(point.x as usize, point.y as usize), Some(color));
         }
+        assert_eq!(sim.pixel(6, 8), Some(Rgb888::BLACK));
+        assert_eq!(sim.pixel(4, 10), Some(Rgb888::GREEN));
     }
 
-    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
-        self.clear_screen(color)
+    #[test]
+    fn burst_size() {
+        let mut display = mock::display().with_burst_size::<4>();
+        display.interface.clear();
+
+        display
+            .draw_iter((0..10).map(|x| Pixel(Point::new(x, 0), Rgb666::WHITE)))
+            .unwrap();
+        let writes = display.interface.commands();
+        let writes: Vec<_> = writes.iter().filter(|(c, _)| *c == 0x2c).collect();
+        assert_eq!(writes.len(), 3);
+        assert_eq!(writes[0].1.len(), 4 * 3);
     }
 }
@@ -0,0 +1,50 @@
+use embedded_graphics_core::prelude::RgbColor;
+
+/// The 8 colors the display can show in idle mode
+///
+/// In idle mode only the most significant bit of every color channel is used,
+/// so colors that only differ in the lower bits become indistinguishable.
+pub struct IdleSafePalette;
+
+impl IdleSafePalette {
+    /// All idle-safe colors, indexed by `0brgb`
+    pub fn colors<C: RgbColor>() -> [C; 8] {
+        [
+            C::BLACK,
+            C::BLUE,
+            C::GREEN,
+            C::CYAN,
+            C::RED,
+            C::MAGENTA,
+            C::YELLOW,
+            C::WHITE,
+        ]
+    }
+
+    /// The color shown in idle mode for `color`
+    pub fn quantize<C: RgbColor>(color: C) -> C {
+        let msb = |value: u8, max: u8| (value > max / 2) as usize;
+        let index = (msb(color.r(), C::MAX_R) << 2)
+            | (msb(color.g(), C::MAX_G) << 1)
+            | msb(color.b(), C::MAX_B);
+        Self::colors()[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics_core::pixelcolor::{Rgb565, Rgb666};
+
+    #[test]
+    fn quantize_uses_most_significant_bits() {
+        assert_eq!(
+            IdleSafePalette::quantize(Rgb666::new(32, 31, 63)),
+            Rgb666::MAGENTA
+        );
+        assert_eq!(
+            IdleSafePalette::quantize(Rgb565::new(15, 32, 16)),
+            Rgb565::CYAN
+        );
+    }
+}
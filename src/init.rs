@@ -0,0 +1,712 @@
+use display_interface::WriteOnlyDataCommand;
+use embedded_hal::delay::DelayNs;
+
+use crate::dcs::{Instruction, Nop, SetAddressMode, SetPixelFormat, SoftReset};
+use crate::gamma::{presets, DEFAULT_CONTRAST};
+use crate::profiles::{InitCommand, Profile};
+use crate::{
+    ColorOrder, DriverDelay, DriverState, FrameRateClockDivision, GammaTable, Ili9488,
+    Ili9488PixelFormat, Mode, ModeState, NoDelay, PanelState, ReadDataCommand, ResetDriver, Result,
+    VerifyError,
+};
+
+/// Entry Mode Set parameter used in normal operation
+const ENTRY_MODE: u8 = 0xC6;
+
+/// Deep standby bit (DSTB) of Entry Mode Set
+const DEEP_STANDBY: u8 = 0x08;
+
+/// Phase of the initialization that runs on the next [Ili9488Init::step]
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    ResetHigh,
+    ResetLow,
+    ResetRelease,
+    SoftwareReset,
+    /// Running the initialization sequence from the given entry, then configuring
+    Configure(usize),
+    Done,
+}
+
+/// An entry of an initialization sequence like [INIT_SEQUENCE]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InitOp {
+    /// Send a command with its parameters
+    Command(InitCommand),
+    /// Wait for a number of milliseconds before the next entry
+    Delay(u32),
+}
+
+impl InitOp {
+    pub const fn command(instruction: Instruction, params: &'static [u8]) -> Self {
+        Self::Command(InitCommand::new(instruction, params))
+    }
+}
+
+/// Manufacturer commands of [PanelVariant::TftEspi], taken from
+/// (https://github.com/Bodmer/TFT_eSPI/blob/master/TFT_Drivers/ILI9488_Init.h)
+///
+/// The sequence runs after the software reset. Memory access control, pixel format,
+/// sleep out and display on follow it, from the settings of the driver.
+pub const INIT_SEQUENCE: &[InitOp] = &[
+    InitOp::command(
+        Instruction::PositiveGammaControl,
+        &presets::TFT_ESPI.positive,
+    ),
+    InitOp::command(
+        Instruction::NegativeGammaControl,
+        &presets::TFT_ESPI.negative,
+    ),
+    InitOp::command(Instruction::PowerControl1, &[0x17, 0x15]),
+    InitOp::command(Instruction::PowerControl2, &[0x41]),
+    InitOp::command(Instruction::VCOMControl, &[0x00, 0x12, 0x80]),
+    InitOp::command(Instruction::InterfaceModeControl, &[0x00]),
+    // 60 Hz without clock division
+    InitOp::command(Instruction::NormalModeFrameRate, &[0xA0, 0x11]),
+    InitOp::command(Instruction::DisplayInversionControl, &[0x02]),
+    InitOp::command(Instruction::DisplayFunctionControl, &[0x02, 0x02, 0x3B]),
+    InitOp::command(Instruction::EntryModeSet, &[ENTRY_MODE]),
+    InitOp::command(Instruction::AdjustControl3, &[0xA9, 0x51, 0x2C, 0x82]),
+];
+
+/// [INIT_SEQUENCE] without Adjust Control 3, its last entry
+const STANDARD_SEQUENCE: &[InitOp] = INIT_SEQUENCE.split_at(INIT_SEQUENCE.len() - 1).0;
+
+/// States of [Ili9488::low_power_test_mode], in the order they are entered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LowPowerState {
+    /// Display on, as it was before the test
+    Normal,
+    /// Idle mode, 8 colors
+    Idle,
+    /// Sleep mode, the display is off and the memory is kept
+    Sleep,
+    /// Deep standby, memory and settings are lost
+    DeepStandby,
+}
+
+/// Known-good initialization sequences, selected with [Ili9488Init::panel_variant]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PanelVariant {
+    /// The sequence of TFT_eSPI, including Adjust Control 3 (0xF7)
+    #[default]
+    TftEspi,
+    /// Like [PanelVariant::TftEspi] without Adjust Control 3, which locks up some clones
+    Standard,
+    /// No manufacturer commands at all, the panel keeps its reset defaults for power,
+    /// gamma, frame rate and inversion
+    NoVendorExt,
+    /// The initialization table of a known board, see [Ili9488::new_with_profile]
+    Profile(Profile),
+    /// A custom sequence in the format of [INIT_SEQUENCE].
+    ///
+    /// Frame rate commands in it are not tracked, [Ili9488::frame_rate_hz] assumes 60 Hz.
+    Sequence(&'static [InitOp]),
+}
+
+impl PanelVariant {
+    /// The sequence run after the software reset
+    pub(crate) const fn init_sequence(self) -> &'static [InitOp] {
+        match self {
+            Self::TftEspi => INIT_SEQUENCE,
+            Self::Standard => STANDARD_SEQUENCE,
+            Self::NoVendorExt | Self::Profile(_) => &[],
+            Self::Sequence(sequence) => sequence,
+        }
+    }
+}
+
+/// The orientation the display had before it was reinitialized
+struct CurrentMode {
+    madctl: u8,
+    landscape: bool,
+}
+
+impl Mode for CurrentMode {
+    fn mode(&self) -> u8 {
+        self.madctl
+    }
+
+    fn is_landscape(&self) -> bool {
+        self.landscape
+    }
+}
+
+/// Initialization of the display split into phases, created with [Ili9488::begin_init].
+///
+/// Instead of blocking for the reset waits like [Ili9488::new], every call to
+/// [Ili9488Init::step] runs one phase and returns how long to wait before the next one,
+/// so other startup work can run in the meantime.
+///
+/// ```ignore
+/// let mut init = Ili9488::begin_init(interface, reset, Orientation::Landscape, Rgb666Mode)?;
+/// while let Some(ms) = init.step()? {
+///     // Do at most `ms` milliseconds of other work, then wait for the rest
+///     delay.delay_ms(ms);
+/// }
+/// let mut display = init.finish(&mut delay)?;
+/// ```
+pub struct Ili9488Init<IFACE, RESET, PixelFormat, MODE> {
+    display: Ili9488<IFACE, RESET, PixelFormat>,
+    orientation: MODE,
+    phase: Phase,
+}
+
+impl<IFACE, RESET, PixelFormat> Ili9488<IFACE, RESET, PixelFormat>
+where
+    IFACE: WriteOnlyDataCommand,
+    RESET: ResetDriver,
+    PixelFormat: Ili9488PixelFormat,
+{
+    /// Start a non-blocking initialization of the display, see [Ili9488Init]
+    pub fn begin_init<MODE: Mode>(
+        interface: IFACE,
+        reset: RESET,
+        orientation: MODE,
+        pixel_format: PixelFormat,
+    ) -> Ili9488Init<IFACE, RESET, PixelFormat, MODE> {
+        Ili9488Init {
+            display: Ili9488 {
+                interface,
+                reset,
+                state: DriverState::default(),
+                delay: NoDelay,
+                _pixel_format: pixel_format,
+            },
+            orientation,
+            phase: Phase::ResetHigh,
+        }
+    }
+}
+
+impl<IFACE, RESET, PixelFormat> Ili9488<IFACE, RESET, PixelFormat>
+where
+    IFACE: ReadDataCommand,
+    RESET: ResetDriver,
+    PixelFormat: Ili9488PixelFormat,
+{
+    /// Like [Ili9488::new], but check the controller is an ILI9488 after the reset and
+    /// before configuring it.
+    ///
+    /// Fails with [VerifyError::UnsupportedController] and the IDs that were read otherwise,
+    /// which catches a miswired DC or CS line, an unconnected data output or an ILI9486 panel
+    /// early instead of showing a garbled picture.
+    pub fn new_verified<DELAY, MODE>(
+        interface: IFACE,
+        reset: RESET,
+        delay: &mut DELAY,
+        orientation: MODE,
+        pixel_format: PixelFormat,
+    ) -> Result<Self, VerifyError>
+    where
+        DELAY: DelayNs,
+        MODE: Mode,
+    {
+        let mut init = Self::begin_init(interface, reset, orientation, pixel_format);
+        while !matches!(init.phase, Phase::Configure(_)) {
+            if let Some(ms) = init.step()? {
+                delay.delay_ms(ms);
+            }
+        }
+        let id = init.display.read_controller_id()?;
+        if !id.is_ili9488() {
+            return Err(VerifyError::UnsupportedController(id));
+        }
+        Ok(init.finish(delay)?)
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, MODE> Ili9488Init<IFACE, RESET, PixelFormat, MODE>
+where
+    IFACE: WriteOnlyDataCommand,
+    RESET: ResetDriver,
+    PixelFormat: Ili9488PixelFormat,
+    MODE: Mode,
+{
+    /// Select the initialization sequence, before the first [Ili9488Init::step].
+    ///
+    /// ```ignore
+    /// let display = Ili9488::begin_init(interface, reset, Orientation::Portrait, Rgb666Mode)
+    ///     .panel_variant(PanelVariant::Standard)
+    ///     .finish(&mut delay)?;
+    /// ```
+    pub fn panel_variant(mut self, variant: PanelVariant) -> Self {
+        self.display.state.panel_variant = match variant {
+            // The same sequence, sent the same way
+            PanelVariant::Profile(Profile::TftEspi) => PanelVariant::TftEspi,
+            variant => variant,
+        };
+        self
+    }
+
+    /// Send the gamma table `gamma` after the initialization sequence, e.g. one of
+    /// [presets](crate::gamma::presets), see [Ili9488::set_gamma_preset]
+    pub fn gamma(mut self, gamma: GammaTable) -> Self {
+        self.display.state.gamma = Some(gamma);
+        self
+    }
+
+    /// Set the color order of the panel before the first [Ili9488Init::step],
+    /// see [Ili9488::set_color_order]
+    pub fn color_order(mut self, color_order: ColorOrder) -> Self {
+        self.display.state.color_order = color_order;
+        self
+    }
+
+    /// Run the next phase of the initialization.
+    ///
+    /// Returns the time in milliseconds to wait before calling `step` again,
+    /// or `None` once the display is initialized.
+    pub fn step(&mut self) -> Result<Option<u32>> {
+        self.display.init_phase(&mut self.phase, &self.orientation)
+    }
+
+    /// Run the remaining phases, blocking on `delay`, and return the initialized display
+    pub fn finish<DELAY: DelayNs>(
+        mut self,
+        delay: &mut DELAY,
+    ) -> Result<Ili9488<IFACE, RESET, PixelFormat>> {
+        while let Some(ms) = self.step()? {
+            delay.delay_ms(ms);
+        }
+        Ok(self.display)
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+where
+    IFACE: WriteOnlyDataCommand,
+    RESET: ResetDriver,
+    PixelFormat: Ili9488PixelFormat,
+    DELAY: DelayNs,
+{
+    /// Enter deep standby, where the display draws the least power.
+    ///
+    /// The display memory and all settings are lost, the display can only be woken up
+    /// with [Ili9488::wake_from_deep_standby].
+    pub fn deep_standby(&mut self) -> Result {
+        self.enter_deep_standby(&mut |display, ms| display.delay.delay_ms(ms))
+    }
+
+    /// Walk the panel through ever lower power states, calling `measure` once the supply has
+    /// settled in each of them, so the current of every state can be measured with one
+    /// firmware image.
+    ///
+    /// The display is left in deep standby, wake it with [Ili9488::wake_from_deep_standby].
+    ///
+    /// ```ignore
+    /// display.low_power_test_mode(|state| {
+    ///     info!("{}: measure now", state);
+    ///     wait_for_button();
+    ///     Ok(())
+    /// })?;
+    /// ```
+    pub fn low_power_test_mode<F>(&mut self, measure: F) -> Result
+    where
+        F: FnMut(LowPowerState) -> Result,
+    {
+        self.run_low_power_test(&mut |display, ms| display.delay.delay_ms(ms), measure)
+    }
+
+    /// Leave deep standby with a hardware reset and run the initialization again.
+    ///
+    /// The orientation is kept, other settings are back to the ones set by [Ili9488::new]
+    /// and the display memory has to be redrawn.
+    pub fn wake_from_deep_standby(&mut self) -> Result {
+        self.wake(&mut |display, ms| display.delay.delay_ms(ms))
+    }
+}
+
+/// Without a delay given to [Ili9488::with_delay], the waits are left to `delay`
+impl<IFACE, RESET, PixelFormat, const BURST: usize>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, NoDelay>
+where
+    IFACE: WriteOnlyDataCommand,
+    RESET: ResetDriver,
+    PixelFormat: Ili9488PixelFormat,
+{
+    /// Enter deep standby, see [Ili9488::deep_standby]
+    pub fn deep_standby<D: DelayNs>(&mut self, delay: &mut D) -> Result {
+        self.enter_deep_standby(&mut |_, ms| delay.delay_ms(ms))
+    }
+
+    /// Measure the current of every low power state, see [Ili9488::low_power_test_mode]
+    pub fn low_power_test_mode<D, F>(&mut self, delay: &mut D, measure: F) -> Result
+    where
+        D: DelayNs,
+        F: FnMut(LowPowerState) -> Result,
+    {
+        self.run_low_power_test(&mut |_, ms| delay.delay_ms(ms), measure)
+    }
+
+    /// Leave deep standby, see [Ili9488::wake_from_deep_standby]
+    pub fn wake_from_deep_standby<D: DelayNs>(&mut self, delay: &mut D) -> Result {
+        self.wake(&mut |_, ms| delay.delay_ms(ms))
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+where
+    IFACE: WriteOnlyDataCommand,
+    RESET: ResetDriver,
+    PixelFormat: Ili9488PixelFormat,
+    DELAY: DriverDelay,
+{
+    /// Reset the display and run the initialization again, waiting with the delay given to
+    /// [Ili9488::with_delay], e.g. after a brown-out left the controller in an unknown state.
+    ///
+    /// Like [Ili9488::wake_from_deep_standby] the orientation is kept and the display memory
+    /// has to be redrawn.
+    pub fn reinit(&mut self) -> Result {
+        self.wake(&mut |display, ms| display.delay.wait_ms(ms))
+    }
+
+    fn enter_deep_standby(&mut self, wait: &mut impl FnMut(&mut Self, u32)) -> Result {
+        self.display_mode(ModeState::Off)?;
+        self.sleep_mode(ModeState::On)?;
+        // Wait for the supply voltages to settle before the next command
+        wait(self, 5);
+        self.command(Instruction::EntryModeSet, &[ENTRY_MODE | DEEP_STANDBY])
+    }
+
+    fn run_low_power_test(
+        &mut self,
+        wait: &mut impl FnMut(&mut Self, u32),
+        mut measure: impl FnMut(LowPowerState) -> Result,
+    ) -> Result {
+        measure(LowPowerState::Normal)?;
+
+        self.idle_mode(ModeState::On)?;
+        // A couple of frames for the idle frame rate to take over
+        wait(self, 40);
+        measure(LowPowerState::Idle)?;
+
+        self.idle_mode(ModeState::Off)?;
+        self.sleep_mode(ModeState::On)?;
+        // The booster and oscillator need up to 120 ms to stop
+        wait(self, 120);
+        measure(LowPowerState::Sleep)?;
+
+        self.enter_deep_standby(wait)?;
+        measure(LowPowerState::DeepStandby)
+    }
+
+    /// Reset and initialize the display again, keeping the orientation
+    pub(crate) fn wake(&mut self, wait: &mut impl FnMut(&mut Self, u32)) -> Result {
+        let orientation = self.current_mode();
+        let mut phase = Phase::ResetHigh;
+        while let Some(ms) = self.init_phase(&mut phase, &orientation)? {
+            wait(self, ms);
+        }
+        Ok(())
+    }
+
+    fn current_mode(&self) -> CurrentMode {
+        CurrentMode {
+            // The flip is applied again by set_orientation
+            madctl: self.state.madctl ^ self.state.flip.madctl(self.state.landscape),
+            landscape: self.state.landscape,
+        }
+    }
+
+    /// Run `phase` of the initialization and advance it,
+    /// returns the time in milliseconds to wait before the next phase
+    fn init_phase<MODE: Mode>(
+        &mut self,
+        phase: &mut Phase,
+        orientation: &MODE,
+    ) -> Result<Option<u32>> {
+        let (next, wait_ms) = match *phase {
+            Phase::ResetHigh => {
+                // Put SPI bus in known state for TFT with CS tied low
+                self.write_command(Nop)?;
+                self.reset.release()?;
+                (Phase::ResetLow, 5)
+            }
+            Phase::ResetLow => {
+                // Do hardware reset by holding reset low for at least 10us
+                self.reset.hold()?;
+                (Phase::ResetRelease, 20)
+            }
+            Phase::ResetRelease => {
+                // Set high for normal operation and wait for reset to complete
+                self.reset.release()?;
+                (Phase::SoftwareReset, 150)
+            }
+            Phase::SoftwareReset => {
+                // Wait 5ms after reset before sending commands
+                // and 120ms before sending Sleep Out
+                self.write_command(SoftReset)?;
+                self.state.panel = PanelState::default();
+                self.state.frame_rate = DriverState::default().frame_rate;
+                (Phase::Configure(0), 150)
+            }
+            Phase::Configure(start) => {
+                let sequence = self.state.panel_variant.init_sequence();
+                for (index, op) in sequence.iter().enumerate().skip(start) {
+                    match *op {
+                        InitOp::Command(command) => {
+                            self.write_raw(command.instruction, command.params)?
+                        }
+                        InitOp::Delay(ms) => {
+                            *phase = Phase::Configure(index + 1);
+                            return Ok(Some(ms));
+                        }
+                    }
+                }
+                self.configure(orientation)?;
+                *phase = Phase::Done;
+                return Ok(None);
+            }
+            Phase::Done => return Ok(None),
+        };
+        *phase = next;
+        Ok(Some(wait_ms))
+    }
+
+    fn configure<MODE: Mode>(&mut self, orientation: &MODE) -> Result {
+        if let PanelVariant::Profile(profile) = self.state.panel_variant {
+            for command in profile.init_table() {
+                self.write_raw(command.instruction, command.params)?;
+            }
+            self.normal_mode_frame_rate(FrameRateClockDivision::Fosc, profile.frame_rate())?;
+            if profile.inverted() {
+                self.invert_mode(ModeState::On)?;
+            }
+        }
+
+        if self.state.gamma.is_some() || self.state.contrast != DEFAULT_CONTRAST {
+            if let Some(gamma) = self.base_gamma() {
+                self.send_gamma(&gamma)?;
+            }
+        }
+
+        self.write_command(SetAddressMode(0x40 | self.state.color_order.madctl()))?; // MX
+
+        self.write_command(SetPixelFormat(PixelFormat::DATA))?;
+
+        self.sleep_mode(ModeState::Off)?;
+
+        self.set_orientation(orientation)?;
+
+        self.display_mode(ModeState::On)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dcs::Instruction;
+    use crate::mock::{self, MockDelay, MockInterface, MockPin};
+    use crate::{
+        ControllerId, FrameRate, FrameRateClockDivision, Ili9488, InitOp, LowPowerState, ModeState,
+        Orientation, PanelVariant, Rgb666Mode, VerifyError,
+    };
+    use display_interface::DisplayError;
+    use std::vec::Vec;
+
+    #[test]
+    fn phases() {
+        let mut init = Ili9488::begin_init(
+            MockInterface::new(),
+            MockPin::default(),
+            Orientation::Portrait,
+            Rgb666Mode,
+        );
+        let mut waits = Vec::new();
+        while let Some(ms) = init.step().unwrap() {
+            waits.push(ms);
+        }
+        assert_eq!(waits, [5, 20, 150, 150]);
+        assert_eq!(init.step().unwrap(), None);
+
+        let mut delay = MockDelay::default();
+        let display = init.finish(&mut delay).unwrap();
+        assert_eq!(delay.elapsed_ns, 0);
+        assert_eq!(display.interface.commands().last().unwrap().0, 0x29);
+    }
+
+    #[test]
+    fn custom_sequence() {
+        const SEQUENCE: &[InitOp] = &[
+            InitOp::command(Instruction::PowerControl1, &[0x10, 0x10]),
+            InitOp::Delay(10),
+            InitOp::command(Instruction::VCOMControl, &[0x00, 0x22, 0x80]),
+        ];
+        let mut init = Ili9488::begin_init(
+            MockInterface::new(),
+            MockPin::default(),
+            Orientation::Portrait,
+            Rgb666Mode,
+        )
+        .panel_variant(PanelVariant::Sequence(SEQUENCE));
+        let mut waits = Vec::new();
+        while let Some(ms) = init.step().unwrap() {
+            waits.push(ms);
+        }
+        assert_eq!(waits, [5, 20, 150, 150, 10]);
+
+        let display = init.finish(&mut MockDelay::default()).unwrap();
+        let commands: Vec<u8> = display.interface.commands().iter().map(|c| c.0).collect();
+        assert_eq!(
+            commands,
+            [0x00, 0x01, 0xc0, 0xc5, 0x36, 0x3a, 0x11, 0x36, 0x29]
+        );
+    }
+
+    #[test]
+    fn low_power_test_mode() {
+        let mut display = mock::display();
+        display.interface.clear();
+
+        let mut delay = MockDelay::default();
+        let mut states = Vec::new();
+        display
+            .low_power_test_mode(&mut delay, |state| {
+                states.push(state);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            states,
+            [
+                LowPowerState::Normal,
+                LowPowerState::Idle,
+                LowPowerState::Sleep,
+                LowPowerState::DeepStandby
+            ]
+        );
+        let commands: Vec<u8> = display.interface.commands().iter().map(|c| c.0).collect();
+        assert_eq!(commands, [0x39, 0x38, 0x10, 0x28, 0x10, 0xb7]);
+        assert_eq!(delay.elapsed_ns, (40 + 120 + 5) * 1_000_000);
+
+        // An error of the callback ends the test
+        display.interface.clear();
+        let result = display.low_power_test_mode(&mut delay, |state| match state {
+            LowPowerState::Idle => Err(DisplayError::BusWriteError),
+            _ => Ok(()),
+        });
+        assert!(matches!(result, Err(DisplayError::BusWriteError)));
+        assert_eq!(display.interface.commands().len(), 1);
+    }
+
+    #[test]
+    fn deep_standby_and_wake() {
+        let mut delay = MockDelay::default();
+        let mut display = mock::display_with(Orientation::LandscapeFlipped, Rgb666Mode);
+        display
+            .normal_mode_frame_rate(FrameRateClockDivision::FoscDiv2, FrameRate::FrameRate30)
+            .unwrap();
+        display.interface.clear();
+
+        display.deep_standby(&mut delay).unwrap();
+        assert_eq!(
+            display.interface.commands()[2],
+            (0xb7, [0xc6 | 0x08].into())
+        );
+
+        display.interface.clear();
+        display.wake_from_deep_standby(&mut delay).unwrap();
+        assert_eq!(display.width(), 480);
+        assert_eq!(display.frame_rate_hz(), FrameRate::FrameRate60.hz());
+        let commands = display.interface.commands();
+        assert_eq!(commands[1].0, 0x01);
+        assert_eq!(commands[commands.len() - 2], (0x36, [0xe8].into()));
+    }
+
+    #[test]
+    fn stored_delay() {
+        let mut display = mock::display().with_delay(MockDelay::default());
+
+        display.sleep_mode(ModeState::On).unwrap();
+        display.sleep_mode(ModeState::Off).unwrap();
+        assert_eq!(display.delay.elapsed_ns, 125_000_000);
+
+        display.delay.elapsed_ns = 0;
+        display.reinit().unwrap();
+        // Reset phases, then leaving sleep mode
+        assert_eq!(
+            display.delay.elapsed_ns,
+            (5 + 20 + 150 + 150 + 120) * 1_000_000
+        );
+
+        // Deep standby waits with the stored delay too
+        display.delay.elapsed_ns = 0;
+        display.deep_standby().unwrap();
+        assert_eq!(display.delay.elapsed_ns, (5 + 5) * 1_000_000);
+        display.delay.elapsed_ns = 0;
+        display.wake_from_deep_standby().unwrap();
+        assert_eq!(
+            display.delay.elapsed_ns,
+            (5 + 20 + 150 + 150 + 120) * 1_000_000
+        );
+        display.delay.elapsed_ns = 0;
+        display.low_power_test_mode(|_| Ok(())).unwrap();
+        assert_eq!(display.delay.elapsed_ns, (40 + 5 + 120 + 5 + 5) * 1_000_000);
+    }
+
+    #[test]
+    fn new_verified() {
+        let verified = |ids: [&[u8]; 2]| {
+            let mut interface = MockInterface::new();
+            interface.push_read(ids[0]);
+            interface.push_read(ids[1]);
+            Ili9488::new_verified(
+                interface,
+                MockPin::default(),
+                &mut MockDelay::default(),
+                Orientation::Portrait,
+                Rgb666Mode,
+            )
+        };
+        let display = verified([&[0, 0x54, 0x80, 0x66], &[0, 0x00, 0x94, 0x88]]).unwrap();
+        assert_eq!(display.interface.commands().last().unwrap().0, 0x29);
+
+        // ILI9486, the panel is left unconfigured
+        match verified([&[0, 0, 0, 0], &[0, 0x00, 0x94, 0x86]]) {
+            Err(VerifyError::UnsupportedController(id)) => assert_eq!(
+                id,
+                ControllerId {
+                    display_id: [0, 0, 0],
+                    ic_id: [0x00, 0x94, 0x86],
+                }
+            ),
+            _ => panic!("ILI9486 accepted"),
+        }
+        // Nothing answering
+        assert!(matches!(
+            verified([&[], &[]]),
+            Err(VerifyError::UnsupportedController(_))
+        ));
+    }
+
+    #[test]
+    fn panel_variants() {
+        let commands = |variant| {
+            let display = Ili9488::begin_init(
+                MockInterface::new(),
+                MockPin::default(),
+                Orientation::Portrait,
+                Rgb666Mode,
+            )
+            .panel_variant(variant)
+            .finish(&mut MockDelay::default())
+            .unwrap();
+            let commands: Vec<u8> = display.interface.commands().iter().map(|c| c.0).collect();
+            commands
+        };
+        assert!(commands(PanelVariant::TftEspi).contains(&0xf7));
+        let standard = commands(PanelVariant::Standard);
+        assert!(!standard.contains(&0xf7));
+        assert!(standard.contains(&0xb7));
+        assert_eq!(
+            commands(PanelVariant::NoVendorExt),
+            [0x00, 0x01, 0x36, 0x3a, 0x11, 0x36, 0x29]
+        );
+    }
+}
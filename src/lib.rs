@@ -24,22 +24,145 @@
 //! display.clear(Rgb666::RED).unwrap()
 //! ```
 //!
+//! Mode and control methods return the driver again, so settings can be chained:
+//!
+//! ```ignore
+//! display
+//!     .invert_mode(ModeState::On)?
+//!     .brightness(200)?
+//!     .idle_mode(ModeState::Off)?;
+//! ```
+//!
+//! ### Shared SPI bus
+//!
+//! `SPIInterface` takes an [embedded_hal::spi::SpiDevice], which asserts chip select for every
+//! command and every chunk of pixel data separately. The display can therefore share the bus
+//! with other devices, e.g. through `embedded-hal-bus`'s `RefCellDevice`, as long as the other
+//! devices are only used between calls into the driver. Use [Ili9488::clear_screen_banded] or
+//! draw in smaller areas to keep the time between those calls short.
+//!
+//! Several displays on one bus, each with its own chip select and DC pin, can be grouped into
+//! a [DisplayArray] to draw the same content to all of them or address them one by one.
+//!
 //! [display-interface-spi crate]: https://crates.io/crates/display-interface-spi
 use embedded_hal::delay::DelayNs;
-use embedded_hal::digital::OutputPin;
 
 use display_interface::{DataFormat, WriteOnlyDataCommand};
 
-use embedded_graphics_core::pixelcolor::{IntoStorage, Rgb565, Rgb666};
-use embedded_graphics_core::prelude::RgbColor;
-
+use embedded_graphics_core::pixelcolor::IntoStorage;
+#[cfg(feature = "rgb666")]
+use embedded_graphics_core::pixelcolor::{Rgb666, Rgb888};
+use embedded_graphics_core::prelude::{Dimensions, RgbColor};
+use embedded_graphics_core::primitives::Rectangle;
+
+#[cfg(any(test, feature = "std"))]
+extern crate std;
+
+mod array;
+#[cfg(feature = "bench")]
+pub mod bench;
+#[cfg(feature = "blocks")]
+pub mod blocks;
+pub mod dcs;
+mod diff;
+mod dma;
+#[cfg(all(feature = "rgb111", feature = "rgb666"))]
+mod dynamic;
+mod framebuf;
+pub mod gamma;
+#[cfg(test)]
+mod golden;
 mod graphics_core;
+mod idle;
+mod init;
+#[cfg(feature = "lvgl")]
+pub mod lvgl;
+#[cfg(any(test, feature = "std"))]
+pub mod mock;
+mod observer;
+#[cfg(feature = "async")]
+pub mod pacer;
+mod packing;
+mod power;
+pub mod profiles;
+#[cfg(feature = "qoi")]
+pub mod qoi;
+mod read;
+mod region;
+mod reset;
 mod rgb111;
+mod rgb_interface;
+pub mod rle;
+mod scanline;
+#[cfg(feature = "critical-section")]
+pub mod shared;
+mod shift_register;
+#[cfg(any(test, feature = "std"))]
+pub mod sim;
+mod software_rotation;
+mod spi3wire;
+#[cfg(feature = "stats")]
+mod stats;
+mod test_patterns;
+#[cfg(feature = "text")]
+pub mod text;
+pub mod theme;
+mod tiled;
+#[cfg(feature = "touch")]
+pub mod touch;
+mod tracked;
+#[cfg(feature = "widgets")]
+pub mod widgets;
+mod writer;
+pub use crate::array::DisplayArray;
+use crate::dcs::{
+    DcsCommand, EnterIdleMode, EnterInvertMode, EnterSleepMode, ExitIdleMode, ExitInvertMode,
+    ExitSleepMode, Instruction, SetAddressMode, SetColumnAddress, SetDisplayBrightness,
+    SetDisplayOff, SetDisplayOn, SetPageAddress, SetPixelFormat, SetScrollArea, SetScrollStart,
+    WriteCtrlDisplay, WriteMemoryStart,
+};
+pub use crate::diff::DiffFlusher;
+pub use crate::dma::{BufferError, DmaDataCommand};
+#[cfg(all(feature = "rgb111", feature = "rgb666"))]
+pub use crate::dynamic::{DynPixelFormat, Ili9488Dyn};
+pub use crate::framebuf::Ili9488FrameBuf;
+pub use crate::gamma::GammaTable;
+pub use crate::graphics_core::ColorConverted;
+pub use crate::idle::IdleSafePalette;
+pub use crate::init::{Ili9488Init, InitOp, LowPowerState, PanelVariant, INIT_SEQUENCE};
+#[cfg(feature = "defmt")]
+pub use crate::observer::DefmtObserver;
+pub use crate::observer::{CommandObserver, ObservedInterface};
+pub use crate::packing::{Ili9488PixelPacking, PackedPixels, WireFormat};
+pub use crate::power::{NoPin, PowerPins};
+pub use crate::read::{
+    ControllerId, DiagnosticReport, PixelMismatch, ReadDataCommand, VerifyError,
+};
+pub use crate::region::Region;
+pub use crate::reset::{ActiveHigh, NoReset, ResetDriver};
 pub use crate::rgb111::*;
+pub use crate::rgb_interface::{
+    BlankingPorches, Dpi16Mode, Dpi18Mode, Dpi24Mode, RgbInterfaceConfig, RgbInterfacePixelFormat,
+};
+pub use crate::scanline::{Band, ScanlineRenderer};
+#[cfg(feature = "critical-section")]
+pub use crate::shared::{SharedIli9488, SharedRegion};
+pub use crate::shift_register::ShiftRegisterInterface;
+pub use crate::software_rotation::SoftwareRotation;
+pub use crate::spi3wire::Spi3WireInterface;
+#[cfg(feature = "stats")]
+pub use crate::stats::WriteStats;
+pub use crate::tiled::Tile;
+pub use crate::tracked::TrackedTarget;
+pub use crate::writer::{PixelWriter, WriteSession, FILL_CHUNK_PIXELS};
 pub use display_interface::DisplayError;
 
 type Result<T = (), E = DisplayError> = core::result::Result<T, E>;
 
+/// Color of the pixels the display writes in its pixel format
+type Color<IFACE, RESET, PixelFormat, const BURST: usize, DELAY> =
+    <Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY> as Ili9488MemoryWrite>::PixelFormat;
+
 /// Trait that defines display size information
 pub trait DisplaySize {
     /// Width in pixels
@@ -60,34 +183,79 @@ impl DisplaySize for DisplaySize320x480 {
 pub trait Ili9488PixelFormat: Copy + Clone {
     /// The data used for the PixelFormatSet command
     const DATA: u8;
+    /// Bits per pixel in the display memory
+    const BPP: u8;
+    /// The bus of this format can carry 3 bpp data as well, so [Ili9488::clear_screen] can
+    /// fill with [Rgb111] colors
+    const FAST_FILL: bool = true;
 }
 
 /// 3 bpp
+///
+/// Drawing needs the `rgb111` feature.
 #[derive(Copy, Clone)]
 pub struct Rgb111Mode;
 
 impl Ili9488PixelFormat for Rgb111Mode {
     const DATA: u8 = 0x1;
+    const BPP: u8 = 3;
 }
 /// 16 bpp
+///
+/// The ILI9488 only accepts 16 bpp over the parallel (DBI) interfaces, not over SPI.
+/// Drawing needs the `rgb565-parallel` feature.
 #[derive(Copy, Clone)]
 pub struct Rgb565Mode;
 
 impl Ili9488PixelFormat for Rgb565Mode {
     const DATA: u8 = 0x55;
+    const BPP: u8 = 16;
+    // Only used with a 16-bit parallel bus, 3 bpp needs the 8-bit bus or SPI
+    const FAST_FILL: bool = false;
 }
 /// 18 bpp
+///
+/// Drawing needs the `rgb666` feature.
 #[derive(Copy, Clone)]
 pub struct Rgb666Mode;
 impl Ili9488PixelFormat for Rgb666Mode {
     const DATA: u8 = 0x66;
+    const BPP: u8 = 18;
+}
+/// 18 bpp over a 16-bit parallel data bus
+///
+/// Pixels are packed into 16-bit words, three words for every two pixels,
+/// as expected by the ILI9488 when its 16-bit DBI interface is used in 18 bpp mode.
+/// Drawing needs the `rgb666` feature.
+#[derive(Copy, Clone)]
+pub struct Rgb666Bus16Mode;
+impl Ili9488PixelFormat for Rgb666Bus16Mode {
+    const DATA: u8 = 0x66;
+    const BPP: u8 = 18;
+    // 3 bpp is only available on the 8-bit bus and SPI
+    const FAST_FILL: bool = false;
+}
+/// 18 bpp, drawn with [Bgr666](embedded_graphics_core::pixelcolor::Bgr666) colors
+///
+/// Sends the same bytes as [Rgb666Mode], only the color type differs, for code that uses
+/// `Bgr666` throughout. How the channels reach the panel is set by [Ili9488::set_color_order],
+/// so the colors do not need to be swapped by hand. Drawing needs the `rgb666` feature.
+#[derive(Copy, Clone)]
+pub struct Bgr666Mode;
+impl Ili9488PixelFormat for Bgr666Mode {
+    const DATA: u8 = 0x66;
+    const BPP: u8 = 18;
 }
 
 /// Trait implementation for writing different pixel formats to the ili9488's memory
+///
+/// Implemented for every pixel format with an [Ili9488PixelPacking] implementation.
 pub trait Ili9488MemoryWrite {
     type PixelFormat: RgbColor;
     fn write_iter<I: IntoIterator<Item = Self::PixelFormat>>(&mut self, data: I) -> Result;
     fn write_slice(&mut self, data: &[Self::PixelFormat]) -> Result;
+    /// Send pixel data without a memory write command first
+    fn write_pixels<I: IntoIterator<Item = Self::PixelFormat>>(&mut self, data: I) -> Result;
 }
 
 /// For quite a few boards (ESP32-S2-Kaluga-1, M5Stack, M5Core2 and others),
@@ -95,14 +263,28 @@ pub trait Ili9488MemoryWrite {
 ///
 /// This trait provides the flexibility for users to define their own
 /// initialization command arguments suitable for the particular board they are using
+///
+/// The BGR bit is added from the color order of the driver, see [Ili9488::set_color_order].
 pub trait Mode {
     fn mode(&self) -> u8;
 
     fn is_landscape(&self) -> bool;
 }
 
+impl<M: Mode> Mode for &M {
+    fn mode(&self) -> u8 {
+        (**self).mode()
+    }
+
+    fn is_landscape(&self) -> bool {
+        (**self).is_landscape()
+    }
+}
+
 /// The default implementation of the Mode trait from above
 /// Should work for most (but not all) boards
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Orientation {
     Portrait,
     PortraitFlipped,
@@ -113,10 +295,10 @@ pub enum Orientation {
 impl Mode for Orientation {
     fn mode(&self) -> u8 {
         match self {
-            Self::Portrait => 0x40 | 0x08,
-            Self::Landscape => 0x20 | 0x08,
-            Self::PortraitFlipped => 0x80 | 0x08,
-            Self::LandscapeFlipped => 0x40 | 0x80 | 0x20 | 0x08,
+            Self::Portrait => 0x40,
+            Self::Landscape => 0x20,
+            Self::PortraitFlipped => 0x80,
+            Self::LandscapeFlipped => 0x40 | 0x80 | 0x20,
         }
     }
 
@@ -128,7 +310,110 @@ impl Mode for Orientation {
     }
 }
 
+/// Clockwise rotation of the picture, relative to [Orientation::Portrait]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Rotation {
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+/// Order of the color channels as the panel is wired, see [Ili9488::set_color_order]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ColorOrder {
+    Rgb,
+    /// Used by most ILI9488 modules
+    #[default]
+    Bgr,
+}
+
+impl ColorOrder {
+    /// The BGR bit of MemoryAccessControl
+    fn madctl(self) -> u8 {
+        match self {
+            Self::Rgb => 0x00,
+            Self::Bgr => 0x08,
+        }
+    }
+}
+
+/// [Mode] composed from a [Rotation] and an optional horizontal mirror
+///
+/// ```ignore
+/// let mode = RotationMode::new(Rotation::Deg90).mirrored(true);
+/// display.set_orientation(mode)?;
+/// ```
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RotationMode {
+    rotation: Rotation,
+    mirrored: bool,
+}
+
+impl RotationMode {
+    /// Not mirrored
+    pub const fn new(rotation: Rotation) -> Self {
+        Self {
+            rotation,
+            mirrored: false,
+        }
+    }
+
+    /// Mirror the picture horizontally
+    pub const fn mirrored(mut self, mirrored: bool) -> Self {
+        self.mirrored = mirrored;
+        self
+    }
+}
+
+impl Mode for RotationMode {
+    fn mode(&self) -> u8 {
+        let rotation = match self.rotation {
+            Rotation::Deg0 => 0x40,
+            Rotation::Deg90 => 0x20,
+            Rotation::Deg180 => 0x80,
+            Rotation::Deg270 => 0x40 | 0x80 | 0x20,
+        };
+        // Mirror along the logical x axis, which is the panel's row axis in landscape
+        let mirror = match (self.mirrored, self.is_landscape()) {
+            (false, _) => 0x00,
+            (true, false) => 0x40,
+            (true, true) => 0x80,
+        };
+        rotation ^ mirror
+    }
+
+    fn is_landscape(&self) -> bool {
+        matches!(self.rotation, Rotation::Deg90 | Rotation::Deg270)
+    }
+}
+
+impl Mode for Rotation {
+    fn mode(&self) -> u8 {
+        RotationMode::new(*self).mode()
+    }
+
+    fn is_landscape(&self) -> bool {
+        RotationMode::new(*self).is_landscape()
+    }
+}
+
+impl From<Orientation> for RotationMode {
+    fn from(orientation: Orientation) -> Self {
+        Self::new(match orientation {
+            Orientation::Portrait => Rotation::Deg0,
+            Orientation::Landscape => Rotation::Deg90,
+            Orientation::PortraitFlipped => Rotation::Deg180,
+            Orientation::LandscapeFlipped => Rotation::Deg270,
+        })
+    }
+}
+
 /// Specify state of specific mode of operation
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ModeState {
     On,
     Off,
@@ -149,19 +434,152 @@ pub enum ModeState {
 /// - As soon as a pixel is received, an internal counter is incremented,
 ///   and the next word will fill the next pixel (the adjacent on the right, or
 ///   the first of the next row if the row ended)
-pub struct Ili9488<IFACE, RESET, PixelFormat> {
+///
+/// Pixels drawn through [embedded_graphics_core::draw_target::DrawTarget::draw_iter] are
+/// batched into bursts of up to `BURST` pixels that share a window. Pick a smaller burst on
+/// RAM-starved MCUs or a larger one to keep SPI and DMA busy, see [Ili9488::with_burst_size].
+pub struct Ili9488<IFACE, RESET, PixelFormat, const BURST: usize = 64, DELAY = NoDelay> {
     interface: IFACE,
     reset: RESET,
+    state: DriverState,
+    /// Waits required after commands, see [Ili9488::with_delay]
+    delay: DELAY,
+    _pixel_format: PixelFormat,
+}
+
+/// Everything the driver keeps that does not depend on its type parameters, moved as a whole
+/// when they change
+#[derive(Clone, Copy)]
+struct DriverState {
     width: usize,
     height: usize,
     landscape: bool,
-    _pixel_format: PixelFormat,
+    /// Last value sent with MemoryAccessControl
+    madctl: u8,
+    /// Color order OR'd into every MemoryAccessControl value
+    color_order: ColorOrder,
+    /// Normal mode frame rate settings
+    frame_rate: (FrameRateClockDivision, FrameRate),
+    /// Initialization sequence, kept to wake up from deep standby
+    panel_variant: PanelVariant,
+    /// Gamma table set with [Ili9488::set_gamma_preset], sent on every initialization
+    gamma: Option<GammaTable>,
+    /// Mid-tone contrast set with [Ili9488::set_display_contrast], applied to every gamma table
+    contrast: u8,
+    idle_safe_colors: bool,
+    /// Incremented on every orientation change, flip and scroll reset, to detect outdated
+    /// [Scroller]s
+    orientation_generation: u16,
+    /// Modes last set on the controller
+    panel: PanelState,
+    retry_policy: RetryPolicy,
+    /// Window `[x0, y0, x1, y1]` set with [Ili9488::set_draw_region]
+    draw_region: Option<[u16; 4]>,
+    power_policy: PowerPolicy,
+    data_width: DataWidth,
+    /// Mirroring set with [Ili9488::flip_horizontal] and [Ili9488::flip_vertical]
+    flip: Flip,
+    /// Counters of [Ili9488::stats]
+    #[cfg(feature = "stats")]
+    stats: stats::Stats,
+}
+
+impl Default for DriverState {
+    fn default() -> Self {
+        Self {
+            width: DisplaySize320x480::WIDTH,
+            height: DisplaySize320x480::HEIGHT,
+            landscape: false,
+            madctl: 0x48,
+            color_order: ColorOrder::default(),
+            frame_rate: (FrameRateClockDivision::Fosc, FrameRate::FrameRate60),
+            panel_variant: PanelVariant::default(),
+            gamma: None,
+            contrast: gamma::DEFAULT_CONTRAST,
+            idle_safe_colors: false,
+            orientation_generation: 0,
+            panel: PanelState::default(),
+            retry_policy: RetryPolicy::default(),
+            draw_region: None,
+            power_policy: PowerPolicy::default(),
+            data_width: DataWidth::default(),
+            flip: Flip::default(),
+            #[cfg(feature = "stats")]
+            stats: Default::default(),
+        }
+    }
+}
+
+/// Delay that does not wait, used until a delay is given with [Ili9488::with_delay]
+///
+/// Until then methods that cannot do without waiting, like [Ili9488::deep_standby], take a
+/// delay as a parameter.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoDelay;
+
+/// The delay of the driver, [NoDelay] or the [DelayNs] given with [Ili9488::with_delay]
+pub trait DriverDelay {
+    fn wait_ms(&mut self, ms: u32);
+}
+
+impl<D: DelayNs> DriverDelay for D {
+    fn wait_ms(&mut self, ms: u32) {
+        self.delay_ms(ms);
+    }
+}
+
+impl DriverDelay for NoDelay {
+    fn wait_ms(&mut self, _ms: u32) {}
+}
+
+/// The delay of a view, see [Ili9488::with_rgb111_mode]
+impl DriverDelay for &mut NoDelay {
+    fn wait_ms(&mut self, _ms: u32) {}
+}
+
+/// What the driver does when sending to the display fails, see [Ili9488::set_retry_policy]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RetryPolicy {
+    /// Return the error right away
+    #[default]
+    Never,
+    /// Send a NOP to resynchronize the controller, set the window again and retry up to
+    /// this many times before returning the error
+    Retries(u8),
+}
+
+/// Modes last set on the controller, starting from the defaults after reset
+#[derive(Clone, Copy)]
+struct PanelState {
+    sleeping: bool,
+    inverted: bool,
+    idle: bool,
+    brightness: u8,
+    display_on: bool,
+    backlight: BacklightControl,
+    /// Column and page addresses `[x0, y0, x1, y1]`, `None` when unknown
+    window: Option<[u16; 4]>,
+}
+
+impl Default for PanelState {
+    fn default() -> Self {
+        Self {
+            sleeping: true,
+            inverted: false,
+            idle: false,
+            brightness: 0,
+            display_on: false,
+            backlight: BacklightControl::default(),
+            window: None,
+        }
+    }
 }
 
 impl<IFACE, RESET, PixelFormat> Ili9488<IFACE, RESET, PixelFormat>
 where
     IFACE: WriteOnlyDataCommand,
-    RESET: OutputPin,
+    RESET: ResetDriver,
     PixelFormat: Ili9488PixelFormat,
 {
     pub fn new<DELAY, MODE>(
@@ -175,240 +593,553 @@ where
         DELAY: DelayNs,
         MODE: Mode,
     {
-        let mut ili9488 = Self {
-            interface,
-            reset,
-            width: DisplaySize320x480::WIDTH,
-            height: DisplaySize320x480::HEIGHT,
-            landscape: false,
-            _pixel_format: pixel_format,
-        };
-
-        // Put SPI bus in known state for TFT with CS tied low
-        ili9488.command(Command::NOP, &[])?;
-
-        ili9488
-            .reset
-            .set_high()
-            .map_err(|_| DisplayError::RSError)?;
-        delay.delay_ms(5);
-
-        // Do hardware reset by holding reset low for at least 10us
-        ili9488.reset.set_low().map_err(|_| DisplayError::RSError)?;
-        let _ = delay.delay_ms(20);
-
-        // Set high for normal operation
-        ili9488
-            .reset
-            .set_high()
-            .map_err(|_| DisplayError::RSError)?;
-
-        // Wait for reset to complete
-        let _ = delay.delay_ms(150);
-
-        // Do software reset
-        ili9488.command(Command::SoftwareReset, &[])?;
-
-        // Wait 5ms after reset before sending commands
-        // and 120ms before sending Sleep Out
-        let _ = delay.delay_ms(150);
-
-        // Initialization Sequence, taken from (https://github.com/Bodmer/TFT_eSPI/blob/master/TFT_Drivers/ILI9488_Init.h)
-
-        // Positive Gamma Control
-        ili9488.command(
-            Command::PositiveGammaControl,
-            &[
-                0x00, 0x03, 0x09, 0x08, 0x16, 0x0A, 0x3F, 0x78, 0x4C, 0x09, 0x0A, 0x08, 0x16, 0x1A,
-                0x0F,
-            ],
-        )?;
-
-        // Negative Gamma Control
-        ili9488.command(
-            Command::NegativeGammaControl,
-            &[
-                0x00, 0x16, 0x19, 0x03, 0x0F, 0x05, 0x32, 0x45, 0x46, 0x04, 0x0E, 0x0D, 0x35, 0x37,
-                0x0F,
-            ],
-        )?;
-
-        ili9488.command(Command::PowerControl1, &[0x17, 0x15])?;
-
-        ili9488.command(Command::PowerControl2, &[0x41])?;
-
-        ili9488.command(Command::VCOMControl, &[0x00, 0x12, 0x80])?;
-
-        ili9488.command(Command::MemoryAccessControl, &[0x48])?; // MX, BGR
-
-        ili9488.command(Command::PixelFormatSet, &[PixelFormat::DATA])?;
-
-        ili9488.command(Command::InterfaceModeControl, &[0x00])?;
-
-        ili9488.command(Command::NormalModeFrameRate, &[0xA0])?;
-
-        ili9488.command(Command::DisplayInversionControl, &[0x02])?;
-
-        ili9488.command(Command::DisplayFunctionControl, &[0x02, 0x02, 0x3B])?;
-
-        ili9488.command(Command::EntryModeSet, &[0xC6])?;
-
-        ili9488.command(Command::AdjustControl3, &[0xA9, 0x51, 0x2C, 0x82])?;
-
-        ili9488.sleep_mode(ModeState::Off)?;
-
-        ili9488.set_orientation(orientation)?;
-
-        ili9488.display_mode(ModeState::On)?;
-
-        Ok(ili9488)
+        Self::begin_init(interface, reset, orientation, pixel_format).finish(delay)
     }
 }
 
-impl<IFACE, RESET, PixelFormat> Ili9488<IFACE, RESET, PixelFormat>
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
 where
     IFACE: WriteOnlyDataCommand,
     PixelFormat: Ili9488PixelFormat,
 {
+    /// Temporarily switch the controller to pixel format `P` and run `f` with a view of the
+    /// driver that uses it.
+    ///
+    /// The original pixel format is restored afterwards, even when `f` returns an error.
+    pub fn with_pixel_format<P, R, F>(&mut self, pixel_format: P, f: F) -> Result<R>
+    where
+        P: Ili9488PixelFormat,
+        F: FnOnce(&mut Ili9488<InterfaceRef<'_, IFACE>, (), P, BURST, &mut DELAY>) -> Result<R>,
+    {
+        if P::DATA == PixelFormat::DATA {
+            return self.with_view(pixel_format, f);
+        }
+
+        self.write_command(SetPixelFormat(P::DATA))?;
+        let result = self.with_view(pixel_format, f);
+        let restored = self.write_command(SetPixelFormat(PixelFormat::DATA));
+        result.and_then(|value| restored.map(|_| value))
+    }
+
+    /// Temporarily switch the controller to 3 bpp and run `f` with an [Rgb111] view of the driver.
+    ///
+    /// Useful to quickly draw large backgrounds before adding details in the original pixel format.
+    ///
+    /// ```ignore
+    /// display.with_rgb111_mode(|display| {
+    ///     display.draw_raw_iter(0, 0, 479, 159, core::iter::repeat_n(Rgb111::BLUE, 480 * 160))
+    /// })?;
+    /// ```
+    pub fn with_rgb111_mode<R, F>(&mut self, f: F) -> Result<R>
+    where
+        F: FnOnce(
+            &mut Ili9488<InterfaceRef<'_, IFACE>, (), Rgb111Mode, BURST, &mut DELAY>,
+        ) -> Result<R>,
+    {
+        self.with_pixel_format(Rgb111Mode, f)
+    }
+
+    /// Switch the controller to pixel format `P` for good.
+    ///
+    /// Orientation, mirroring, color order and the other settings carry over, see
+    /// [Ili9488::refresh_configuration] to send them to the controller again.
     pub fn change_pixel_format<P: Ili9488PixelFormat>(
         mut self,
         pixel_format: P,
-    ) -> Result<Ili9488<IFACE, RESET, P>> {
-        self.command(Command::PixelFormatSet, &[P::DATA])?;
+    ) -> Result<Ili9488<IFACE, RESET, P, BURST, DELAY>> {
+        self.write_command(SetPixelFormat(P::DATA))?;
 
         Ok(Ili9488 {
             interface: self.interface,
             reset: self.reset,
-            width: self.width,
-            height: self.height,
-            landscape: self.landscape,
+            state: self.state,
+            delay: self.delay,
             _pixel_format: pixel_format,
         })
     }
-    fn command(&mut self, cmd: Command, args: &[u8]) -> Result {
-        self.interface.send_commands(DataFormat::U8(&[cmd as u8]))?;
-        self.interface.send_data(DataFormat::U8(args))
+
+    /// Send a typed [DcsCommand] with its parameters, see the [dcs] module
+    pub fn write_command<C: DcsCommand>(&mut self, command: C) -> Result {
+        let mut params = [0; dcs::MAX_PARAMS];
+        let len = command.fill_params_buf(&mut params);
+        self.write_raw(command.instruction(), &params[..len])
+    }
+
+    /// Send any `instruction` with `params` as they are, e.g. manufacturer commands
+    /// listed in [dcs::Instruction].
+    ///
+    /// The driver does not track state changed this way, such as the orientation or pixel format.
+    /// Only address and reset commands are noticed, so the next drawing sets its window again.
+    pub fn write_raw(&mut self, instruction: u8, params: &[u8]) -> Result {
+        let resets_window = [
+            Instruction::SoftwareReset,
+            Instruction::ColumnAddressSet,
+            Instruction::PageAddressSet,
+        ];
+        if resets_window.map(|i| i as u8).contains(&instruction) {
+            self.state.panel.window = None;
+        }
+        #[cfg(feature = "stats")]
+        self.state.stats.command(instruction, params);
+        self.retrying(|display| {
+            display
+                .interface
+                .send_commands(DataFormat::U8(&[instruction]))?;
+            display.interface.send_data(DataFormat::U8(params))
+        })
+    }
+
+    /// Run `f`, and while it fails and the [RetryPolicy] allows, send a NOP and run it again.
+    ///
+    /// The window is forgotten before every retry, so `f` sets it again. Calls nested in `f`
+    /// are not retried on their own.
+    fn retrying(&mut self, mut f: impl FnMut(&mut Self) -> Result) -> Result {
+        let policy = core::mem::take(&mut self.state.retry_policy);
+        let mut retries = match policy {
+            RetryPolicy::Never => 0,
+            RetryPolicy::Retries(retries) => retries,
+        };
+        let result = loop {
+            let result = f(self);
+            if result.is_ok() || retries == 0 {
+                break result;
+            }
+            retries -= 1;
+            self.state.panel.window = None;
+            // A bus that is still failing shows up in the retry
+            let _ = self
+                .interface
+                .send_commands(DataFormat::U8(&[Instruction::Nop as u8]));
+        };
+        self.state.retry_policy = policy;
+        result
+    }
+
+    fn command(&mut self, cmd: Instruction, args: &[u8]) -> Result {
+        self.write_raw(cmd as u8, args)
     }
 
+    /// Set the window, skipping the commands when it is already set,
+    /// e.g. when a widget is redrawn every frame
     fn set_window(&mut self, x0: u16, y0: u16, x1: u16, y1: u16) -> Result {
-        self.command(
-            Command::ColumnAddressSet,
-            &[
-                (x0 >> 8) as u8,
-                (x0 & 0xff) as u8,
-                (x1 >> 8) as u8,
-                (x1 & 0xff) as u8,
-            ],
-        )?;
-        self.command(
-            Command::PageAddressSet,
-            &[
-                (y0 >> 8) as u8,
-                (y0 & 0xff) as u8,
-                (y1 >> 8) as u8,
-                (y1 & 0xff) as u8,
-            ],
+        let window = [x0, y0, x1, y1];
+        if self.state.panel.window == Some(window) {
+            return Ok(());
+        }
+        self.write_command(SetColumnAddress::new(x0, x1))?;
+        self.write_command(SetPageAddress::new(y0, y1))?;
+        self.state.panel.window = Some(window);
+        Ok(())
+    }
+
+    /// Set the window to `region`, which must be on screen and not empty
+    fn set_region_window(&mut self, region: &Rectangle) -> Result {
+        let bottom_right = region
+            .bottom_right()
+            .filter(|_| self.bounding_box().intersection(region) == *region)
+            .ok_or(DisplayError::OutOfBoundsError)?;
+        self.set_window(
+            region.top_left.x as u16,
+            region.top_left.y as u16,
+            bottom_right.x as u16,
+            bottom_right.y as u16,
         )
     }
 
     /// Configures the screen for hardware-accelerated vertical scrolling.
+    ///
+    /// The hardware can only scroll along the panel's long axis, which is horizontal in landscape
+    /// orientations. [ScrollError::UnsupportedOrientation] is returned in that case,
+    /// use [Ili9488::configure_horizontal_scroll] or [Ili9488::configure_software_scroll] instead.
+    ///
+    /// Fails with [ScrollError::InvalidScrollConfig] when the fixed areas leave no lines
+    /// to scroll.
     pub fn configure_vertical_scroll(
         &mut self,
         fixed_top_lines: u16,
         fixed_bottom_lines: u16,
-    ) -> Result<Scroller> {
-        let height = if self.landscape {
-            self.width
-        } else {
-            self.height
-        } as u16;
-        let scroll_lines = height as u16 - fixed_top_lines - fixed_bottom_lines;
+    ) -> Result<Scroller, ScrollError> {
+        if self.state.landscape {
+            return Err(ScrollError::UnsupportedOrientation);
+        }
+        self.configure_scroll(fixed_top_lines, fixed_bottom_lines)
+    }
 
-        self.command(
-            Command::VerticalScrollDefine,
-            &[
-                (fixed_top_lines >> 8) as u8,
-                (fixed_top_lines & 0xff) as u8,
-                (scroll_lines >> 8) as u8,
-                (scroll_lines & 0xff) as u8,
-                (fixed_bottom_lines >> 8) as u8,
-                (fixed_bottom_lines & 0xff) as u8,
-            ],
-        )?;
+    /// Configures the screen for hardware-accelerated horizontal scrolling, the counterpart of
+    /// [Ili9488::configure_vertical_scroll] for landscape orientations.
+    ///
+    /// Fails with [ScrollError::UnsupportedOrientation] in portrait orientations and with
+    /// [ScrollError::InvalidScrollConfig] when the fixed areas leave no columns to scroll.
+    pub fn configure_horizontal_scroll(
+        &mut self,
+        fixed_left_columns: u16,
+        fixed_right_columns: u16,
+    ) -> Result<Scroller, ScrollError> {
+        if !self.state.landscape {
+            return Err(ScrollError::UnsupportedOrientation);
+        }
+        self.configure_scroll(fixed_left_columns, fixed_right_columns)
+    }
+
+    /// Configure the scroll area along the panel's long axis, with `fixed_start` lines before
+    /// it and `fixed_end` lines after it in the current orientation
+    fn configure_scroll(
+        &mut self,
+        fixed_start: u16,
+        fixed_end: u16,
+    ) -> Result<Scroller, ScrollError> {
+        let length = DisplaySize320x480::HEIGHT as u16;
+        // The fixed and scrolling areas have to add up to the 480 lines of the panel
+        let scroll_lines = length
+            .checked_sub(fixed_start)
+            .and_then(|lines| lines.checked_sub(fixed_end))
+            .filter(|&lines| lines > 0)
+            .ok_or(ScrollError::InvalidScrollConfig)?;
+
+        // The controller counts the fixed areas from the first line of the panel, which is the
+        // end of the axis when the row address order is reversed
+        let reversed = self.state.madctl & 0x80 != 0;
+        let (fixed_top_lines, fixed_bottom_lines) = match reversed {
+            false => (fixed_start, fixed_end),
+            true => (fixed_end, fixed_start),
+        };
+        self.write_command(SetScrollArea::new(
+            fixed_top_lines,
+            scroll_lines,
+            fixed_bottom_lines,
+        ))?;
+
+        Ok(Scroller::new(
+            fixed_start,
+            fixed_end,
+            length,
+            self.state.landscape,
+            reversed,
+            self.state.orientation_generation,
+        ))
+    }
+
+    /// Configure the scroll area again if the orientation changed since `scroller` was configured,
+    /// keeping its position.
+    ///
+    /// Fails with [ScrollError::UnsupportedOrientation] when the scroll axis is not along the
+    /// panel's long axis anymore.
+    fn refresh_scroller(&mut self, scroller: &mut Scroller) -> Result<(), ScrollError> {
+        if scroller.generation == self.state.orientation_generation {
+            return Ok(());
+        }
+        if scroller.landscape != self.state.landscape {
+            return Err(ScrollError::UnsupportedOrientation);
+        }
+        let position = scroller.top_offset - scroller.fixed_top_lines;
+        *scroller = self.configure_scroll(scroller.fixed_top_lines, scroller.fixed_bottom_lines)?;
+        scroller.top_offset = scroller.fixed_top_lines + position % scroller.scroll_lines();
+        Ok(())
+    }
 
-        Ok(Scroller::new(fixed_top_lines, fixed_bottom_lines, height))
+    /// Make the whole screen the scroll area again and scroll back to the start,
+    /// undoing [Ili9488::configure_vertical_scroll].
+    ///
+    /// [Scroller]s configured before are reconfigured on their next use.
+    pub fn reset_scroll(&mut self) -> Result {
+        self.write_command(SetScrollArea::new(0, DisplaySize320x480::HEIGHT as u16, 0))?;
+        self.write_command(SetScrollStart(0))?;
+        self.state.orientation_generation = self.state.orientation_generation.wrapping_add(1);
+        Ok(())
     }
 
-    pub fn scroll_vertically(&mut self, scroller: &mut Scroller, num_lines: u16) -> Result {
+    /// Scroll down by `num_lines`, wrapping around at the end of the scroll area.
+    ///
+    /// A `scroller` configured before the last orientation change is reconfigured first,
+    /// see [Ili9488::set_orientation].
+    pub fn scroll_vertically(
+        &mut self,
+        scroller: &mut Scroller,
+        num_lines: u16,
+    ) -> Result<(), ScrollError> {
+        self.refresh_scroller(scroller)?;
         scroller.top_offset += num_lines;
-        if scroller.top_offset > (scroller.height - scroller.fixed_bottom_lines) {
+        if scroller.top_offset >= (scroller.height - scroller.fixed_bottom_lines) {
             scroller.top_offset = scroller.fixed_top_lines
                 + (scroller.top_offset + scroller.fixed_bottom_lines - scroller.height)
         }
 
-        self.command(
-            Command::VerticalScrollAddr,
-            &[
-                (scroller.top_offset >> 8) as u8,
-                (scroller.top_offset & 0xff) as u8,
-            ],
-        )
+        Ok(self.write_command(SetScrollStart(scroller.scroll_start()))?)
+    }
+
+    /// Scroll right by `num_columns` with a scroller from
+    /// [Ili9488::configure_horizontal_scroll], see [Ili9488::scroll_vertically]
+    pub fn scroll_horizontally(
+        &mut self,
+        scroller: &mut Scroller,
+        num_columns: u16,
+    ) -> Result<(), ScrollError> {
+        self.scroll_vertically(scroller, num_columns)
+    }
+
+    /// Scroll to an absolute `position`, in lines from the start of the scroll area
+    pub fn set_scroll_position(
+        &mut self,
+        scroller: &mut Scroller,
+        position: u16,
+    ) -> Result<(), ScrollError> {
+        self.refresh_scroller(scroller)?;
+        scroller.top_offset = scroller.fixed_top_lines + position % scroller.scroll_lines();
+
+        Ok(self.write_command(SetScrollStart(scroller.scroll_start()))?)
+    }
+
+    /// Smoothly scroll from position `from` to position `to` in `steps` steps,
+    /// waiting `step_ms` milliseconds after each step.
+    ///
+    /// Positions are in lines from the start of the scroll area, see [Ili9488::set_scroll_position].
+    pub fn animate_scroll<D: DelayNs>(
+        &mut self,
+        scroller: &mut Scroller,
+        from: u16,
+        to: u16,
+        steps: u16,
+        delay: &mut D,
+        step_ms: u32,
+    ) -> Result<(), ScrollError> {
+        self.animate_scroll_with(scroller, from, to, steps, || delay.delay_ms(step_ms))
+    }
+
+    /// Smoothly scroll from position `from` to position `to` in `steps` steps,
+    /// calling `wait` after each step.
+    ///
+    /// `wait` can block until the next tearing effect (TE) pulse, so every step lands on a frame.
+    pub fn animate_scroll_with<F: FnMut()>(
+        &mut self,
+        scroller: &mut Scroller,
+        from: u16,
+        to: u16,
+        steps: u16,
+        mut wait: F,
+    ) -> Result<(), ScrollError> {
+        let steps = steps.max(1) as i32;
+        let distance = to as i32 - from as i32;
+        for step in 1..=steps {
+            let position = from as i32 + distance * step / steps;
+            self.set_scroll_position(scroller, position as u16)?;
+            wait();
+        }
+        Ok(())
     }
 
-    /// Change the orientation of the screen
-    pub fn set_orientation<MODE>(&mut self, orientation: MODE) -> Result
+    /// Change the orientation of the screen.
+    ///
+    /// [Scroller]s configured before are reconfigured on their next use, or fail with
+    /// [ScrollError::UnsupportedOrientation] when their axis is not the panel's long axis
+    /// anymore.
+    pub fn set_orientation<MODE>(&mut self, orientation: MODE) -> Result<&mut Self>
     where
         MODE: Mode,
     {
-        self.command(Command::MemoryAccessControl, &[orientation.mode()])?;
+        let madctl = (orientation.mode() ^ self.state.flip.madctl(orientation.is_landscape()))
+            | self.state.color_order.madctl();
+        self.write_command(SetAddressMode(madctl))?;
+        self.state.madctl = madctl;
 
-        if self.landscape ^ orientation.is_landscape() {
-            core::mem::swap(&mut self.height, &mut self.width);
+        if self.state.landscape ^ orientation.is_landscape() {
+            core::mem::swap(&mut self.state.height, &mut self.state.width);
         }
-        self.landscape = orientation.is_landscape();
-        Ok(())
+        self.state.landscape = orientation.is_landscape();
+        self.state.orientation_generation = self.state.orientation_generation.wrapping_add(1);
+        Ok(self)
+    }
+
+    /// Mirror the picture left to right, on top of the orientation, e.g. for a teleprompter
+    /// or a head-up display reflected in glass.
+    ///
+    /// Width, height and coordinates stay the same and the setting is kept when the
+    /// orientation changes. Only new drawing is mirrored, redraw the screen afterwards.
+    pub fn flip_horizontal(&mut self, flip: bool) -> Result<&mut Self> {
+        self.set_flip(Flip {
+            horizontal: flip,
+            ..self.state.flip
+        })
+    }
+
+    /// Mirror the picture top to bottom, on top of the orientation, see
+    /// [Ili9488::flip_horizontal]
+    pub fn flip_vertical(&mut self, flip: bool) -> Result<&mut Self> {
+        self.set_flip(Flip {
+            vertical: flip,
+            ..self.state.flip
+        })
+    }
+
+    fn set_flip(&mut self, flip: Flip) -> Result<&mut Self> {
+        let madctl = self.state.madctl
+            ^ self.state.flip.madctl(self.state.landscape)
+            ^ flip.madctl(self.state.landscape);
+        self.write_command(SetAddressMode(madctl))?;
+        self.state.madctl = madctl;
+        self.state.flip = flip;
+        // The scroll area depends on the row address order
+        self.state.orientation_generation = self.state.orientation_generation.wrapping_add(1);
+        Ok(self)
+    }
+
+    /// Set the order of the color channels as the panel is wired, [ColorOrder::Bgr] by default.
+    ///
+    /// Panels wired RGB show red and blue swapped until this is set to [ColorOrder::Rgb].
+    /// The color order is kept when the orientation changes.
+    ///
+    /// The setting only acts between frame memory and panel, so reading pixels back can not
+    /// tell how the panel is wired. Check with [Ili9488::draw_color_bars] instead.
+    pub fn set_color_order(&mut self, color_order: ColorOrder) -> Result<&mut Self> {
+        let madctl = (self.state.madctl & !ColorOrder::Bgr.madctl()) | color_order.madctl();
+        self.write_command(SetAddressMode(madctl))?;
+        self.state.madctl = madctl;
+        self.state.color_order = color_order;
+        Ok(self)
+    }
+
+    /// Send memory access control, pixel format and inversion to the controller again, from
+    /// the settings of the driver.
+    ///
+    /// Useful after the controller may have lost them, e.g. after a glitch on the bus or when
+    /// another driver shared the display, without a full [Ili9488::reinit].
+    pub fn refresh_configuration(&mut self) -> Result<&mut Self> {
+        self.write_command(SetAddressMode(self.state.madctl))?;
+        self.write_command(SetPixelFormat(PixelFormat::DATA))?;
+        let inversion = match self.state.panel.inverted {
+            true => ModeState::On,
+            false => ModeState::Off,
+        };
+        self.invert_mode(inversion)
     }
 
     /// Control the screen sleep mode:
-    pub fn sleep_mode(&mut self, mode: ModeState) -> Result {
+    ///
+    /// Waits 5 ms after entering and 120 ms after leaving sleep mode with the delay given
+    /// to [Ili9488::with_delay].
+    pub fn sleep_mode(&mut self, mode: ModeState) -> Result<&mut Self>
+    where
+        DELAY: DriverDelay,
+    {
         match mode {
-            ModeState::On => self.command(Command::SleepModeOn, &[]),
-            ModeState::Off => self.command(Command::SleepModeOff, &[]),
+            ModeState::On => {
+                self.backlight_off_before_blanking()?;
+                self.write_command(EnterSleepMode)?;
+                self.delay.wait_ms(5);
+            }
+            ModeState::Off => {
+                self.write_command(ExitSleepMode)?;
+                self.delay.wait_ms(120);
+            }
         }
+        self.state.panel.sleeping = matches!(mode, ModeState::On);
+        self.restore_backlight_after_wake()?;
+        Ok(self)
     }
 
     /// Control the screen display mode
-    pub fn display_mode(&mut self, mode: ModeState) -> Result {
+    pub fn display_mode(&mut self, mode: ModeState) -> Result<&mut Self> {
         match mode {
-            ModeState::On => self.command(Command::DisplayOn, &[]),
-            ModeState::Off => self.command(Command::DisplayOff, &[]),
+            ModeState::On => self.write_command(SetDisplayOn)?,
+            ModeState::Off => {
+                self.backlight_off_before_blanking()?;
+                self.write_command(SetDisplayOff)?
+            }
+        }
+        self.state.panel.display_on = matches!(mode, ModeState::On);
+        self.restore_backlight_after_wake()?;
+        Ok(self)
+    }
+
+    /// Whether the display is on and awake, showing the frame memory
+    fn is_visible(&self) -> bool {
+        self.state.panel.display_on && !self.state.panel.sleeping
+    }
+
+    /// With [PowerPolicy::BacklightFirst], switch the backlight off while the display is still
+    /// showing the frame memory
+    fn backlight_off_before_blanking(&mut self) -> Result {
+        let backlight = self.state.panel.backlight;
+        if self.state.power_policy == PowerPolicy::BacklightFirst
+            && backlight.backlight
+            && self.is_visible()
+        {
+            let off = BacklightControl {
+                backlight: false,
+                ..backlight
+            };
+            self.write_command(WriteCtrlDisplay(off.bits()))?;
+        }
+        Ok(())
+    }
+
+    /// With [PowerPolicy::BacklightFirst], restore the brightness and backlight once the
+    /// display shows the frame memory again
+    fn restore_backlight_after_wake(&mut self) -> Result {
+        let backlight = self.state.panel.backlight;
+        if self.state.power_policy == PowerPolicy::BacklightFirst
+            && backlight.backlight
+            && self.is_visible()
+        {
+            self.write_command(SetDisplayBrightness(self.state.panel.brightness))?;
+            self.write_command(WriteCtrlDisplay(backlight.bits()))?;
         }
+        Ok(())
     }
 
     /// Invert the pixel color on screen
-    pub fn invert_mode(&mut self, mode: ModeState) -> Result {
+    pub fn invert_mode(&mut self, mode: ModeState) -> Result<&mut Self> {
         match mode {
-            ModeState::On => self.command(Command::InvertOn, &[]),
-            ModeState::Off => self.command(Command::InvertOff, &[]),
+            ModeState::On => self.write_command(EnterInvertMode)?,
+            ModeState::Off => self.write_command(ExitInvertMode)?,
         }
+        self.state.panel.inverted = matches!(mode, ModeState::On);
+        Ok(self)
     }
 
     /// Idle mode reduces the number of colors to 8
-    pub fn idle_mode(&mut self, mode: ModeState) -> Result {
+    ///
+    /// See [Ili9488::set_idle_safe_colors] to keep drawings legible while in idle mode.
+    pub fn idle_mode(&mut self, mode: ModeState) -> Result<&mut Self> {
         match mode {
-            ModeState::On => self.command(Command::IdleModeOn, &[]),
-            ModeState::Off => self.command(Command::IdleModeOff, &[]),
+            ModeState::On => self.write_command(EnterIdleMode)?,
+            ModeState::Off => self.write_command(ExitIdleMode)?,
         }
+        self.state.panel.idle = matches!(mode, ModeState::On);
+        Ok(self)
+    }
+
+    /// Turn the tearing effect (TE) output on or off, it pulses at every vertical blanking.
+    ///
+    /// Wait for the pulse before drawing, e.g. with a `FramePacer` from the `async` feature,
+    /// to avoid tearing in animations.
+    pub fn tearing_effect(&mut self, mode: ModeState) -> Result<&mut Self> {
+        match mode {
+            ModeState::On => self.command(Instruction::TearingEffectLineOn, &[0x00])?,
+            ModeState::Off => self.command(Instruction::TearingEffectLineOff, &[])?,
+        }
+        Ok(self)
+    }
+
+    /// Set display brightness to the value between 0 and 255
+    pub fn brightness(&mut self, brightness: u8) -> Result<&mut Self> {
+        self.write_command(SetDisplayBrightness(brightness))?;
+        self.state.panel.brightness = brightness;
+        Ok(self)
+    }
+
+    /// Configure the brightness control block and the backlight output of the controller,
+    /// for panels whose backlight is driven by its LEDPWM pin
+    pub fn backlight_control(&mut self, control: BacklightControl) -> Result<&mut Self> {
+        self.write_command(WriteCtrlDisplay(control.bits()))?;
+        self.state.panel.backlight = control;
+        Ok(self)
     }
 
-    /// Set display brightness to the value between 0 and 255
-    pub fn brightness(&mut self, brightness: u8) -> Result {
-        self.command(Command::SetBrightness, &[brightness])
-    }
-
     /// Set adaptive brightness value equal to [AdaptiveBrightness]
-    pub fn content_adaptive_brightness(&mut self, value: AdaptiveBrightness) -> Result {
-        self.command(Command::ContentAdaptiveBrightness, &[value as _])
+    pub fn content_adaptive_brightness(&mut self, value: AdaptiveBrightness) -> Result<&mut Self> {
+        self.command(Instruction::ContentAdaptiveBrightness, &[value as _])?;
+        Ok(self)
     }
 
     /// Configure [FrameRateClockDivision] and [FrameRate] in normal mode
@@ -416,11 +1147,13 @@ where
         &mut self,
         clk_div: FrameRateClockDivision,
         frame_rate: FrameRate,
-    ) -> Result {
+    ) -> Result<&mut Self> {
         self.command(
-            Command::NormalModeFrameRate,
-            &[clk_div as _, frame_rate as _],
-        )
+            Instruction::NormalModeFrameRate,
+            &frame_rate_control(clk_div, frame_rate),
+        )?;
+        self.state.frame_rate = (clk_div, frame_rate);
+        Ok(self)
     }
 
     /// Configure [FrameRateClockDivision] and [FrameRate] in idle mode
@@ -428,90 +1161,219 @@ where
         &mut self,
         clk_div: FrameRateClockDivision,
         frame_rate: FrameRate,
-    ) -> Result {
-        self.command(Command::IdleModeFrameRate, &[clk_div as _, frame_rate as _])
+    ) -> Result<&mut Self> {
+        self.command(
+            Instruction::IdleModeFrameRate,
+            &frame_rate_control(clk_div, frame_rate),
+        )?;
+        Ok(self)
     }
-}
 
-impl<IFACE, RESET> Ili9488MemoryWrite for Ili9488<IFACE, RESET, Rgb666Mode>
-where
-    IFACE: WriteOnlyDataCommand,
-{
-    type PixelFormat = Rgb666;
+    /// Apply a [PowerProfile], setting the frame rates, idle mode, content adaptive
+    /// brightness and brightness together.
+    ///
+    /// Brightness and CABC only dim panels whose backlight is driven by the controller, see
+    /// [Ili9488::backlight_control].
+    pub fn set_power_profile(&mut self, profile: PowerProfile) -> Result<&mut Self> {
+        let (frame_rate, idle, cabc, brightness) = match profile {
+            PowerProfile::Max => (FrameRate::FrameRate60, false, AdaptiveBrightness::Off, 255),
+            PowerProfile::Balanced => (
+                FrameRate::FrameRate45,
+                false,
+                AdaptiveBrightness::UserInterfaceImage,
+                160,
+            ),
+            PowerProfile::LowPower => (
+                FrameRate::FrameRate30,
+                true,
+                AdaptiveBrightness::StillPicture,
+                64,
+            ),
+        };
+        self.normal_mode_frame_rate(FrameRateClockDivision::Fosc, frame_rate)?;
+        self.idle_mode_frame_rate(FrameRateClockDivision::Fosc, frame_rate)?;
+        self.content_adaptive_brightness(cabc)?;
+        self.brightness(brightness)?;
+        self.idle_mode(if idle { ModeState::On } else { ModeState::Off })
+    }
 
-    fn write_iter<I: IntoIterator<Item = Self::PixelFormat>>(&mut self, data: I) -> Result {
-        self.command(Command::MemoryWrite, &[])?;
-        for color in data {
-            self.interface.send_data(DataFormat::U8(&[
-                color.r() << 2,
-                color.g() << 2,
-                color.b() << 2,
-            ]))?;
-        }
-        Ok(())
+    /// Select the panel driving [InversionMode]
+    ///
+    /// Panels that flicker with the default 2-dot inversion may look better with another mode.
+    pub fn display_inversion_control(&mut self, mode: InversionMode) -> Result<&mut Self> {
+        self.command(Instruction::DisplayInversionControl, &[mode as _])?;
+        Ok(self)
     }
-    fn write_slice(&mut self, data: &[Self::PixelFormat]) -> Result {
-        self.command(Command::MemoryWrite, &[])?;
-        for color in data {
-            self.interface.send_data(DataFormat::U8(&[
-                color.r() << 2,
-                color.g() << 2,
-                color.b() << 2,
-            ]))?;
-        }
-        Ok(())
+
+    /// Configure the serial data pins and RGB interface signal polarities with [ImCtl]
+    ///
+    /// Boards that share a single SDA line for reads and writes need [SerialDataPins::Shared]
+    /// before registers can be read back.
+    pub fn interface_mode_control(&mut self, ctl: ImCtl) -> Result<&mut Self> {
+        self.command(Instruction::InterfaceModeControl, &[ctl.data()])?;
+        Ok(self)
+    }
+
+    /// Set the gamma reference and step-up voltages with [PowerConfig]
+    ///
+    /// Panels that look washed out or dim can be tuned at runtime.
+    pub fn power_control(&mut self, config: PowerConfig) -> Result<&mut Self> {
+        self.command(
+            Instruction::PowerControl1,
+            &[config.vreg1out & 0x1f, config.vreg2out & 0x1f],
+        )?;
+        self.command(Instruction::PowerControl2, &[config.step_up & 0x07 | 0x40])?;
+        Ok(self)
+    }
+
+    /// Set the common electrode voltage with [VcomConfig]
+    pub fn vcom_control(&mut self, config: VcomConfig) -> Result<&mut Self> {
+        self.command(Instruction::VCOMControl, &[0x00, config.vcm_reg, 0x80])?;
+        Ok(self)
     }
 }
-impl<IFACE, RESET> Ili9488MemoryWrite for Ili9488<IFACE, RESET, Rgb565Mode>
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY> Ili9488MemoryWrite
+    for Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
 where
     IFACE: WriteOnlyDataCommand,
+    PixelFormat: Ili9488PixelPacking,
 {
-    type PixelFormat = Rgb565;
+    type PixelFormat = PixelFormat::Color;
 
     fn write_iter<I: IntoIterator<Item = Self::PixelFormat>>(&mut self, data: I) -> Result {
-        self.command(Command::MemoryWrite, &[])?;
-        use DataFormat::U16BEIter;
-        self.interface
-            .send_data(U16BEIter(&mut data.into_iter().map(|c| c.into_storage())))
+        self.write_command(WriteMemoryStart)?;
+        self.write_pixels(data)
+    }
+    fn write_pixels<I: IntoIterator<Item = Self::PixelFormat>>(&mut self, data: I) -> Result {
+        self.write_packed(&mut PackedPixels::new(data))
     }
     fn write_slice(&mut self, data: &[Self::PixelFormat]) -> Result {
-        self.command(Command::MemoryWrite, &[])?;
-        self.interface.send_data(DataFormat::U16BEIter(
-            &mut data.into_iter().map(|c| c.into_storage()),
-        ))
+        self.write_iter(data.iter().copied())
     }
 }
-impl<IFACE, RESET> Ili9488MemoryWrite for Ili9488<IFACE, RESET, Rgb111Mode>
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
 where
     IFACE: WriteOnlyDataCommand,
+    PixelFormat: Ili9488PixelPacking,
 {
-    type PixelFormat = Rgb111;
-    // TODO: Fix implementations for embedded graphics
-    fn write_iter<I: IntoIterator<Item = Self::PixelFormat>>(&mut self, data: I) -> Result {
-        self.command(Command::MemoryWrite, &[])?;
+    /// Send pixel data already packed for the pixel format after Memory Write, e.g. the
+    /// chunks of [Ili9488::stream_frame].
+    ///
+    /// The bytes are sent like the pixels of [Ili9488MemoryWrite::write_pixels], as 16-bit
+    /// words with [DataWidth::Words].
+    pub fn write_pixel_bytes(&mut self, bytes: &[u8]) -> Result {
+        #[cfg(feature = "stats")]
+        {
+            let pixels = self.state.stats.pixels_in(bytes.len());
+            self.state.stats.pixels(pixels);
+        }
+        self.send_packed(bytes.iter().copied())
+    }
 
-        let mut data = data.into_iter();
-        while let Some(p1) = data.next() {
-            self.interface
-                .send_data(DataFormat::U8(&[(p1.into_storage() << 3)
-                    | (data.next().map(|p| p.into_storage()).unwrap_or_default())]))?;
+    /// Stream pixels to `region` as raw wire bytes, in chunks no larger than `buf`.
+    ///
+    /// The drawing window and `MemoryWrite` command are sent first, leaving the display ready
+    /// for pixel data. The pixels are then packed into `buf` and every filled chunk is
+    /// handed to `sink` together with the display, so the chunk can be passed on to a DMA
+    /// transfer while the next one is being packed. Send it with
+    /// [Ili9488::write_pixel_bytes], which takes care of the data width.
+    ///
+    /// `buf` must hold at least a pair of pixels, e.g. 6 bytes with [Rgb666Mode]. Chunks
+    /// always contain whole pairs, only the last one can end after an odd pixel.
+    ///
+    /// ```ignore
+    /// display.stream_frame(screen, pixels, &mut buf, |display, chunk| {
+    ///     display.write_pixel_bytes(chunk)
+    /// })?;
+    /// ```
+    pub fn stream_frame<I, F>(
+        &mut self,
+        region: Rectangle,
+        pixels: I,
+        buf: &mut [u8],
+        mut sink: F,
+    ) -> Result
+    where
+        I: IntoIterator<Item = PixelFormat::Color>,
+        F: FnMut(&mut Self, &[u8]) -> Result,
+    {
+        let pair = PixelFormat::Pair::default().as_ref().len();
+        let chunk_len = buf.len() - buf.len() % pair;
+        if chunk_len == 0 {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        self.set_region_window(&region)?;
+        self.write_command(WriteMemoryStart)?;
+
+        let mut bytes = PackedPixels::<PixelFormat, _>::new(pixels);
+        loop {
+            let len = buf[..chunk_len]
+                .iter_mut()
+                .zip(&mut bytes)
+                .map(|(slot, byte)| *slot = byte)
+                .count();
+            if len > 0 {
+                sink(self, &buf[..len])?;
+            }
+            if len < chunk_len {
+                return Ok(());
+            }
         }
-        Ok(())
     }
-    fn write_slice(&mut self, data: &[Self::PixelFormat]) -> Result {
-        self.command(Command::MemoryWrite, &[])?;
-        self.interface
-            .send_data(DataFormat::U8Iter(&mut data.chunks(2).map(|pixels| {
-                (pixels[0].raw() << 3) | pixels.get(1).map(|p| p.into_storage()).unwrap_or_default()
-            })))?;
-        Ok(())
+
+    /// Send the bytes of `pixels`, counting the pixels packed
+    pub(crate) fn write_packed<I>(&mut self, pixels: &mut PackedPixels<PixelFormat, I>) -> Result
+    where
+        I: Iterator<Item = PixelFormat::Color>,
+    {
+        let result = self.send_packed(&mut *pixels);
+        #[cfg(feature = "stats")]
+        self.state.stats.pixels(pixels.packed());
+        result
+    }
+
+    /// Send the bytes of packed pixels, the path all pixel data takes to the interface
+    fn send_packed(&mut self, bytes: impl Iterator<Item = u8>) -> Result {
+        #[cfg(feature = "stats")]
+        let mut sent = 0;
+        #[cfg(feature = "stats")]
+        let bytes = bytes.inspect(|_| sent += 1);
+        let result = PixelFormat::send(&mut self.interface, bytes, self.state.data_width);
+        #[cfg(feature = "stats")]
+        self.state.stats.data(sent);
+        result
     }
 }
 
-impl<IFACE, RESET> Ili9488<IFACE, RESET, Rgb666Mode>
+#[cfg(feature = "rgb666")]
+impl<IFACE, RESET, const BURST: usize, DELAY> Ili9488<IFACE, RESET, Rgb666Mode, BURST, DELAY>
 where
     IFACE: WriteOnlyDataCommand,
 {
+    /// Draw RGB888 pixels, e.g. decoded PNG or JPEG data, to the rectangle (x0, y0)-(x1, y1)
+    /// like [Ili9488::draw_raw_slice].
+    ///
+    /// The two low bits of every channel are dropped while the pixels are sent,
+    /// no converted copy is made.
+    pub fn write_rgb888_slice(
+        &mut self,
+        x0: u16,
+        y0: u16,
+        x1: u16,
+        y1: u16,
+        data: &[Rgb888],
+    ) -> Result {
+        self.retrying(|display| {
+            display.set_window(x0, y0, x1, y1)?;
+            display.write_iter(
+                data.iter()
+                    .map(|c| Rgb666::new(c.r() >> 2, c.g() >> 2, c.b() >> 2)),
+            )
+        })
+    }
     /// Draw a raw RGB565 image buffer to the display in RGB666 mode.
     ///
     /// `data` - A slice of u16 values in RGB565 big endian format.
@@ -554,7 +1416,7 @@ where
         let ratio = screen_width / original_width;
         let screen_height = (data.len() / original_width as usize) as u16 * ratio;
         self.set_window(x0, y0, x0 + screen_width - 1, y0 + screen_height - 1)?;
-        self.command(Command::MemoryWrite, &[])?;
+        self.write_command(WriteMemoryStart)?;
         // For each horizontal line
         //  For each pixel, repeat it ratio times
         //  Repeat expanded horizontal line ratio times
@@ -563,29 +1425,81 @@ where
                 for pixel in line {
                     for _ in 0..ratio {
                         // Convert rgb565 to rgb666
-                        self.interface.send_data(DataFormat::U8(&[
+                        self.write_pixel_bytes(&[
                             ((pixel & 0xF800) >> 8) as u8,
                             ((pixel & 0x07E0) >> 3) as u8,
                             (pixel & 0x001F << 3) as u8,
-                        ]))?;
+                        ])?;
                     }
                 }
             }
         }
         Ok(())
     }
+    /// Draw a 4 bits per pixel image through a 16 color `palette`.
+    ///
+    /// `data` holds two pixels per byte, the first in the high nibble, in rows of
+    /// `region.size.width` pixels. Indexed images take a quarter of the space of raw images.
+    pub fn draw_indexed_image(
+        &mut self,
+        region: Rectangle,
+        palette: &[Rgb666; 16],
+        data: &[u8],
+    ) -> Result {
+        let len = region.size.width as usize * region.size.height as usize;
+        if data.len() < len.div_ceil(2) {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        self.set_region_window(&region)?;
+        let pixels = data
+            .iter()
+            .flat_map(|byte| [byte >> 4, byte & 0x0f])
+            .take(len)
+            .map(|index| palette[index as usize]);
+        self.write_iter(pixels)
+    }
+    /// Draw `region` one row at a time, for images that do not fit in RAM,
+    /// e.g. streamed from an SD card or external flash.
+    ///
+    /// `row_provider` is called for every row, top to bottom, to fill the row's 3 bytes per pixel,
+    /// red, green and blue, into the first `3 * region.size.width` bytes of `line_buffer`.
+    /// The 2 least significant bits of every byte are ignored, so 24-bit RGB data such as the
+    /// rows of a BMP file can be passed as is.
+    ///
+    /// ```ignore
+    /// display.draw_streamed(region, &mut line, |row| {
+    ///     file.read_exact(row).map_err(|_| DisplayError::BusWriteError)
+    /// })?;
+    /// ```
+    pub fn draw_streamed<F>(
+        &mut self,
+        region: Rectangle,
+        line_buffer: &mut [u8],
+        mut row_provider: F,
+    ) -> Result
+    where
+        F: FnMut(&mut [u8]) -> Result,
+    {
+        let row = line_buffer
+            .get_mut(..region.size.width as usize * 3)
+            .ok_or(DisplayError::OutOfBoundsError)?;
+        self.set_region_window(&region)?;
+        self.write_command(WriteMemoryStart)?;
+        for _ in 0..region.size.height {
+            row_provider(row)?;
+            self.write_pixel_bytes(row)?;
+        }
+        Ok(())
+    }
 }
-impl<IFACE, RESET, PixelFormat> Ili9488<IFACE, RESET, PixelFormat>
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
 where
     Self: Ili9488MemoryWrite,
     IFACE: WriteOnlyDataCommand,
     PixelFormat: Ili9488PixelFormat,
 {
-    pub fn draw_raw_iter<
-        I: IntoIterator<
-            Item = <Ili9488<IFACE, RESET, PixelFormat> as Ili9488MemoryWrite>::PixelFormat,
-        >,
-    >(
+    pub fn draw_raw_iter<I: IntoIterator<Item = Color<IFACE, RESET, PixelFormat, BURST, DELAY>>>(
         &mut self,
         x0: u16,
         y0: u16,
@@ -609,80 +1523,670 @@ where
         y0: u16,
         x1: u16,
         y1: u16,
-        data: &[<Ili9488<IFACE, RESET, PixelFormat> as Ili9488MemoryWrite>::PixelFormat],
+        data: &[Color<IFACE, RESET, PixelFormat, BURST, DELAY>],
+    ) -> Result {
+        self.retrying(|display| {
+            display.set_window(x0, y0, x1, y1)?;
+            display.write_slice(data)
+        })
+    }
+
+    /// Fill the rectangle (x0, y0)-(x1, y1) with `color`
+    fn fill_rect(
+        &mut self,
+        x0: u16,
+        y0: u16,
+        x1: u16,
+        y1: u16,
+        color: Color<IFACE, RESET, PixelFormat, BURST, DELAY>,
     ) -> Result {
+        let len = (x1 - x0 + 1) as usize * (y1 - y0 + 1) as usize;
+        self.retrying(|display| {
+            display.draw_raw_iter(x0, y0, x1, y1, core::iter::repeat_n(color, len))
+        })
+    }
+
+    /// Set `region` as the window of [Ili9488::rewrite], for widgets that update the same
+    /// rectangle every frame.
+    ///
+    /// The region must be on screen and not empty.
+    ///
+    /// ```ignore
+    /// display.set_draw_region(area)?;
+    /// loop {
+    ///     render(&mut buffer);
+    ///     display.rewrite(buffer.iter().copied())?;
+    /// }
+    /// ```
+    pub fn set_draw_region(&mut self, region: Rectangle) -> Result {
+        self.set_region_window(&region)?;
+        self.state.draw_region = self.state.panel.window;
+        Ok(())
+    }
+    /// Write `pixels` to the region set with [Ili9488::set_draw_region].
+    ///
+    /// Only Memory Write and the pixels are sent while the window is still set. After other
+    /// drawing in between the window is set again first.
+    pub fn rewrite<I>(&mut self, pixels: I) -> Result
+    where
+        I: IntoIterator<Item = Color<IFACE, RESET, PixelFormat, BURST, DELAY>>,
+    {
+        let [x0, y0, x1, y1] = self
+            .state
+            .draw_region
+            .ok_or(DisplayError::OutOfBoundsError)?;
         self.set_window(x0, y0, x1, y1)?;
-        self.write_slice(data)
+        self.write_iter(pixels)
+    }
+    /// Draw `buf` back into `region`, row by row, e.g. to remove a popup or menu without
+    /// redrawing everything underneath it.
+    ///
+    /// `buf` holds what was underneath, read with [Ili9488::save_region] where the display
+    /// can be read back, or kept up to date by the caller as a shadow of the region otherwise.
+    /// It must hold at least as many pixels as `region`.
+    pub fn restore_region(
+        &mut self,
+        region: Rectangle,
+        buf: &[Color<IFACE, RESET, PixelFormat, BURST, DELAY>],
+    ) -> Result {
+        if region.is_zero_sized() {
+            return Ok(());
+        }
+        let len = region.size.width as usize * region.size.height as usize;
+        let buf = buf.get(..len).ok_or(DisplayError::OutOfBoundsError)?;
+        self.set_region_window(&region)?;
+        self.write_slice(buf)
+    }
+    /// Draw an image into `region`, rotated clockwise by `rotation`, e.g. to show assets made
+    /// for landscape on a display in portrait orientation.
+    ///
+    /// `data` holds the pixels in rows as the image was made, so for [Rotation::Deg90] and
+    /// [Rotation::Deg270] it is `region.size.height` pixels wide. The pixels are picked in the
+    /// order of the display while they are sent, no rotated copy is made.
+    pub fn draw_image_rotated(
+        &mut self,
+        region: Rectangle,
+        data: &[Color<IFACE, RESET, PixelFormat, BURST, DELAY>],
+        rotation: Rotation,
+    ) -> Result {
+        if region.is_zero_sized() {
+            return Ok(());
+        }
+        let (width, height) = (region.size.width as usize, region.size.height as usize);
+        let data = data
+            .get(..width * height)
+            .ok_or(DisplayError::OutOfBoundsError)?;
+        self.set_region_window(&region)?;
+        let pixels = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+        self.write_iter(pixels.map(|(x, y)| {
+            data[match rotation {
+                Rotation::Deg0 => y * width + x,
+                Rotation::Deg90 => (width - 1 - x) * height + y,
+                Rotation::Deg180 => (height - 1 - y) * width + width - 1 - x,
+                Rotation::Deg270 => x * height + height - 1 - y,
+            }]
+        }))
+    }
+
+    /// Write several regions, each with its pixels in row order, e.g. the dirty rectangles of a
+    /// UI frame.
+    ///
+    /// The regions are written top to bottom and left to right, whatever their order in
+    /// `regions`. Only the column or page address that differs from the previous region is
+    /// sent, so regions on the same rows or columns save a command. All regions are checked
+    /// before anything is written, an off screen region or one with too few pixels fails with
+    /// [DisplayError::OutOfBoundsError].
+    pub fn update_regions<C>(&mut self, regions: &[(Rectangle, &[C])]) -> Result
+    where
+        Self: Ili9488MemoryWrite<PixelFormat = C>,
+    {
+        let screen = self.bounding_box();
+        for (region, data) in regions {
+            let len = region.size.width as usize * region.size.height as usize;
+            if screen.intersection(region) != *region || data.len() < len {
+                return Err(DisplayError::OutOfBoundsError);
+            }
+        }
+        // Selection in raster order, without a buffer to sort into
+        let key = |i: usize| (regions[i].0.top_left.y, regions[i].0.top_left.x, i);
+        let mut last = None;
+        while let Some(next) = (0..regions.len())
+            .filter(|&i| last.is_none_or(|last| key(i) > last))
+            .min_by_key(|&i| key(i))
+        {
+            last = Some(key(next));
+            let (region, data) = regions[next];
+            let Some(bottom_right) = region.bottom_right() else {
+                continue;
+            };
+            let (x0, y0) = (region.top_left.x as u16, region.top_left.y as u16);
+            let (x1, y1) = (bottom_right.x as u16, bottom_right.y as u16);
+            let window = self.state.panel.window;
+            if window.map(|[x0, _, x1, _]| (x0, x1)) != Some((x0, x1)) {
+                self.write_command(SetColumnAddress::new(x0, x1))?;
+            }
+            if window.map(|[_, y0, _, y1]| (y0, y1)) != Some((y0, y1)) {
+                self.write_command(SetPageAddress::new(y0, y1))?;
+            }
+            self.state.panel.window = Some([x0, y0, x1, y1]);
+            let len = region.size.width as usize * region.size.height as usize;
+            self.write_slice(&data[..len])?;
+        }
+        Ok(())
+    }
+    /// Configures an area of the screen for software vertical scrolling.
+    ///
+    /// Unlike [Ili9488::configure_vertical_scroll] this works in every orientation, at the cost
+    /// of rewriting the whole scroll area from a caller-owned buffer on every scroll.
+    pub fn configure_software_scroll(
+        &self,
+        fixed_top_lines: u16,
+        fixed_bottom_lines: u16,
+    ) -> Result<SoftwareScroller, ScrollError> {
+        let lines = (self.state.height as u16)
+            .checked_sub(fixed_top_lines)
+            .and_then(|lines| lines.checked_sub(fixed_bottom_lines))
+            .filter(|&lines| lines > 0)
+            .ok_or(ScrollError::InvalidScrollConfig)?;
+        Ok(SoftwareScroller {
+            top: fixed_top_lines,
+            lines,
+            offset: 0,
+        })
+    }
+
+    /// Scroll the software scroll area by `num_lines` and redraw it.
+    ///
+    /// `content` holds the unscrolled content of the scroll area, `width * lines` pixels in row order.
+    pub fn software_scroll_vertically(
+        &mut self,
+        scroller: &mut SoftwareScroller,
+        num_lines: u16,
+        content: &[Color<IFACE, RESET, PixelFormat, BURST, DELAY>],
+    ) -> Result {
+        if content.len() != self.state.width * scroller.lines as usize {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        scroller.offset = (scroller.offset + num_lines) % scroller.lines;
+
+        let (tail, head) = content.split_at(scroller.offset as usize * self.state.width);
+        self.draw_raw_iter(
+            0,
+            scroller.top,
+            self.state.width as u16 - 1,
+            scroller.top + scroller.lines - 1,
+            head.iter().chain(tail).copied(),
+        )
+    }
+    /// Draw a 1 bit per pixel bitmap, like a font glyph, with `fg` for set bits and `bg` for
+    /// cleared bits, in a single window.
+    ///
+    /// Every row of `region.size.width` pixels starts on a new byte, most significant bit first.
+    pub fn blit_mono_bitmap(
+        &mut self,
+        region: Rectangle,
+        bits: &[u8],
+        fg: Color<IFACE, RESET, PixelFormat, BURST, DELAY>,
+        bg: Color<IFACE, RESET, PixelFormat, BURST, DELAY>,
+    ) -> Result {
+        let width = region.size.width as usize;
+        let row_bytes = width.div_ceil(8);
+        if bits.len() < row_bytes * region.size.height as usize {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        self.set_region_window(&region)?;
+        let pixels = bits
+            .chunks_exact(row_bytes.max(1))
+            .take(region.size.height as usize)
+            .flat_map(|row| {
+                (0..width).map(move |x| {
+                    if row[x / 8] & (0x80 >> (x % 8)) != 0 {
+                        fg
+                    } else {
+                        bg
+                    }
+                })
+            });
+        self.write_iter(pixels)
     }
     /// Fill entire screen with specfied color
+    ///
+    /// Colors with every channel either off or at its maximum, like black and white, are
+    /// filled in [Rgb111Mode] when the pixel format allows it and the `rgb111` feature is on,
+    /// sending a fraction of the data.
     pub fn clear_screen(
         &mut self,
-        color: <Ili9488<IFACE, RESET, PixelFormat> as Ili9488MemoryWrite>::PixelFormat,
+        color: Color<IFACE, RESET, PixelFormat, BURST, DELAY>,
     ) -> Result {
-        let color = core::iter::repeat(color).take(self.width * self.height);
-        self.draw_raw_iter(0, 0, self.width as u16, self.height as u16, color)
+        let (x1, y1) = (self.state.width as u16 - 1, self.state.height as u16 - 1);
+        #[cfg(feature = "rgb111")]
+        if let Some(color) = Rgb111::exact(color).filter(|_| PixelFormat::FAST_FILL) {
+            return self.with_rgb111_mode(|display| display.fill_rect(0, 0, x1, y1, color));
+        }
+        self.fill_rect(0, 0, x1, y1, color)
     }
-    /// Fast way to fill the entire screen. Only works with [Rgb111] colors
-    pub fn clear_screen_fast(&mut self, color: Rgb111) -> Result {
-        // Switch pixel format to 3 bpp
-        if PixelFormat::DATA != Rgb111Mode::DATA {
-            self.command(Command::PixelFormatSet, &[Rgb111Mode::DATA])?;
+    /// Fill entire screen with specfied color, `band_rows` rows at a time.
+    ///
+    /// `between` is called after every band, so other devices on a shared SPI bus, like an
+    /// SD card or touch controller, can be serviced instead of waiting for the whole screen.
+    /// Every band sets its own window, so the display does not mind other traffic in between.
+    ///
+    /// ```ignore
+    /// display.clear_screen_banded(Rgb666::BLACK, 40, || {
+    ///     touch.poll().map_err(|_| DisplayError::BusWriteError)
+    /// })?;
+    /// ```
+    pub fn clear_screen_banded<F>(
+        &mut self,
+        color: Color<IFACE, RESET, PixelFormat, BURST, DELAY>,
+        band_rows: u16,
+        mut between: F,
+    ) -> Result
+    where
+        F: FnMut() -> Result,
+    {
+        let band_rows = band_rows.max(1);
+        let (width, height) = (self.state.width as u16, self.state.height as u16);
+        for y0 in (0..height).step_by(band_rows as usize) {
+            let y1 = (y0 + band_rows).min(height) - 1;
+            self.fill_rect(0, y0, width - 1, y1, color)?;
+            between()?;
         }
-
+        Ok(())
+    }
+    /// Fast way to fill the entire screen. Only works with [Rgb111] colors,
+    /// [Ili9488::clear_screen] uses it for the colors that have an exact [Rgb111] equivalent
+    pub fn clear_screen_fast(&mut self, color: Rgb111) -> Result {
         // Clear the screen with 3 bpp
-        let color = (color.into_storage() << 3) | color.into_storage();
-        let mut data = core::iter::repeat(color).take(self.width * self.height / 2);
-
-        self.set_window(0, 0, self.width as u16, self.height as u16)?;
-        self.command(Command::MemoryWrite, &[])?;
-        self.interface.send_data(DataFormat::U8Iter(&mut data))?;
-
-        // Switch back to original pixel format
-        if PixelFormat::DATA != Rgb111Mode::DATA {
-            self.command(Command::PixelFormatSet, &[PixelFormat::DATA])
-        } else {
-            Ok(())
+        self.with_rgb111_mode(|display| {
+            let color = (color.into_storage() << 3) | color.into_storage();
+            let mut data =
+                core::iter::repeat_n(color, display.state.width * display.state.height / 2);
+
+            display.set_window(
+                0,
+                0,
+                display.state.width as u16 - 1,
+                display.state.height as u16 - 1,
+            )?;
+            display.write_command(WriteMemoryStart)?;
+            #[cfg(feature = "stats")]
+            {
+                display.state.stats.pixels(data.len() * 2);
+                display.state.stats.data(data.len());
+            }
+            display.interface.send_data(DataFormat::U8Iter(&mut data))
+        })
+    }
+    /// Change the orientation like [Ili9488::set_orientation], reset hardware scrolling and,
+    /// when `clear` is given, fill the screen with it, so nothing drawn for the old
+    /// orientation is left over.
+    pub fn set_orientation_and_reset<MODE: Mode>(
+        &mut self,
+        orientation: MODE,
+        clear: Option<Color<IFACE, RESET, PixelFormat, BURST, DELAY>>,
+    ) -> Result {
+        self.set_orientation(orientation)?;
+        self.reset_scroll()?;
+        match clear {
+            Some(color) => self.clear_screen(color),
+            None => Ok(()),
         }
     }
 }
 
-impl<IFACE, RESET, PixelFormat> Ili9488<IFACE, RESET, PixelFormat> {
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+{
     /// Get the current screen width. It can change based on the current orientation
     pub fn width(&self) -> usize {
-        self.width
+        self.state.width
     }
 
     /// Get the current screen heighth. It can change based on the current orientation
     pub fn height(&self) -> usize {
-        self.height
+        self.state.height
+    }
+
+    /// Nominal refresh rate in normal mode in Hz, as configured with
+    /// [Ili9488::normal_mode_frame_rate]. Useful to compute frame budgets for tearing-free animation.
+    pub fn frame_rate_hz(&self) -> f32 {
+        let (clk_div, frame_rate) = self.state.frame_rate;
+        frame_rate.hz() / (1 << clk_div as u8) as f32
+    }
+    /// Whether sleep mode was last turned on, see [Ili9488::sleep_mode]
+    pub fn is_sleeping(&self) -> bool {
+        self.state.panel.sleeping
+    }
+    /// Whether display inversion was last turned on, see [Ili9488::invert_mode]
+    pub fn is_inverted(&self) -> bool {
+        self.state.panel.inverted
+    }
+    /// Whether idle mode was last turned on, see [Ili9488::idle_mode]
+    pub fn is_idle(&self) -> bool {
+        self.state.panel.idle
+    }
+    /// Brightness last set with [Ili9488::brightness], 0 after reset
+    pub fn current_brightness(&self) -> u8 {
+        self.state.panel.brightness
+    }
+    /// Color order set with [Ili9488::set_color_order]
+    pub fn color_order(&self) -> ColorOrder {
+        self.state.color_order
+    }
+    /// Orientation last set, or `None` for a custom [Mode] that is none of the [Orientation]s
+    pub fn current_orientation(&self) -> Option<Orientation> {
+        [
+            Orientation::Portrait,
+            Orientation::PortraitFlipped,
+            Orientation::Landscape,
+            Orientation::LandscapeFlipped,
+        ]
+        .into_iter()
+        .find(|orientation| {
+            let flip = self.state.flip.madctl(orientation.is_landscape());
+            (orientation.mode() ^ flip) | self.state.color_order.madctl() == self.state.madctl
+        })
+    }
+    /// Quantize the colors drawn through [embedded_graphics_core::draw_target::DrawTarget]
+    /// to the 8 colors shown in idle mode, see [IdleSafePalette]
+    pub fn set_idle_safe_colors(&mut self, enabled: bool) {
+        self.state.idle_safe_colors = enabled;
+    }
+    /// Retry commands and drawing that fail on the bus, e.g. on long or noisy cables where
+    /// an occasional SPI fault would otherwise abort a whole frame.
+    ///
+    /// Commands, [Ili9488::draw_raw_slice], [Ili9488::clear_screen] and solid fills are
+    /// retried. Pixels from an iterator can't be sent again, [Ili9488::draw_raw_iter] and
+    /// [DrawTarget::fill_contiguous](embedded_graphics_core::draw_target::DrawTarget::fill_contiguous)
+    /// still return the first error.
+    ///
+    /// ```ignore
+    /// display.set_retry_policy(RetryPolicy::Retries(1));
+    /// ```
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.state.retry_policy = policy;
+    }
+    /// Sequence the backlight with [Ili9488::display_mode] and [Ili9488::sleep_mode],
+    /// to avoid the white flash of a lit panel without display output.
+    ///
+    /// Needs the backlight to be driven by the controller and switched on with
+    /// [Ili9488::backlight_control].
+    ///
+    /// ```ignore
+    /// display.set_power_policy(PowerPolicy::BacklightFirst);
+    /// display.backlight_control(BacklightControl {
+    ///     brightness_control: true,
+    ///     dimming: false,
+    ///     backlight: true,
+    /// })?;
+    /// display.display_mode(ModeState::Off)?; // Backlight off, then display off
+    /// display.display_mode(ModeState::On)?; // Display on, then brightness and backlight
+    /// ```
+    pub fn set_power_policy(&mut self, policy: PowerPolicy) {
+        self.state.power_policy = policy;
+    }
+    /// Send pixel data as 16-bit words, for interfaces that move words faster than bytes,
+    /// like SPI in 16-bit frame mode or the FSMC of an STM32 on an 8-bit bus.
+    ///
+    /// The bytes on the bus stay the same, only half as many items are handed to the
+    /// interface. When a write has an odd number of bytes, e.g. one [Rgb666] pixel, the last
+    /// byte is sent on its own.
+    pub fn set_data_width(&mut self, width: DataWidth) {
+        self.state.data_width = width;
+    }
+    /// Get a reference to the display interface
+    pub fn interface(&self) -> &IFACE {
+        &self.interface
     }
     /// Consumes the ILI9488, gives back the interface and reset peripherals
     pub fn release(self) -> (IFACE, RESET) {
         (self.interface, self.reset)
     }
+
+    /// Give the driver a delay to wait as long as the datasheet requires after commands,
+    /// e.g. after leaving sleep mode. Without one the driver never waits, that is left to
+    /// the caller.
+    ///
+    /// Owned delays and `&mut` references work alike.
+    ///
+    /// ```ignore
+    /// let mut display = Ili9488::new(iface, reset, &mut delay, Orientation::Portrait, Rgb666Mode)?
+    ///     .with_delay(delay);
+    /// display.sleep_mode(ModeState::Off)?; // Waits 120 ms
+    /// ```
+    pub fn with_delay<D: DelayNs>(self, delay: D) -> Ili9488<IFACE, RESET, PixelFormat, BURST, D> {
+        Ili9488 {
+            interface: self.interface,
+            reset: self.reset,
+            state: self.state,
+            delay,
+            _pixel_format: self._pixel_format,
+        }
+    }
+
+    /// Batch up to `N` pixels per burst when drawing with `draw_iter`, 64 by default.
+    ///
+    /// The burst buffer is on the stack while drawing and takes `N` colors.
+    ///
+    /// ```ignore
+    /// let mut display: Ili9488<_, _, _, 1024> = Ili9488::new(/* ... */)?.with_burst_size();
+    /// ```
+    pub fn with_burst_size<const N: usize>(self) -> Ili9488<IFACE, RESET, PixelFormat, N, DELAY> {
+        Ili9488 {
+            interface: self.interface,
+            reset: self.reset,
+            state: self.state,
+            delay: self.delay,
+            _pixel_format: self._pixel_format,
+        }
+    }
+
+    /// Run `f` with a view of the driver that uses pixel format `P`, sharing the interface.
+    ///
+    /// The controller is not told about the pixel format, that is up to the caller.
+    /// All state changed through the view is kept afterwards.
+    fn with_view<P, R>(
+        &mut self,
+        pixel_format: P,
+        f: impl FnOnce(&mut Ili9488<InterfaceRef<'_, IFACE>, (), P, BURST, &mut DELAY>) -> R,
+    ) -> R {
+        let mut view = Ili9488 {
+            interface: InterfaceRef(&mut self.interface),
+            reset: (),
+            state: self.state,
+            delay: &mut self.delay,
+            _pixel_format: pixel_format,
+        };
+        let result = f(&mut view);
+        self.state = view.state;
+        result
+    }
+}
+
+/// Mutable reference to the display interface of a driver,
+/// used by views of the driver with another pixel format
+pub struct InterfaceRef<'a, IFACE>(&'a mut IFACE);
+
+impl<IFACE: WriteOnlyDataCommand> WriteOnlyDataCommand for InterfaceRef<'_, IFACE> {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result {
+        self.0.send_commands(cmd)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.0.send_data(buf)
+    }
 }
 
 /// Scroller must be provided in order to scroll the screen. It can only be obtained
 /// by configuring the screen for scrolling.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Scroller {
+    /// Scroll start in the current orientation, as if the row address order was not reversed
     top_offset: u16,
+    /// Fixed lines at the end of the scroll axis in the current orientation
     fixed_bottom_lines: u16,
+    /// Fixed lines at the start of the scroll axis in the current orientation
     fixed_top_lines: u16,
     height: u16,
+    /// Scrolls along the x axis
+    landscape: bool,
+    /// The row address order was reversed when configured, so the panel's first line is the
+    /// end of the scroll axis
+    reversed: bool,
+    /// Orientation generation of the driver when configured
+    generation: u16,
 }
 
 impl Scroller {
-    fn new(fixed_top_lines: u16, fixed_bottom_lines: u16, height: u16) -> Scroller {
+    fn new(
+        fixed_top_lines: u16,
+        fixed_bottom_lines: u16,
+        height: u16,
+        landscape: bool,
+        reversed: bool,
+        generation: u16,
+    ) -> Scroller {
         Scroller {
             top_offset: fixed_top_lines,
             fixed_top_lines,
             fixed_bottom_lines,
             height,
+            landscape,
+            reversed,
+            generation,
+        }
+    }
+
+    fn scroll_lines(&self) -> u16 {
+        self.height - self.fixed_top_lines - self.fixed_bottom_lines
+    }
+
+    /// Vertical Scrolling Start Address for the controller, which scrolls the panel's lines
+    /// the other way when the row address order is reversed
+    fn scroll_start(&self) -> u16 {
+        if !self.reversed {
+            return self.top_offset;
         }
+        let lines = self.scroll_lines();
+        let position = (self.top_offset - self.fixed_top_lines) % lines;
+        self.fixed_bottom_lines + (lines - position) % lines
+    }
+}
+
+/// Software scroll state, obtained from [Ili9488::configure_software_scroll].
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SoftwareScroller {
+    top: u16,
+    lines: u16,
+    offset: u16,
+}
+
+/// Errors that can occur when configuring scrolling
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ScrollError {
+    /// Hardware scrolling is not available in the current orientation
+    UnsupportedOrientation,
+    /// The fixed areas leave no lines to scroll
+    InvalidScrollConfig,
+    /// Communication with the display failed
+    Display(DisplayError),
+}
+
+impl From<DisplayError> for ScrollError {
+    fn from(error: DisplayError) -> Self {
+        Self::Display(error)
+    }
+}
+
+/// Settings of Write CTRL Display, see [Ili9488::backlight_control]
+///
+/// The defaults are the ones after reset, with everything off.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BacklightControl {
+    /// Brightness control block (BCTRL), needed for [Ili9488::brightness] to take effect
+    pub brightness_control: bool,
+    /// Dim smoothly when the brightness changes (DD)
+    pub dimming: bool,
+    /// Backlight on (BL)
+    pub backlight: bool,
+}
+
+impl BacklightControl {
+    /// The parameter of Write CTRL Display
+    fn bits(self) -> u8 {
+        [
+            (self.brightness_control, 0x20),
+            (self.dimming, 0x08),
+            (self.backlight, 0x04),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .fold(0, |bits, (_, bit)| bits | bit)
     }
 }
 
+/// Mirroring of the picture in logical directions, see [Ili9488::flip_horizontal]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct Flip {
+    horizontal: bool,
+    vertical: bool,
+}
+
+impl Flip {
+    /// Memory access control bits to toggle, rows and columns are exchanged in landscape
+    fn madctl(self, landscape: bool) -> u8 {
+        // Column (MX) and row (MY) address order
+        let (x, y) = if landscape {
+            (0x80, 0x40)
+        } else {
+            (0x40, 0x80)
+        };
+        (if self.horizontal { x } else { 0 }) | (if self.vertical { y } else { 0 })
+    }
+}
+
+/// How [Ili9488::display_mode] and [Ili9488::sleep_mode] treat the backlight,
+/// see [Ili9488::set_power_policy]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PowerPolicy {
+    /// Leave the backlight alone
+    #[default]
+    Immediate,
+    /// Switch the backlight off before the display is turned off or put to sleep, and back
+    /// on with the last brightness once it is on and awake again
+    BacklightFirst,
+}
+
+/// Presets for [Ili9488::set_power_profile]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PowerProfile {
+    /// 60 Hz, full brightness and no CABC
+    Max,
+    /// 45 Hz, brightness 160 and CABC for user interfaces
+    Balanced,
+    /// 30 Hz in idle mode with 8 colors, brightness 64 and CABC for still pictures
+    LowPower,
+}
+
+/// How pixel data is handed to the interface, see [Ili9488::set_data_width]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DataWidth {
+    /// [DataFormat::U8Iter]
+    #[default]
+    Bytes,
+    /// [DataFormat::U16BEIter], most significant byte first
+    Words,
+}
+
 /// Available Adaptive Brightness values
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum AdaptiveBrightness {
     Off = 0x00,
     UserInterfaceImage = 0x01,
@@ -690,27 +2194,188 @@ pub enum AdaptiveBrightness {
     MovingImage = 0x03,
 }
 
-/// Available frame rate in Hz
+/// Parameters of the frame rate control commands, with the default 17 clocks per line
+fn frame_rate_control(clk_div: FrameRateClockDivision, frame_rate: FrameRate) -> [u8; 2] {
+    [((frame_rate as u8) << 4) | clk_div as u8, 0x11]
+}
+
+/// Frame rate selection (FRS) of the frame rate control commands.
+///
+/// Named after the nominal frame rate in Hz without clock division
+/// and with the default of 17 clocks per line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum FrameRate {
-    FrameRate119 = 0x10,
-    FrameRate112 = 0x11,
-    FrameRate106 = 0x12,
-    FrameRate100 = 0x13,
-    FrameRate95 = 0x14,
-    FrameRate90 = 0x15,
-    FrameRate86 = 0x16,
-    FrameRate83 = 0x17,
-    FrameRate79 = 0x18,
-    FrameRate76 = 0x19,
-    FrameRate73 = 0x1a,
-    FrameRate70 = 0x1b,
-    FrameRate68 = 0x1c,
-    FrameRate65 = 0x1d,
-    FrameRate63 = 0x1e,
-    FrameRate61 = 0x1f,
-}
-
-/// Frame rate clock division
+    FrameRate29 = 0x0,
+    FrameRate30 = 0x1,
+    FrameRate32 = 0x2,
+    FrameRate34 = 0x3,
+    FrameRate36 = 0x4,
+    FrameRate39 = 0x5,
+    FrameRate42 = 0x6,
+    FrameRate45 = 0x7,
+    FrameRate49 = 0x8,
+    FrameRate54 = 0x9,
+    FrameRate60 = 0xa,
+    FrameRate68 = 0xb,
+    FrameRate77 = 0xc,
+    FrameRate89 = 0xd,
+    FrameRate107 = 0xe,
+    FrameRate134 = 0xf,
+}
+
+impl FrameRate {
+    /// Nominal frame rate in Hz, from the datasheet
+    pub fn hz(self) -> f32 {
+        match self {
+            Self::FrameRate29 => 28.78,
+            Self::FrameRate30 => 30.38,
+            Self::FrameRate32 => 32.17,
+            Self::FrameRate34 => 34.14,
+            Self::FrameRate36 => 36.36,
+            Self::FrameRate39 => 38.90,
+            Self::FrameRate42 => 41.83,
+            Self::FrameRate45 => 45.25,
+            Self::FrameRate49 => 49.29,
+            Self::FrameRate54 => 54.15,
+            Self::FrameRate60 => 60.10,
+            Self::FrameRate68 => 67.50,
+            Self::FrameRate77 => 76.92,
+            Self::FrameRate89 => 89.45,
+            Self::FrameRate107 => 107.07,
+            Self::FrameRate134 => 133.87,
+        }
+    }
+}
+
+/// Available display inversion modes
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InversionMode {
+    ColumnInversion = 0x00,
+    OneDot = 0x01,
+    TwoDot = 0x02,
+}
+
+/// Usage of the data pins by the 3/4-wire serial interface.
+///
+/// Whether the 3-wire or 4-wire serial interface is used is selected by the IM pins,
+/// this only selects how the data lines are shared.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SerialDataPins {
+    /// DIN is used for input and SDO for output
+    Separate = 0x00,
+    /// DIN/SDA is used for both input and output, SDO is not used
+    Shared = 0x80,
+}
+
+/// Interface Mode Control settings
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ImCtl {
+    /// Usage of the serial data pins
+    pub serial_data_pins: SerialDataPins,
+    /// VSYNC is active high instead of active low (RGB interface)
+    pub vsync_active_high: bool,
+    /// HSYNC is active high instead of active low (RGB interface)
+    pub hsync_active_high: bool,
+    /// Data is fetched on the falling instead of the rising DOTCLK edge (RGB interface)
+    pub dotclk_falling_edge: bool,
+    /// ENABLE is active low instead of active high (RGB interface)
+    pub enable_active_low: bool,
+}
+
+impl ImCtl {
+    fn data(&self) -> u8 {
+        let mut data = self.serial_data_pins as u8;
+        for (set, bit) in [
+            (self.vsync_active_high, 0x08),
+            (self.hsync_active_high, 0x04),
+            (self.dotclk_falling_edge, 0x02),
+            (self.enable_active_low, 0x01),
+        ] {
+            if set {
+                data |= bit;
+            }
+        }
+        data
+    }
+}
+
+impl Default for ImCtl {
+    fn default() -> Self {
+        Self {
+            serial_data_pins: SerialDataPins::Separate,
+            vsync_active_high: false,
+            hsync_active_high: false,
+            dotclk_falling_edge: false,
+            enable_active_low: false,
+        }
+    }
+}
+
+/// Power Control 1 and 2 settings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PowerConfig {
+    /// VRH1 from 0 to 31, selects VREG1OUT, the positive gamma reference voltage
+    pub vreg1out: u8,
+    /// VRH2 from 0 to 31, selects VREG2OUT, the negative gamma reference voltage
+    pub vreg2out: u8,
+    /// BT from 0 to 7, selects the step-up factors of the gate driver voltages
+    pub step_up: u8,
+}
+
+impl Default for PowerConfig {
+    /// The values set by [Ili9488::new]
+    fn default() -> Self {
+        Self {
+            vreg1out: 0x17,
+            vreg2out: 0x15,
+            step_up: 0x01,
+        }
+    }
+}
+
+/// VCOM Control settings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VcomConfig {
+    /// VCM_REG, VCOM is `-2 V + vcm_reg * 15.625 mV`
+    pub vcm_reg: u8,
+}
+
+impl VcomConfig {
+    /// VCOM from -2000 mV to 0 mV, rounded to the nearest step of 15.625 mV
+    pub const fn from_millivolts(millivolts: i16) -> Self {
+        let millivolts = if millivolts < -2000 {
+            -2000
+        } else if millivolts > 0 {
+            0
+        } else {
+            millivolts
+        };
+        Self {
+            vcm_reg: (((millivolts as i32 + 2000) * 8 + 62) / 125) as u8,
+        }
+    }
+
+    /// VCOM in mV, rounded towards -2 V
+    pub const fn millivolts(&self) -> i16 {
+        (self.vcm_reg as i32 * 125 / 8 - 2000) as i16
+    }
+}
+
+impl Default for VcomConfig {
+    /// The value set by [Ili9488::new]
+    fn default() -> Self {
+        Self { vcm_reg: 0x12 }
+    }
+}
+
+/// Frame rate clock division (DIVA)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum FrameRateClockDivision {
     Fosc = 0x00,
     FoscDiv2 = 0x01,
@@ -718,38 +2383,906 @@ pub enum FrameRateClockDivision {
     FoscDiv8 = 0x03,
 }
 
-#[derive(Clone, Copy)]
-enum Command {
-    NOP = 0x00,
-    SoftwareReset = 0x01,
-    SleepModeOn = 0x10,
-    SleepModeOff = 0x11,
-    InvertOff = 0x20,
-    InvertOn = 0x21,
-    DisplayOff = 0x28,
-    DisplayOn = 0x29,
-    ColumnAddressSet = 0x2a,
-    PageAddressSet = 0x2b,
-    MemoryWrite = 0x2c,
-    VerticalScrollDefine = 0x33,
-    MemoryAccessControl = 0x36,
-    VerticalScrollAddr = 0x37,
-    IdleModeOff = 0x38,
-    IdleModeOn = 0x39,
-    PixelFormatSet = 0x3a,
-    // MemoryWriteContinue = 0x3c,
-    SetBrightness = 0x51,
-    ContentAdaptiveBrightness = 0x55,
-    InterfaceModeControl = 0xb0,
-    NormalModeFrameRate = 0xb1,
-    IdleModeFrameRate = 0xb2,
-    DisplayInversionControl = 0xb4,
-    DisplayFunctionControl = 0xb6,
-    EntryModeSet = 0xb7,
-    PowerControl1 = 0xc0,
-    PowerControl2 = 0xc1,
-    VCOMControl = 0xc5,
-    PositiveGammaControl = 0xe0,
-    NegativeGammaControl = 0xe1,
-    AdjustControl3 = 0xf7,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, MockDelay, MockInterface, MockPin, Transaction::Data};
+    use crate::sim;
+    use embedded_graphics_core::pixelcolor::Rgb565;
+    use embedded_graphics_core::prelude::{Point, Size};
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    fn init_sequence() {
+        let mut delay = MockDelay::default();
+        let display = Ili9488::new(
+            MockInterface::new(),
+            MockPin::default(),
+            &mut delay,
+            Orientation::Landscape,
+            Rgb666Mode,
+        )
+        .unwrap();
+
+        let commands: Vec<u8> = display.interface.commands().iter().map(|c| c.0).collect();
+        assert_eq!(
+            commands,
+            [
+                0x00, 0x01, 0xe0, 0xe1, 0xc0, 0xc1, 0xc5, 0xb0, 0xb1, 0xb4, 0xb6, 0xb7, 0xf7, 0x36,
+                0x3a, 0x11, 0x36, 0x29
+            ]
+        );
+        assert_eq!(display.interface.commands()[14], (0x3a, vec![0x66]));
+        assert_eq!(display.interface.commands()[16], (0x36, vec![0x28]));
+        assert_eq!(display.reset.states, [true, false, true]);
+        assert_eq!(delay.elapsed_ns, 325_000_000);
+    }
+
+    #[test]
+    fn set_window_byte_order() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        display
+            .draw_raw_slice(0x0102, 0x0304, 0x0105, 0x0307, &[])
+            .unwrap();
+
+        let commands = display.interface.commands();
+        assert_eq!(commands[0], (0x2a, vec![0x01, 0x02, 0x01, 0x05]));
+        assert_eq!(commands[1], (0x2b, vec![0x03, 0x04, 0x03, 0x07]));
+        assert_eq!(commands[2].0, 0x2c);
+    }
+
+    #[test]
+    fn set_window_cached() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        let pixels = [Rgb666::RED; 4];
+        display.draw_raw_slice(10, 10, 11, 11, &pixels).unwrap();
+        display.draw_raw_slice(10, 10, 11, 11, &pixels).unwrap();
+        display.draw_raw_slice(10, 10, 13, 10, &pixels).unwrap();
+        display.write_raw(0x2a, &[0, 0, 0, 0]).unwrap();
+        display.draw_raw_slice(10, 10, 13, 10, &pixels).unwrap();
+
+        let instructions: Vec<u8> = display.interface.commands().iter().map(|c| c.0).collect();
+        assert_eq!(
+            instructions,
+            [0x2a, 0x2b, 0x2c, 0x2c, 0x2a, 0x2b, 0x2c, 0x2a, 0x2a, 0x2b, 0x2c]
+        );
+    }
+
+    #[test]
+    fn rewrite_draw_region() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        let pixels = [Rgb666::RED; 4];
+        assert!(display.rewrite(pixels).is_err());
+        display
+            .set_draw_region(Rectangle::new(Point::new(10, 10), Size::new(2, 2)))
+            .unwrap();
+        display.rewrite(pixels).unwrap();
+        display.rewrite(pixels).unwrap();
+        display.draw_raw_slice(0, 0, 0, 0, &[Rgb666::BLUE]).unwrap();
+        display.rewrite(pixels).unwrap();
+
+        let instructions: Vec<u8> = display.interface.commands().iter().map(|c| c.0).collect();
+        assert_eq!(
+            instructions,
+            [0x2a, 0x2b, 0x2c, 0x2c, 0x2a, 0x2b, 0x2c, 0x2a, 0x2b, 0x2c]
+        );
+        assert!(display
+            .set_draw_region(Rectangle::new(Point::new(319, 0), Size::new(2, 1)))
+            .is_err());
+    }
+
+    #[test]
+    fn retry_policy() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        let pixels = [Rgb666::RED; 4];
+        display.draw_raw_slice(10, 10, 11, 11, &pixels).unwrap();
+        display.interface.fail_data(1);
+        assert!(matches!(
+            display.draw_raw_slice(10, 10, 11, 11, &pixels),
+            Err(DisplayError::BusWriteError)
+        ));
+
+        display.set_retry_policy(RetryPolicy::Retries(1));
+        display.interface.clear();
+        display.interface.fail_data(1);
+        display.draw_raw_slice(10, 10, 11, 11, &pixels).unwrap();
+        // NOP after the failed memory write, then the window is set again
+        let commands = display.interface.commands();
+        let instructions: Vec<u8> = commands.iter().map(|c| c.0).collect();
+        assert_eq!(instructions, [0x2c, 0x00, 0x2a, 0x2b, 0x2c]);
+        assert_eq!(commands[4].1.len(), 4 * 3);
+
+        display.interface.fail_data(2);
+        assert!(matches!(
+            display.clear_screen(Rgb666::BLUE),
+            Err(DisplayError::BusWriteError)
+        ));
+    }
+
+    #[test]
+    fn clear_screen_strategy() {
+        let data_len = |interface: &MockInterface| interface.commands().last().unwrap().1.len();
+        let mut rgb666 = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        rgb666.interface.clear();
+        rgb666.clear_screen(Rgb666::new(63, 20, 0)).unwrap();
+        assert_eq!(data_len(&rgb666.interface), 320 * 480 * 3);
+        rgb666.interface.clear();
+        rgb666.clear_screen(Rgb666::CYAN).unwrap();
+        let instructions: Vec<u8> = rgb666.interface.commands().iter().map(|c| c.0).collect();
+        assert_eq!(instructions, [0x3a, 0x2c, 0x3a]);
+        assert_eq!(rgb666.interface.commands()[1].1, [0b011_011; 320 * 480 / 2]);
+        assert_eq!(data_len(&rgb666.interface), 1);
+
+        // No 3 bpp on a 16-bit bus
+        let mut bus16 = mock::display_with(Orientation::Portrait, Rgb666Bus16Mode);
+        bus16.interface.clear();
+        bus16.clear_screen(Rgb666::CYAN).unwrap();
+        assert_eq!(data_len(&bus16.interface), 320 * 480 * 3);
+
+        // Nor in 16 bpp, which is only used on the 16-bit bus
+        let mut rgb565 = mock::display_with(Orientation::Portrait, Rgb565Mode);
+        rgb565.interface.clear();
+        for color in [Rgb565::BLACK, Rgb565::WHITE, Rgb565::RED] {
+            rgb565.clear_screen(color).unwrap();
+        }
+        let commands = rgb565.interface.commands();
+        assert!(!commands.contains(&(0x3a, vec![0x01])));
+        assert_eq!(data_len(&rgb565.interface), 320 * 480 * 2);
+    }
+
+    #[test]
+    fn rgb666_write() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        display
+            .draw_raw_slice(0, 0, 1, 0, &[Rgb666::new(63, 0, 1), Rgb666::new(1, 2, 3)])
+            .unwrap();
+
+        assert_eq!(
+            display.interface.commands()[2],
+            (0x2c, vec![0xfc, 0x00, 0x04, 0x04, 0x08, 0x0c])
+        );
+    }
+
+    #[test]
+    fn rgb888_slice() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        display
+            .write_rgb888_slice(0, 0, 1, 0, &[Rgb888::new(0xff, 0x81, 0x02), Rgb888::WHITE])
+            .unwrap();
+        assert_eq!(
+            display.interface.commands()[2],
+            (0x2c, vec![0xfc, 0x80, 0x00, 0xfc, 0xfc, 0xfc])
+        );
+    }
+
+    #[test]
+    fn rgb666_words() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        display.set_data_width(DataWidth::Words);
+        let pixels = [Rgb666::new(63, 0, 1), Rgb666::new(1, 2, 3)];
+        display.draw_raw_slice(0, 0, 1, 0, &pixels).unwrap();
+        display.draw_raw_slice(0, 0, 0, 0, &pixels[..1]).unwrap();
+
+        let transactions = display.interface.transactions();
+        assert_eq!(
+            transactions[6],
+            Data(vec![0xfc, 0x00, 0x04, 0x04, 0x08, 0x0c])
+        );
+        // The odd byte of a single pixel is sent on its own
+        assert_eq!(
+            transactions[transactions.len() - 2..],
+            [Data(vec![0xfc, 0x00]), Data(vec![0x04])]
+        );
+    }
+
+    #[test]
+    fn rgb666_bus16_packing() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Bus16Mode);
+        display.interface.clear();
+        display
+            .draw_raw_slice(0, 0, 2, 0, &[Rgb666::new(1, 2, 3); 3])
+            .unwrap();
+
+        // The odd pixel is padded to two words with the first byte of the first pixel
+        assert_eq!(
+            display.interface.commands()[2],
+            (
+                0x2c,
+                vec![0x04, 0x08, 0x0c, 0x04, 0x08, 0x0c, 0x04, 0x08, 0x0c, 0x04]
+            )
+        );
+    }
+
+    #[test]
+    fn rgb111_packing() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb111Mode);
+        display.interface.clear();
+        display
+            .draw_raw_slice(0, 0, 2, 0, &[Rgb111::RED, Rgb111::CYAN, Rgb111::WHITE])
+            .unwrap();
+        display
+            .draw_raw_iter(0, 0, 2, 0, [Rgb111::RED, Rgb111::CYAN, Rgb111::WHITE])
+            .unwrap();
+
+        let commands = display.interface.commands();
+        assert_eq!(commands[2], (0x2c, vec![0b100_011, 0b111_100]));
+        // Same window, so it is not set again
+        assert_eq!(commands[3], (0x2c, vec![0b100_011, 0b111_100]));
+    }
+
+    #[test]
+    fn bounding_box_follows_orientation() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        assert_eq!(display.bounding_box().size, Size::new(320, 480));
+
+        display.set_orientation(Orientation::Landscape).unwrap();
+        assert_eq!(display.bounding_box().size, Size::new(480, 320));
+
+        display
+            .set_orientation_and_reset(Orientation::Portrait, Some(Rgb666::BLACK))
+            .unwrap();
+        assert_eq!(display.bounding_box().size, Size::new(320, 480));
+        let commands = display.interface.commands();
+        // Black is cleared in 3 bpp
+        assert_eq!(
+            commands[commands.len() - 7..commands.len() - 3],
+            [
+                (0x33, vec![0, 0, 0x01, 0xe0, 0, 0]),
+                (0x37, vec![0, 0]),
+                (0x3a, vec![0x01]),
+                (0x2a, vec![0, 0, 0x01, 0x3f]),
+            ]
+        );
+
+        // Scrollers are configured again after a reset
+        let mut scroller = display.configure_vertical_scroll(10, 10).unwrap();
+        display.reset_scroll().unwrap();
+        display.interface.clear();
+        display.scroll_vertically(&mut scroller, 1).unwrap();
+        assert_eq!(display.interface.commands()[0].0, 0x33);
+    }
+
+    #[test]
+    fn flip_keeps_orientation() {
+        let mut display = sim::display_with(Orientation::Landscape, Rgb666Mode);
+        display.flip_horizontal(true).unwrap();
+        assert_eq!(display.current_orientation(), Some(Orientation::Landscape));
+        assert_eq!(display.bounding_box().size, Size::new(480, 320));
+        display
+            .draw_raw_slice(0, 10, 0, 10, &[Rgb666::RED])
+            .unwrap();
+        display.flip_horizontal(false).unwrap();
+        assert_eq!(display.interface.pixel(479, 10), Some(Rgb888::RED));
+
+        // Kept across orientation changes, in logical directions
+        display.flip_vertical(true).unwrap();
+        display.set_orientation(Orientation::Portrait).unwrap();
+        assert_eq!(display.current_orientation(), Some(Orientation::Portrait));
+        display.draw_raw_slice(5, 0, 5, 0, &[Rgb666::BLUE]).unwrap();
+        display.flip_vertical(false).unwrap();
+        assert_eq!(display.interface.pixel(5, 479), Some(Rgb888::BLUE));
+    }
+
+    #[test]
+    fn refresh_configuration() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        display.set_orientation(Orientation::Landscape).unwrap();
+        display.flip_vertical(true).unwrap();
+        display.invert_mode(ModeState::On).unwrap();
+
+        let mut display = display.change_pixel_format(Rgb565Mode).unwrap();
+        assert_eq!(display.current_orientation(), Some(Orientation::Landscape));
+        assert_eq!(display.width(), 480);
+        display.interface.clear();
+        display.refresh_configuration().unwrap();
+        assert_eq!(
+            display.interface.commands(),
+            [(0x36, vec![0x68]), (0x3a, vec![0x55]), (0x21, Vec::new())]
+        );
+    }
+
+    #[test]
+    fn mode_state_getters() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        assert!(!display.is_sleeping());
+        assert!(!display.is_inverted());
+        assert_eq!(display.current_orientation(), Some(Orientation::Portrait));
+
+        display
+            .sleep_mode(ModeState::On)
+            .unwrap()
+            .invert_mode(ModeState::On)
+            .unwrap()
+            .idle_mode(ModeState::On)
+            .unwrap()
+            .brightness(200)
+            .unwrap();
+        display
+            .set_orientation(Orientation::LandscapeFlipped)
+            .unwrap();
+        assert!(display.is_sleeping());
+        assert!(display.is_inverted());
+        assert!(display.is_idle());
+        assert_eq!(display.current_brightness(), 200);
+        assert_eq!(
+            display.current_orientation(),
+            Some(Orientation::LandscapeFlipped)
+        );
+
+        let mut display = display.change_pixel_format(Rgb565Mode).unwrap();
+        display
+            .with_rgb111_mode(|d| d.idle_mode(ModeState::Off).map(drop))
+            .unwrap();
+        assert!(!display.is_idle());
+        assert!(display.is_inverted());
+
+        // Gamma and contrast set through a view are kept too
+        display
+            .with_rgb111_mode(|d| {
+                d.set_gamma_preset(gamma::presets::VIVID)?;
+                d.set_display_contrast(200).map(drop)
+            })
+            .unwrap();
+        assert_eq!(display.state.gamma, Some(gamma::presets::VIVID));
+        assert_eq!(display.display_contrast(), 200);
+
+        display
+            .set_orientation(RotationMode::new(Rotation::Deg0).mirrored(true))
+            .unwrap();
+        assert_eq!(display.current_orientation(), None);
+    }
+
+    #[test]
+    fn draw_image_rotated() {
+        let mut display = sim::display();
+        // 3x2 image, made in landscape
+        let image = [
+            Rgb666::RED,
+            Rgb666::GREEN,
+            Rgb666::BLUE,
+            Rgb666::WHITE,
+            Rgb666::CYAN,
+            Rgb666::YELLOW,
+        ];
+        let tall = Rectangle::new(Point::new(10, 10), Size::new(2, 3));
+        display
+            .draw_image_rotated(tall, &image, Rotation::Deg90)
+            .unwrap();
+        display
+            .draw_image_rotated(
+                Rectangle::new(Point::new(20, 10), tall.size),
+                &image,
+                Rotation::Deg270,
+            )
+            .unwrap();
+        let wide = Rectangle::new(Point::new(30, 10), Size::new(3, 2));
+        display
+            .draw_image_rotated(wide, &image, Rotation::Deg180)
+            .unwrap();
+        assert!(display
+            .draw_image_rotated(wide, &image[1..], Rotation::Deg0)
+            .is_err());
+
+        let sim = display.interface();
+        // The first row of the image runs down the right column
+        assert_eq!(sim.pixel(11, 10), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(11, 12), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(10, 10), Some(Rgb888::WHITE));
+        // And up the left column
+        assert_eq!(sim.pixel(20, 12), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(20, 10), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(21, 12), Some(Rgb888::WHITE));
+        assert_eq!(sim.pixel(30, 10), Some(Rgb888::YELLOW));
+        assert_eq!(sim.pixel(32, 11), Some(Rgb888::RED));
+    }
+
+    #[test]
+    fn update_regions() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        let red = [Rgb666::RED; 2];
+        let blue = [Rgb666::BLUE; 2];
+        let regions = [
+            (Rectangle::new(Point::new(4, 5), Size::new(2, 1)), &blue[..]),
+            (Rectangle::new(Point::new(0, 0), Size::new(1, 1)), &red[..1]),
+            (Rectangle::new(Point::new(4, 0), Size::new(2, 1)), &red[..]),
+        ];
+        display.update_regions(&regions).unwrap();
+        assert_eq!(
+            display.interface.commands(),
+            [
+                (0x2a, vec![0, 0, 0, 0]),
+                (0x2b, vec![0, 0, 0, 0]),
+                (0x2c, vec![0xfc, 0, 0]),
+                // Same rows
+                (0x2a, vec![0, 4, 0, 5]),
+                (0x2c, vec![0xfc, 0, 0, 0xfc, 0, 0]),
+                // Same columns
+                (0x2b, vec![0, 5, 0, 5]),
+                (0x2c, vec![0, 0, 0xfc, 0, 0, 0xfc]),
+            ]
+        );
+
+        display.interface.clear();
+        let short = [regions[0], (regions[2].0, &red[..1])];
+        assert!(matches!(
+            display.update_regions(&short),
+            Err(DisplayError::OutOfBoundsError)
+        ));
+        let off_screen = [(
+            Rectangle::new(Point::new(319, 0), Size::new(2, 1)),
+            &red[..],
+        )];
+        assert!(display.update_regions(&off_screen).is_err());
+        assert!(display.interface.commands().is_empty());
+    }
+
+    #[test]
+    fn power_profile() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        display.set_power_profile(PowerProfile::LowPower).unwrap();
+        assert_eq!(
+            display.interface.commands(),
+            [
+                (0xb1, vec![0x10, 0x11]),
+                (0xb2, vec![0x10, 0x11]),
+                (0x55, vec![0x02]),
+                (0x51, vec![64]),
+                (0x39, vec![]),
+            ]
+        );
+        assert!(display.is_idle());
+        assert_eq!(display.frame_rate_hz(), FrameRate::FrameRate30.hz());
+
+        display.set_power_profile(PowerProfile::Max).unwrap();
+        assert!(!display.is_idle());
+        assert_eq!(display.current_brightness(), 255);
+        assert_eq!(display.frame_rate_hz(), FrameRate::FrameRate60.hz());
+    }
+
+    #[test]
+    fn power_policy_sequences_backlight() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        display.set_power_policy(PowerPolicy::BacklightFirst);
+        display
+            .backlight_control(BacklightControl {
+                brightness_control: true,
+                dimming: false,
+                backlight: true,
+            })
+            .unwrap()
+            .brightness(180)
+            .unwrap();
+        display.interface.clear();
+
+        display.display_mode(ModeState::Off).unwrap();
+        display.sleep_mode(ModeState::On).unwrap();
+        display.sleep_mode(ModeState::Off).unwrap();
+        display.display_mode(ModeState::On).unwrap();
+        assert_eq!(
+            display.interface.commands(),
+            [
+                (0x53, vec![0x20]),
+                (0x28, vec![]),
+                (0x10, vec![]),
+                (0x11, vec![]),
+                (0x29, vec![]),
+                (0x51, vec![180]),
+                (0x53, vec![0x24]),
+            ]
+        );
+
+        display.set_power_policy(PowerPolicy::Immediate);
+        display.interface.clear();
+        display.display_mode(ModeState::Off).unwrap();
+        assert_eq!(display.interface.commands(), [(0x28, vec![])]);
+    }
+
+    #[test]
+    fn orientation_swaps_width_and_height() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        assert_eq!((display.width(), display.height()), (320, 480));
+
+        display.set_orientation(Orientation::Landscape).unwrap();
+        assert_eq!((display.width(), display.height()), (480, 320));
+
+        display
+            .set_orientation(Orientation::LandscapeFlipped)
+            .unwrap();
+        assert_eq!((display.width(), display.height()), (480, 320));
+
+        display
+            .set_orientation(Orientation::PortraitFlipped)
+            .unwrap();
+        assert_eq!((display.width(), display.height()), (320, 480));
+        assert_eq!(
+            display.interface.commands().last(),
+            Some(&(0x36, vec![0x88]))
+        );
+    }
+
+    #[test]
+    fn rotation_mode_matches_orientation() {
+        for orientation in [
+            Orientation::Portrait,
+            Orientation::Landscape,
+            Orientation::PortraitFlipped,
+            Orientation::LandscapeFlipped,
+        ] {
+            let (mode, landscape) = (orientation.mode(), orientation.is_landscape());
+            let rotation_mode = RotationMode::from(orientation);
+            assert_eq!(rotation_mode.mode(), mode);
+            assert_eq!(rotation_mode.is_landscape(), landscape);
+        }
+
+        let mode = RotationMode::new(Rotation::Deg0).mirrored(true);
+        assert_eq!(mode.mode(), 0x00);
+        assert_eq!(
+            RotationMode::new(Rotation::Deg90).mirrored(true).mode(),
+            0xa0
+        );
+    }
+
+    #[test]
+    fn color_order_kept_across_orientations() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        display.set_color_order(ColorOrder::Rgb).unwrap();
+        display.set_orientation(Orientation::Landscape).unwrap();
+        assert_eq!(display.current_orientation(), Some(Orientation::Landscape));
+        display.set_color_order(ColorOrder::Bgr).unwrap();
+
+        let madctl: Vec<Vec<u8>> = display
+            .interface
+            .commands()
+            .into_iter()
+            .filter(|c| c.0 == 0x36)
+            .map(|c| c.1)
+            .collect();
+        assert_eq!(madctl, [vec![0x40], vec![0x20], vec![0x28]]);
+    }
+
+    #[test]
+    fn invalid_scroll_config() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        for (top, bottom) in [(400, 100), (240, 240), (u16::MAX, 1)] {
+            assert!(matches!(
+                display.configure_vertical_scroll(top, bottom),
+                Err(ScrollError::InvalidScrollConfig)
+            ));
+            assert!(matches!(
+                display.configure_software_scroll(top, bottom),
+                Err(ScrollError::InvalidScrollConfig)
+            ));
+        }
+        assert!(display.interface.commands().is_empty());
+        let scroller = display.configure_vertical_scroll(240, 239).unwrap();
+        assert_eq!(scroller.scroll_lines(), 1);
+    }
+
+    #[test]
+    fn animate_scroll_steps() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        let mut scroller = display.configure_vertical_scroll(10, 20).unwrap();
+        display.interface.clear();
+
+        let mut waits = 0;
+        display
+            .animate_scroll_with(&mut scroller, 0, 449, 3, || waits += 1)
+            .unwrap();
+
+        assert_eq!(waits, 3);
+        assert_eq!(
+            display.interface.commands(),
+            [
+                (0x37, vec![0x00, 0x9f]),
+                (0x37, vec![0x01, 0x35]),
+                (0x37, vec![0x01, 0xcb]),
+            ]
+        );
+    }
+
+    #[test]
+    fn scroller_follows_orientation_changes() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        let mut scroller = display.configure_vertical_scroll(10, 20).unwrap();
+        display.set_scroll_position(&mut scroller, 100).unwrap();
+
+        display.set_orientation(Orientation::Landscape).unwrap();
+        assert!(matches!(
+            display.scroll_vertically(&mut scroller, 1),
+            Err(ScrollError::UnsupportedOrientation)
+        ));
+
+        display
+            .set_orientation(Orientation::PortraitFlipped)
+            .unwrap();
+        display.interface.clear();
+        display.scroll_vertically(&mut scroller, 1).unwrap();
+        // The fixed areas swap ends on the panel and the start counts backwards
+        assert_eq!(
+            display.interface.commands(),
+            [
+                (0x33, vec![0x00, 0x14, 0x01, 0xc2, 0x00, 0x0a]),
+                (0x37, vec![0x01, 0x71]),
+            ]
+        );
+    }
+
+    #[test]
+    fn scroll_follows_madctl() {
+        // Scroll area and start for 10 fixed lines at the start, 20 at the end, position 100
+        let cases = [
+            (Orientation::Portrait, [10, 450, 20], 110u16),
+            (Orientation::Landscape, [10, 450, 20], 110),
+            (Orientation::PortraitFlipped, [20, 450, 10], 370),
+            (Orientation::LandscapeFlipped, [20, 450, 10], 370),
+        ];
+        for (orientation, area, start) in cases {
+            let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+            display.interface.clear();
+            display.set_orientation(orientation).unwrap();
+            let landscape = display.state.landscape;
+            assert!(matches!(
+                match landscape {
+                    true => display.configure_vertical_scroll(10, 20),
+                    false => display.configure_horizontal_scroll(10, 20),
+                },
+                Err(ScrollError::UnsupportedOrientation)
+            ));
+            display.interface.clear();
+            let mut scroller = match landscape {
+                true => display.configure_horizontal_scroll(10, 20),
+                false => display.configure_vertical_scroll(10, 20),
+            }
+            .unwrap();
+            display.set_scroll_position(&mut scroller, 100).unwrap();
+            let area = area.iter().flat_map(|lines: &u16| lines.to_be_bytes());
+            assert_eq!(
+                display.interface.commands(),
+                [(0x33, area.collect()), (0x37, start.to_be_bytes().to_vec())],
+                "{orientation:?}"
+            );
+
+            // Scrolling past the end wraps around to the start of the scroll area
+            display.interface.clear();
+            display.scroll_vertically(&mut scroller, 350).unwrap();
+            let start: u16 = match display.state.madctl & 0x80 {
+                0 => 10,
+                _ => 20,
+            };
+            assert_eq!(
+                display.interface.commands(),
+                [(0x37, start.to_be_bytes().to_vec())]
+            );
+        }
+
+        // A vertical flip reverses the row address order as well
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        let mut scroller = display.configure_vertical_scroll(10, 20).unwrap();
+        display.flip_vertical(true).unwrap();
+        display.interface.clear();
+        display.set_scroll_position(&mut scroller, 1).unwrap();
+        assert_eq!(
+            display.interface.commands(),
+            [
+                (0x33, vec![0x00, 0x14, 0x01, 0xc2, 0x00, 0x0a]),
+                (0x37, vec![0x01, 0xd5]),
+            ]
+        );
+    }
+
+    #[test]
+    fn rgb111_mode_is_restored_on_error() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        let result: Result = display.with_rgb111_mode(|display| {
+            display.draw_raw_iter(0, 0, 1, 0, [Rgb111::RED, Rgb111::BLUE])?;
+            Err(DisplayError::BusWriteError)
+        });
+
+        assert!(matches!(result, Err(DisplayError::BusWriteError)));
+        let commands = display.interface.commands();
+        assert_eq!(commands.first(), Some(&(0x3a, vec![0x01])));
+        assert_eq!(commands[3], (0x2c, vec![0b100_001]));
+        assert_eq!(commands.last(), Some(&(0x3a, vec![0x66])));
+    }
+
+    #[test]
+    fn clear_screen_banded() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        let mut bands = 0;
+        display
+            .clear_screen_banded(Rgb666::BLACK, 200, || {
+                bands += 1;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(bands, 3);
+
+        let commands = display.interface.commands();
+        assert_eq!(commands[7], (0x2b, vec![0x01, 0x90, 0x01, 0xdf]));
+        assert_eq!(commands[8].1.len(), 320 * 80 * 3);
+    }
+
+    #[test]
+    fn draw_streamed_rows() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        let mut row = 0;
+        display
+            .draw_streamed(
+                Rectangle::new(Point::new(2, 3), Size::new(2, 2)),
+                &mut [0; 8],
+                |buf| {
+                    row += 1;
+                    buf.fill(row);
+                    Ok(())
+                },
+            )
+            .unwrap();
+
+        let commands = display.interface.commands();
+        assert_eq!(commands[1], (0x2b, vec![0, 3, 0, 4]));
+        assert_eq!(
+            commands[2],
+            (0x2c, vec![1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2])
+        );
+    }
+
+    #[test]
+    fn streamed_words() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        display.set_data_width(DataWidth::Words);
+        let region = Rectangle::new(Point::zero(), Size::new(1, 2));
+        display
+            .draw_streamed(region, &mut [0; 3], |buf| {
+                buf.copy_from_slice(&[4, 8, 12]);
+                Ok(())
+            })
+            .unwrap();
+        // The odd byte of every row is sent on its own
+        let transactions = display.interface.transactions();
+        assert_eq!(
+            transactions[transactions.len() - 4..],
+            [
+                Data(vec![4, 8]),
+                Data(vec![12]),
+                Data(vec![4, 8]),
+                Data(vec![12])
+            ]
+        );
+
+        display.interface.clear();
+        display
+            .stream_frame(
+                region,
+                [Rgb666::new(1, 2, 3); 2],
+                &mut [0; 6],
+                |display, chunk| display.write_pixel_bytes(chunk),
+            )
+            .unwrap();
+        let transactions = display.interface.transactions();
+        assert_eq!(transactions.last(), Some(&Data(vec![4, 8, 12, 4, 8, 12])));
+    }
+
+    #[test]
+    fn stream_frame() {
+        let mut display = sim::display();
+        let region = Rectangle::new(Point::new(10, 20), Size::new(3, 2));
+        let colors = [
+            Rgb666::RED,
+            Rgb666::GREEN,
+            Rgb666::BLUE,
+            Rgb666::WHITE,
+            Rgb666::YELLOW,
+        ];
+        let mut chunks = Vec::new();
+        // Room for a pair and a half, chunks hold a pair
+        display
+            .stream_frame(region, colors, &mut [0; 9], |display, chunk| {
+                chunks.push(chunk.len());
+                display.write_pixel_bytes(chunk)
+            })
+            .unwrap();
+        assert_eq!(chunks, [6, 6, 3]);
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(10, 20), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(12, 20), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(10, 21), Some(Rgb888::WHITE));
+        assert_eq!(sim.pixel(11, 21), Some(Rgb888::YELLOW));
+        assert_eq!(sim.pixel(12, 21), Some(Rgb888::BLACK));
+
+        assert!(matches!(
+            display.stream_frame(region, colors, &mut [0; 5], |_, _| Ok(())),
+            Err(DisplayError::OutOfBoundsError)
+        ));
+    }
+
+    #[test]
+    fn draw_indexed_image() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        let mut palette = [Rgb666::BLACK; 16];
+        palette[1] = Rgb666::RED;
+        palette[15] = Rgb666::BLUE;
+        let region = Rectangle::new(Point::new(0, 0), Size::new(3, 1));
+        display
+            .draw_indexed_image(region, &palette, &[0x1f, 0x10])
+            .unwrap();
+
+        assert_eq!(
+            display.interface.commands()[2],
+            (0x2c, vec![0xfc, 0, 0, 0, 0, 0xfc, 0xfc, 0, 0])
+        );
+        assert!(display
+            .draw_indexed_image(region, &palette, &[0x1f])
+            .is_err());
+    }
+
+    #[test]
+    fn blit_mono_bitmap() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb565Mode);
+        display.interface.clear();
+        let region = Rectangle::new(Point::new(0, 0), Size::new(10, 2));
+        display
+            .blit_mono_bitmap(
+                region,
+                &[0b1000_0000, 0b0100_0000, 0x00, 0b1100_0000],
+                Rgb565::WHITE,
+                Rgb565::BLACK,
+            )
+            .unwrap();
+
+        let data = &display.interface.commands()[2].1;
+        let pixels: Vec<bool> = data.chunks(2).map(|p| p[0] != 0).collect();
+        let mut expected = [false; 20];
+        expected[0] = true;
+        expected[9] = true;
+        expected[18] = true;
+        expected[19] = true;
+        assert_eq!(pixels, expected);
+    }
+
+    #[test]
+    fn frame_rate() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Mode);
+        display.interface.clear();
+        assert!((display.frame_rate_hz() - 60.1).abs() < 0.01);
+
+        display
+            .normal_mode_frame_rate(FrameRateClockDivision::FoscDiv2, FrameRate::FrameRate89)
+            .unwrap();
+        assert_eq!(display.interface.commands()[0], (0xb1, vec![0xd1, 0x11]));
+        assert!((display.frame_rate_hz() - 44.725).abs() < 0.01);
+    }
+
+    #[test]
+    fn vcom_millivolts() {
+        assert_eq!(VcomConfig::default().millivolts(), -1719);
+        assert_eq!(VcomConfig::from_millivolts(-1719).vcm_reg, 0x12);
+        assert_eq!(VcomConfig::from_millivolts(-3000).vcm_reg, 0x00);
+        assert_eq!(VcomConfig::from_millivolts(100).vcm_reg, 0x80);
+    }
 }
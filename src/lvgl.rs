@@ -0,0 +1,176 @@
+//! Display backend for LVGL, e.g. through [lvgl](https://crates.io/crates/lvgl) (lv_binding_rust)
+//!
+//! LVGL renders into its draw buffer and hands every finished area to a flush callback.
+//! [LvglDisplay::flush_area] takes that area and its colors, converts them to the pixel
+//! format of the display and streams them into a single window, without a copy.
+//!
+//! ```ignore
+//! let mut backend = LvglDisplay::new(display);
+//! let buffer = DrawBuffer::<{ 480 * 20 }>::default();
+//! let lv_display = lvgl::Display::register(buffer, 480, 320, |refresh| {
+//!     let area = LvglArea {
+//!         x1: refresh.area.x1.into(),
+//!         y1: refresh.area.y1.into(),
+//!         x2: refresh.area.x2.into(),
+//!         y2: refresh.area.y2.into(),
+//!     };
+//!     backend.flush_area(area, refresh.colors.into_iter().map(Rgb565::from)).ok();
+//! })?;
+//! ```
+use display_interface::WriteOnlyDataCommand;
+use embedded_graphics_core::prelude::{Dimensions, Point, PointsIter, Size};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::{Color, Ili9488, Ili9488MemoryWrite, Ili9488PixelFormat, NoDelay, Result};
+
+/// Area to flush, like `lv_area_t`, with inclusive corners
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LvglArea {
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32,
+}
+
+impl LvglArea {
+    /// The area as a rectangle, empty if the corners are swapped
+    pub fn rectangle(&self) -> Rectangle {
+        let top_left = Point::new(self.x1, self.y1);
+        if self.x2 < self.x1 || self.y2 < self.y1 {
+            return Rectangle::new(top_left, Size::zero());
+        }
+        Rectangle::with_corners(top_left, Point::new(self.x2, self.y2))
+    }
+}
+
+/// An [Ili9488] taking the flushes of LVGL, see [crate::lvgl]
+pub struct LvglDisplay<IFACE, RESET, PixelFormat, const BURST: usize = 64, DELAY = NoDelay> {
+    display: Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>,
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    LvglDisplay<IFACE, RESET, PixelFormat, BURST, DELAY>
+where
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>: Ili9488MemoryWrite,
+    IFACE: WriteOnlyDataCommand,
+    PixelFormat: Ili9488PixelFormat,
+{
+    pub fn new(display: Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>) -> Self {
+        Self { display }
+    }
+
+    /// Write the colors LVGL rendered for `area`, in row order.
+    ///
+    /// Colors of any type converting into the color of the display work, e.g.
+    /// [Rgb565](embedded_graphics_core::pixelcolor::Rgb565) for LVGL's 16-bit color depth.
+    /// The part of `area` outside of the screen is dropped. LVGL still has to be told that the
+    /// flush is ready, which its Rust bindings do once the callback returns.
+    pub fn flush_area<C, I>(&mut self, area: LvglArea, colors: I) -> Result
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<Color<IFACE, RESET, PixelFormat, BURST, DELAY>>,
+    {
+        let area = area.rectangle();
+        let visible = area.intersection(&self.display.bounding_box());
+        let Some(bottom_right) = visible.bottom_right() else {
+            return Ok(());
+        };
+        let colors = area
+            .points()
+            .zip(colors)
+            .filter(|(point, _)| visible.contains(*point))
+            .map(|(_, color)| color.into());
+        self.display.draw_raw_iter(
+            visible.top_left.x as u16,
+            visible.top_left.y as u16,
+            bottom_right.x as u16,
+            bottom_right.y as u16,
+            colors,
+        )
+    }
+
+    /// Resolution to register with LVGL
+    pub fn resolution(&self) -> Size {
+        self.display.bounding_box().size
+    }
+
+    /// The display, e.g. to change its brightness while LVGL runs
+    pub fn display(&mut self) -> &mut Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY> {
+        &mut self.display
+    }
+
+    /// Give back the display
+    pub fn release(self) -> Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY> {
+        self.display
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use crate::sim;
+    use crate::{Orientation, Rgb666Mode};
+    use embedded_graphics_core::pixelcolor::{Rgb565, Rgb888, RgbColor};
+
+    #[test]
+    fn flushes_areas() {
+        let display = sim::display_with(Orientation::Landscape, Rgb666Mode);
+        let mut backend = LvglDisplay::new(display);
+        assert_eq!(backend.resolution(), Size::new(480, 320));
+
+        // 3x2 area hanging off the right edge
+        let area = LvglArea {
+            x1: 478,
+            y1: 10,
+            x2: 480,
+            y2: 11,
+        };
+        let colors = [Rgb565::RED, Rgb565::GREEN, Rgb565::WHITE, Rgb565::BLUE];
+        backend
+            .flush_area(area, colors.into_iter().cycle())
+            .unwrap();
+        // Off screen
+        let area = LvglArea {
+            x1: -5,
+            y1: 0,
+            x2: -1,
+            y2: 0,
+        };
+        backend.flush_area(area, colors).unwrap();
+
+        let display = backend.release();
+        let sim = display.interface();
+        assert_eq!(sim.pixel(478, 10), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(479, 10), Some(Rgb888::GREEN));
+        assert_eq!(sim.pixel(478, 11), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(479, 11), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(0, 0), Some(Rgb888::BLACK));
+    }
+
+    #[test]
+    fn swapped_corners_are_empty() {
+        let area = LvglArea {
+            x1: 10,
+            y1: 20,
+            x2: 9,
+            y2: 25,
+        };
+        assert_eq!(area.rectangle().size, Size::zero());
+        let area = LvglArea { y2: 18, ..area };
+        assert_eq!(area.rectangle().size, Size::zero());
+        let area = LvglArea {
+            x2: 10,
+            y2: 20,
+            ..area
+        };
+        assert_eq!(area.rectangle().size, Size::new(1, 1));
+
+        let mut backend = LvglDisplay::new(mock::display());
+        backend.display().interface.clear();
+        let area = LvglArea { x2: 0, ..area };
+        backend.flush_area(area, [Rgb565::RED; 4]).unwrap();
+        assert!(backend.display().interface.commands().is_empty());
+    }
+}
@@ -0,0 +1,270 @@
+//! Mock peripherals that record everything the driver sends, for testing off-target
+//!
+//! ```ignore
+//! let mut display = Ili9488::new(
+//!     MockInterface::new(),
+//!     MockPin::default(),
+//!     &mut MockDelay::default(),
+//!     Orientation::Portrait,
+//!     Rgb666Mode,
+//! )
+//! .unwrap();
+//! ```
+use core::fmt::Write;
+use std::collections::VecDeque;
+use std::string::String;
+use std::vec::Vec;
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+use crate::{BufferError, DmaDataCommand, ReadDataCommand};
+#[cfg(test)]
+use crate::{Ili9488, Ili9488PixelFormat, Orientation, Rgb666Mode};
+
+/// A display on a [MockInterface], initialized in portrait with [Rgb666Mode]
+#[cfg(test)]
+pub(crate) fn display() -> Ili9488<MockInterface, MockPin, Rgb666Mode> {
+    display_with(Orientation::Portrait, Rgb666Mode)
+}
+
+/// A display on a [MockInterface], initialized with `orientation` and `pixel_format`
+#[cfg(test)]
+pub(crate) fn display_with<P: Ili9488PixelFormat>(
+    orientation: Orientation,
+    pixel_format: P,
+) -> Ili9488<MockInterface, MockPin, P> {
+    display_on(MockInterface::new(), orientation, pixel_format)
+}
+
+/// A display on `interface`, initialized with `orientation` and `pixel_format`
+#[cfg(test)]
+pub(crate) fn display_on<IFACE: WriteOnlyDataCommand, P: Ili9488PixelFormat>(
+    interface: IFACE,
+    orientation: Orientation,
+    pixel_format: P,
+) -> Ili9488<IFACE, MockPin, P> {
+    Ili9488::new(
+        interface,
+        MockPin::default(),
+        &mut MockDelay::default(),
+        orientation,
+        pixel_format,
+    )
+    .unwrap()
+}
+
+/// A single call made to the [MockInterface]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Transaction {
+    /// Bytes sent with `send_commands`
+    Command(Vec<u8>),
+    /// Bytes sent with `send_data`
+    Data(Vec<u8>),
+    /// Command sent with `read_data`
+    Read(u8),
+}
+
+/// Display interface that records every command and data transaction.
+///
+/// 16-bit words are recorded in the byte order they would appear on the bus,
+/// native and big endian words most significant byte first.
+#[derive(Default)]
+pub struct MockInterface {
+    transactions: Vec<Transaction>,
+    reads: VecDeque<Vec<u8>>,
+    failing_data: usize,
+}
+
+impl MockInterface {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All transactions recorded so far
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    /// Queue the bytes returned by the next `read_data` call
+    pub fn push_read(&mut self, bytes: &[u8]) {
+        self.reads.push_back(bytes.to_vec());
+    }
+
+    /// Let the next `count` `send_data` calls fail with [DisplayError::BusWriteError],
+    /// without recording them
+    pub fn fail_data(&mut self, count: usize) {
+        self.failing_data = count;
+    }
+
+    /// Forget all recorded transactions
+    pub fn clear(&mut self) {
+        self.transactions.clear();
+    }
+
+    /// The recorded commands, each with the data sent after it
+    pub fn commands(&self) -> Vec<(u8, Vec<u8>)> {
+        let mut commands: Vec<(u8, Vec<u8>)> = Vec::new();
+        for transaction in &self.transactions {
+            match transaction {
+                Transaction::Command(bytes) => {
+                    commands.extend(bytes.iter().map(|&cmd| (cmd, Vec::new())))
+                }
+                Transaction::Read(cmd) => commands.push((*cmd, Vec::new())),
+                Transaction::Data(bytes) => {
+                    if let Some((_, data)) = commands.last_mut() {
+                        data.extend_from_slice(bytes);
+                    }
+                }
+            }
+        }
+        commands
+    }
+
+    /// All data bytes sent, without the commands
+    pub fn data(&self) -> Vec<u8> {
+        self.transactions
+            .iter()
+            .filter_map(|transaction| match transaction {
+                Transaction::Data(bytes) => Some(bytes.as_slice()),
+                Transaction::Command(_) | Transaction::Read(_) => None,
+            })
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// The recorded transactions as text, one per line, for comparing with golden files:
+    /// `C` for commands, `D` for data and `R` for reads, followed by the bytes in hex
+    pub fn transcript(&self) -> String {
+        let mut text = String::new();
+        for transaction in &self.transactions {
+            let (kind, bytes) = match transaction {
+                Transaction::Command(bytes) => ('C', bytes.as_slice()),
+                Transaction::Data(bytes) => ('D', bytes.as_slice()),
+                Transaction::Read(command) => ('R', core::slice::from_ref(command)),
+            };
+            text.push(kind);
+            for byte in bytes {
+                let _ = write!(text, " {byte:02x}");
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// The bytes of `format` in the order they would appear on the bus
+pub(crate) fn to_bytes(format: DataFormat<'_>) -> Result<Vec<u8>, DisplayError> {
+    Ok(match format {
+        DataFormat::U8(bytes) => bytes.to_vec(),
+        DataFormat::U16(words) => words.iter().flat_map(|w| w.to_be_bytes()).collect(),
+        DataFormat::U16BE(words) => words.iter().flat_map(|w| w.to_be_bytes()).collect(),
+        DataFormat::U16LE(words) => words.iter().flat_map(|w| w.to_le_bytes()).collect(),
+        DataFormat::U8Iter(iter) => iter.collect(),
+        DataFormat::U16BEIter(iter) => iter.flat_map(|w| w.to_be_bytes()).collect(),
+        DataFormat::U16LEIter(iter) => iter.flat_map(|w| w.to_le_bytes()).collect(),
+        _ => return Err(DisplayError::DataFormatNotImplemented),
+    })
+}
+
+impl WriteOnlyDataCommand for MockInterface {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        let bytes = to_bytes(cmd)?;
+        self.transactions.push(Transaction::Command(bytes));
+        Ok(())
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        if self.failing_data > 0 {
+            self.failing_data -= 1;
+            return Err(DisplayError::BusWriteError);
+        }
+        let bytes = to_bytes(buf)?;
+        self.transactions.push(Transaction::Data(bytes));
+        Ok(())
+    }
+}
+
+impl DmaDataCommand for MockInterface {
+    /// Records the buffer like `send_data`
+    fn send_buffer(&mut self, buffer: &'static mut [u8]) -> Result<&'static mut [u8], BufferError> {
+        match self.send_data(DataFormat::U8(buffer)) {
+            Ok(()) => Ok(buffer),
+            Err(error) => Err(BufferError { error, buffer }),
+        }
+    }
+}
+
+impl ReadDataCommand for MockInterface {
+    /// Fills `buf` from the responses queued with [MockInterface::push_read], zeros when empty
+    fn read_data(&mut self, command: u8, buf: &mut [u8]) -> Result<(), DisplayError> {
+        self.transactions.push(Transaction::Read(command));
+        buf.fill(0);
+        if let Some(bytes) = self.reads.pop_front() {
+            let len = bytes.len().min(buf.len());
+            buf[..len].copy_from_slice(&bytes[..len]);
+        }
+        Ok(())
+    }
+}
+
+/// Output pin that records every state it was set to
+#[derive(Default)]
+pub struct MockPin {
+    /// `true` for every `set_high`, `false` for every `set_low`
+    pub states: Vec<bool>,
+}
+
+impl embedded_hal::digital::ErrorType for MockPin {
+    type Error = core::convert::Infallible;
+}
+
+impl embedded_hal::digital::OutputPin for MockPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.states.push(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.states.push(true);
+        Ok(())
+    }
+}
+
+/// SPI device that records the bytes of every write
+#[derive(Default)]
+pub struct MockSpi {
+    /// The bytes of every `write` operation, one entry per operation
+    pub writes: Vec<Vec<u8>>,
+}
+
+impl embedded_hal::spi::ErrorType for MockSpi {
+    type Error = core::convert::Infallible;
+}
+
+impl embedded_hal::spi::SpiDevice for MockSpi {
+    fn transaction(
+        &mut self,
+        operations: &mut [embedded_hal::spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            if let embedded_hal::spi::Operation::Write(bytes) = operation {
+                self.writes.push(bytes.to_vec());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Delay that returns immediately and adds up the requested time
+#[derive(Default)]
+pub struct MockDelay {
+    /// Total time delayed in nanoseconds
+    pub elapsed_ns: u64,
+}
+
+impl embedded_hal::delay::DelayNs for MockDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.elapsed_ns += u64::from(ns);
+    }
+}
@@ -0,0 +1,151 @@
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+use crate::dcs::Instruction;
+use crate::{ReadDataCommand, Result};
+
+/// Gets every command sent through an [ObservedInterface], to log or check it
+///
+/// Closures taking the instruction and parameters are observers too.
+pub trait CommandObserver {
+    /// Called for every command with its parameters.
+    ///
+    /// Pixel data after Memory Write (Continue) is not passed, neither are the answers of reads.
+    fn on_command(&mut self, cmd: u8, params: &[u8]);
+}
+
+impl<F: FnMut(u8, &[u8])> CommandObserver for F {
+    fn on_command(&mut self, cmd: u8, params: &[u8]) {
+        self(cmd, params)
+    }
+}
+
+/// [CommandObserver] logging every command with defmt at debug level
+#[cfg(feature = "defmt")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefmtObserver;
+
+#[cfg(feature = "defmt")]
+impl CommandObserver for DefmtObserver {
+    fn on_command(&mut self, cmd: u8, params: &[u8]) {
+        defmt::debug!("ILI9488 command {=u8:#04x} {=[u8]:#04x}", cmd, params);
+    }
+}
+
+/// Display interface adapter passing the commands going through it to a [CommandObserver].
+///
+/// Useful to debug wrong colors or a blank screen without a logic analyzer:
+///
+/// ```ignore
+/// let interface = ObservedInterface::new(spi_interface, DefmtObserver);
+/// let mut display = Ili9488::new(interface, reset, &mut delay, Orientation::Portrait, Rgb666Mode)?;
+/// ```
+pub struct ObservedInterface<DI, O> {
+    interface: DI,
+    observer: O,
+    /// Command waiting for its parameters
+    pending: Option<u8>,
+}
+
+impl<DI: WriteOnlyDataCommand, O: CommandObserver> ObservedInterface<DI, O> {
+    pub fn new(interface: DI, observer: O) -> Self {
+        Self {
+            interface,
+            observer,
+            pending: None,
+        }
+    }
+
+    /// The observer, e.g. to check what it collected
+    pub fn observer(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    /// Give back the wrapped interface and the observer
+    pub fn release(self) -> (DI, O) {
+        (self.interface, self.observer)
+    }
+
+    /// Pass a command still waiting for its parameters on without any
+    fn flush(&mut self) {
+        if let Some(cmd) = self.pending.take() {
+            self.observer.on_command(cmd, &[]);
+        }
+    }
+}
+
+impl<DI: WriteOnlyDataCommand, O: CommandObserver> WriteOnlyDataCommand
+    for ObservedInterface<DI, O>
+{
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> core::result::Result<(), DisplayError> {
+        self.flush();
+        if let DataFormat::U8(&[command]) = cmd {
+            let memory_writes = [Instruction::MemoryWrite, Instruction::MemoryWriteContinue];
+            if memory_writes.map(|i| i as u8).contains(&command) {
+                // Pixel data follows
+                self.observer.on_command(command, &[]);
+            } else {
+                self.pending = Some(command);
+            }
+        }
+        self.interface.send_commands(cmd)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> core::result::Result<(), DisplayError> {
+        match (self.pending.take(), &buf) {
+            (Some(cmd), DataFormat::U8(params)) => self.observer.on_command(cmd, params),
+            (Some(cmd), _) => self.observer.on_command(cmd, &[]),
+            (None, _) => {}
+        }
+        self.interface.send_data(buf)
+    }
+}
+
+impl<DI: ReadDataCommand, O: CommandObserver> ReadDataCommand for ObservedInterface<DI, O> {
+    fn read_data(&mut self, command: u8, buf: &mut [u8]) -> Result {
+        self.flush();
+        self.observer.on_command(command, &[]);
+        self.interface.read_data(command, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, MockInterface};
+    use crate::{Orientation, Rgb666Mode};
+    use embedded_graphics_core::pixelcolor::{Rgb666, RgbColor};
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    fn observes_commands() {
+        let mut commands = Vec::new();
+        let mut display = mock::display_on(
+            ObservedInterface::new(MockInterface::new(), |cmd, params: &[u8]| {
+                commands.push((cmd, params.to_vec()))
+            }),
+            Orientation::Portrait,
+            Rgb666Mode,
+        );
+        display
+            .draw_raw_slice(0, 0, 1, 0, &[Rgb666::RED, Rgb666::BLUE])
+            .unwrap();
+        display.read_controller_id().unwrap();
+        let (interface, _) = display.release().0.release();
+
+        // Everything the driver sent, without the pixels
+        let sent = interface.commands();
+        let (init, drawn) = commands.split_at(commands.len() - 5);
+        assert_eq!(init, &sent[..init.len()]);
+        assert_eq!(
+            drawn,
+            [
+                (0x2a, vec![0, 0, 0, 1]),
+                (0x2b, vec![0, 0, 0, 0]),
+                (0x2c, vec![]),
+                (0x04, vec![]),
+                (0xd3, vec![]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Locking animation loops to the refresh of the panel
+//!
+//! The ILI9488 pulses its tearing effect (TE) output at every vertical blanking once it is
+//! turned on with [Ili9488::tearing_effect](crate::Ili9488::tearing_effect). A [FramePacer]
+//! waits for that pulse on an input pin with interrupt support, and counts the frames an
+//! animation was too slow for.
+//!
+//! ```ignore
+//! display.tearing_effect(ModeState::On)?;
+//! let te = ExtiInput::new(p.PA8, p.EXTI8, Pull::Down);
+//! let mut pacer = FramePacer::new(te, display.frame_rate_hz(), || Instant::now().as_micros());
+//! loop {
+//!     pacer.wait_for_vsync().await?;
+//!     draw_next_frame(&mut display)?;
+//! }
+//! ```
+use embedded_hal::digital::ErrorType;
+use embedded_hal_async::digital::Wait;
+
+/// Frame timing statistics of a [FramePacer]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FrameStats {
+    /// Number of vertical blankings waited for
+    pub frames: u32,
+    /// Number of vertical blankings that passed while the caller was busy
+    pub missed: u32,
+    /// Time between the last two vertical blankings waited for, in microseconds
+    pub last_frame_us: u64,
+}
+
+/// Waits for the tearing effect pulse of the panel, see [crate::pacer]
+pub struct FramePacer<TE, CLOCK> {
+    te: TE,
+    now: CLOCK,
+    period_us: u64,
+    last_vsync: Option<u64>,
+    stats: FrameStats,
+}
+
+impl<TE, CLOCK> FramePacer<TE, CLOCK>
+where
+    TE: Wait,
+    CLOCK: FnMut() -> u64,
+{
+    /// Pace with the TE pulses on `te`, for a panel refreshing at `frame_rate_hz`, e.g.
+    /// [Ili9488::frame_rate_hz](crate::Ili9488::frame_rate_hz).
+    ///
+    /// `now` returns a monotonic time in microseconds, used to detect missed vertical blankings.
+    pub fn new(te: TE, frame_rate_hz: f32, now: CLOCK) -> Self {
+        Self {
+            te,
+            now,
+            period_us: (1_000_000.0 / frame_rate_hz) as u64,
+            last_vsync: None,
+            stats: FrameStats::default(),
+        }
+    }
+
+    /// Wait for the next vertical blanking, returns how many were missed since the last call
+    pub async fn wait_for_vsync(&mut self) -> Result<u32, <TE as ErrorType>::Error> {
+        self.te.wait_for_rising_edge().await?;
+        let now = (self.now)();
+        let mut missed = 0;
+        if let Some(last) = self.last_vsync {
+            let elapsed = now.saturating_sub(last);
+            // Round to whole frames, TE jitters by a few lines
+            let frames = (elapsed + self.period_us / 2) / self.period_us.max(1);
+            missed = frames.saturating_sub(1) as u32;
+            self.stats.last_frame_us = elapsed;
+        }
+        self.last_vsync = Some(now);
+        self.stats.frames = self.stats.frames.wrapping_add(1);
+        self.stats.missed = self.stats.missed.wrapping_add(missed);
+        Ok(missed)
+    }
+
+    /// Timing statistics since creation or the last [FramePacer::reset_stats]
+    pub fn stats(&self) -> FrameStats {
+        self.stats
+    }
+
+    /// Reset the statistics, e.g. when an animation starts
+    pub fn reset_stats(&mut self) {
+        self.stats = FrameStats::default();
+        self.last_vsync = None;
+    }
+
+    /// Give back the TE pin and clock
+    pub fn release(self) -> (TE, CLOCK) {
+        (self.te, self.now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+    use core::convert::Infallible;
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    /// TE pin whose edges are always there
+    struct TePin;
+
+    impl ErrorType for TePin {
+        type Error = Infallible;
+    }
+
+    impl Wait for TePin {
+        async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+        async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+        async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut context = Context::from_waker(Waker::noop());
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn counts_missed_vsyncs() {
+        let time = Cell::new(1_000);
+        let mut pacer = FramePacer::new(TePin, 50.0, || time.get());
+
+        assert_eq!(block_on(pacer.wait_for_vsync()), Ok(0));
+        time.set(21_100);
+        assert_eq!(block_on(pacer.wait_for_vsync()), Ok(0));
+        // Two frames late, 3 periods with a bit of jitter
+        time.set(80_500);
+        assert_eq!(block_on(pacer.wait_for_vsync()), Ok(2));
+        assert_eq!(
+            pacer.stats(),
+            FrameStats {
+                frames: 3,
+                missed: 2,
+                last_frame_us: 59_400,
+            }
+        );
+
+        pacer.reset_stats();
+        time.set(200_000);
+        assert_eq!(block_on(pacer.wait_for_vsync()), Ok(0));
+        assert_eq!(pacer.stats().frames, 1);
+    }
+}
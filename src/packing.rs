@@ -0,0 +1,376 @@
+use display_interface::{DataFormat, WriteOnlyDataCommand};
+#[cfg(any(feature = "rgb111", feature = "rgb565-parallel"))]
+use embedded_graphics_core::pixelcolor::IntoStorage;
+#[cfg(feature = "rgb565-parallel")]
+use embedded_graphics_core::pixelcolor::Rgb565;
+#[cfg(feature = "rgb666")]
+use embedded_graphics_core::pixelcolor::{Bgr666, Rgb666};
+use embedded_graphics_core::prelude::RgbColor;
+
+#[cfg(feature = "rgb565-parallel")]
+use crate::Rgb565Mode;
+#[cfg(feature = "rgb666")]
+use crate::{Bgr666Mode, Rgb666Bus16Mode, Rgb666Mode};
+use crate::{DataWidth, Ili9488PixelFormat, Result};
+#[cfg(feature = "rgb111")]
+use crate::{Rgb111, Rgb111Mode};
+
+/// Pixel formats that can be drawn through the command interface, by packing the pixels into
+/// the bytes sent after Memory Write
+///
+/// Pixels are packed in pairs, so formats with less than a byte per pixel fit. Every format
+/// implementing this trait gets an [Ili9488MemoryWrite](crate::Ili9488MemoryWrite)
+/// implementation sending the bytes of [PackedPixels].
+pub trait Ili9488PixelPacking: Ili9488PixelFormat {
+    /// Color of a pixel
+    type Color: RgbColor;
+    /// Bytes of a pair of pixels
+    type Pair: AsRef<[u8]> + Copy + Default;
+    /// The bytes are sent as 16-bit words, most significant byte first, for 16-bit data buses
+    const WORDS: bool = false;
+    /// Half a pair fills whole bytes, or whole words for [Self::WORDS] formats, so a write can
+    /// end after an odd number of pixels without touching the pixel after them.
+    /// [PixelWriter](crate::PixelWriter) holds back the last pixel of odd chunks when not.
+    const ODD_PIXELS: bool = true;
+
+    /// Pack two pixels into the bytes sent to the controller
+    fn pack(pixels: [Self::Color; 2]) -> Self::Pair;
+
+    /// Send the packed bytes of a memory write to `interface`.
+    ///
+    /// By default the bytes are sent as they are, or as 16-bit words for [Self::WORDS] formats
+    /// and with [DataWidth::Words]. Formats for unusual buses can send them any other way.
+    fn send<DI: WriteOnlyDataCommand>(
+        interface: &mut DI,
+        mut bytes: impl Iterator<Item = u8>,
+        data_width: DataWidth,
+    ) -> Result {
+        if !Self::WORDS && data_width == DataWidth::Bytes {
+            return interface.send_data(DataFormat::U8Iter(&mut bytes));
+        }
+        // Formats sent as words always have an even number of bytes
+        let mut tail = None;
+        let mut words = core::iter::from_fn(|| {
+            let high = bytes.next()?;
+            let Some(low) = bytes.next() else {
+                tail = Some(high);
+                return None;
+            };
+            Some(u16::from_be_bytes([high, low]))
+        });
+        interface.send_data(DataFormat::U16BEIter(&mut words))?;
+        match tail {
+            Some(byte) => interface.send_data(DataFormat::U8(&[byte])),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Pixel formats that write whole pixels, which makes the display an embedded-graphics
+/// [DrawTarget](embedded_graphics_core::draw_target::DrawTarget) for them
+///
+/// Encodings this crate does not know, like pixels padded to 32 bits for some FSMC setups,
+/// can be added outside of it by implementing [Ili9488PixelFormat], [Ili9488PixelPacking]
+/// and this trait for a unit struct:
+///
+/// ```ignore
+/// #[derive(Clone, Copy)]
+/// struct Rgb666Padded;
+///
+/// impl Ili9488PixelFormat for Rgb666Padded {
+///     const DATA: u8 = 0x66;
+///     const BPP: u8 = 18;
+/// }
+///
+/// impl Ili9488PixelPacking for Rgb666Padded {
+///     type Color = Rgb666;
+///     type Pair = [u8; 8];
+///
+///     fn pack([p1, p2]: [Rgb666; 2]) -> [u8; 8] {
+///         [p1.r() << 2, p1.g() << 2, p1.b() << 2, 0, p2.r() << 2, p2.g() << 2, p2.b() << 2, 0]
+///     }
+/// }
+///
+/// impl WireFormat for Rgb666Padded {}
+/// ```
+pub trait WireFormat: Ili9488PixelPacking {}
+
+#[cfg(feature = "rgb565-parallel")]
+impl WireFormat for Rgb565Mode {}
+
+#[cfg(feature = "rgb666")]
+impl WireFormat for Rgb666Mode {}
+
+#[cfg(feature = "rgb666")]
+impl WireFormat for Rgb666Bus16Mode {}
+
+#[cfg(feature = "rgb666")]
+impl WireFormat for Bgr666Mode {}
+
+#[cfg(feature = "rgb111")]
+impl Ili9488PixelPacking for Rgb111Mode {
+    type Color = Rgb111;
+    type Pair = [u8; 1];
+    const ODD_PIXELS: bool = false;
+
+    fn pack([p1, p2]: [Rgb111; 2]) -> [u8; 1] {
+        [(p1.into_storage() << 3) | p2.into_storage()]
+    }
+}
+
+#[cfg(feature = "rgb565-parallel")]
+impl Ili9488PixelPacking for Rgb565Mode {
+    type Color = Rgb565;
+    type Pair = [u8; 4];
+    const WORDS: bool = true;
+
+    fn pack([p1, p2]: [Rgb565; 2]) -> [u8; 4] {
+        let ([b0, b1], [b2, b3]) = (
+            p1.into_storage().to_be_bytes(),
+            p2.into_storage().to_be_bytes(),
+        );
+        [b0, b1, b2, b3]
+    }
+}
+
+#[cfg(feature = "rgb666")]
+impl Ili9488PixelPacking for Rgb666Mode {
+    type Color = Rgb666;
+    type Pair = [u8; 6];
+
+    fn pack([p1, p2]: [Rgb666; 2]) -> [u8; 6] {
+        [
+            p1.r() << 2,
+            p1.g() << 2,
+            p1.b() << 2,
+            p2.r() << 2,
+            p2.g() << 2,
+            p2.b() << 2,
+        ]
+    }
+}
+
+#[cfg(feature = "rgb666")]
+impl Ili9488PixelPacking for Rgb666Bus16Mode {
+    type Color = Rgb666;
+    type Pair = [u8; 6];
+    const WORDS: bool = true;
+    const ODD_PIXELS: bool = false;
+
+    fn pack(pixels: [Rgb666; 2]) -> [u8; 6] {
+        Rgb666Mode::pack(pixels)
+    }
+}
+
+#[cfg(feature = "rgb666")]
+impl Ili9488PixelPacking for Bgr666Mode {
+    type Color = Bgr666;
+    type Pair = [u8; 6];
+
+    fn pack([p1, p2]: [Bgr666; 2]) -> [u8; 6] {
+        Rgb666Mode::pack([
+            Rgb666::new(p1.r(), p1.g(), p1.b()),
+            Rgb666::new(p2.r(), p2.g(), p2.b()),
+        ])
+    }
+}
+
+/// Iterator over the bytes of pixels packed with [Ili9488PixelPacking::pack]
+///
+/// When the number of pixels is odd, the last pixel is packed together with the first one
+/// and only the first half of the pair is sent, rounded up to whole bytes or words. For
+/// [Rgb111Mode] that leaves the first pixel in the low bits of the last byte: once the window
+/// is full the controller wraps around to its start, so it is rewritten with its own color.
+/// Writes split into chunks hold the odd pixel back instead, see
+/// [PixelWriter](crate::PixelWriter).
+pub struct PackedPixels<P: Ili9488PixelPacking, I> {
+    pixels: I,
+    first: Option<P::Color>,
+    pair: P::Pair,
+    pos: usize,
+    len: usize,
+    /// Keep an odd last pixel in `unpaired` instead of sending half a pair
+    hold_odd: bool,
+    unpaired: Option<P::Color>,
+    #[cfg(feature = "stats")]
+    packed: usize,
+}
+
+impl<P, I> PackedPixels<P, I>
+where
+    P: Ili9488PixelPacking,
+    I: Iterator<Item = P::Color>,
+{
+    pub fn new<T: IntoIterator<IntoIter = I>>(pixels: T) -> Self {
+        Self {
+            pixels: pixels.into_iter(),
+            first: None,
+            pair: P::Pair::default(),
+            pos: 0,
+            len: 0,
+            hold_odd: false,
+            unpaired: None,
+            #[cfg(feature = "stats")]
+            packed: 0,
+        }
+    }
+
+    /// Pack `pixels` of a write that continues afterwards: for formats without
+    /// [Ili9488PixelPacking::ODD_PIXELS] an odd last pixel is not sent but kept for
+    /// [PackedPixels::unpaired]
+    pub(crate) fn continued<T: IntoIterator<IntoIter = I>>(pixels: T) -> Self {
+        Self {
+            hold_odd: !P::ODD_PIXELS,
+            ..Self::new(pixels)
+        }
+    }
+
+    /// The odd last pixel held back by [PackedPixels::continued]
+    pub(crate) fn unpaired(&self) -> Option<P::Color> {
+        self.unpaired
+    }
+
+    /// The first pixel packed, the odd pixel at the end of the write is padded with it
+    pub(crate) fn first(&self) -> Option<P::Color> {
+        self.first
+    }
+
+    /// Number of pixels packed so far
+    #[cfg(feature = "stats")]
+    pub(crate) fn packed(&self) -> usize {
+        self.packed
+    }
+}
+
+impl<P: Ili9488PixelPacking> PackedPixels<P, core::iter::Once<P::Color>> {
+    /// Pack the odd last `pixel` of a write split into chunks, padded with the `first` pixel
+    /// of the write like a write in one go
+    pub(crate) fn last(pixel: P::Color, first: P::Color) -> Self {
+        Self {
+            first: Some(first),
+            ..Self::new(core::iter::once(pixel))
+        }
+    }
+}
+
+impl<P, I> Iterator for PackedPixels<P, I>
+where
+    P: Ili9488PixelPacking,
+    I: Iterator<Item = P::Color>,
+{
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.pos == self.len {
+            let p1 = self.pixels.next()?;
+            let first = *self.first.get_or_insert(p1);
+            let p2 = self.pixels.next();
+            if p2.is_none() && self.hold_odd {
+                self.unpaired = Some(p1);
+                return None;
+            }
+            self.pair = P::pack([p1, p2.unwrap_or(first)]);
+            self.len = self.pair.as_ref().len();
+            if p2.is_none() {
+                self.len = self.len.div_ceil(2);
+                if P::WORDS {
+                    self.len = self.len.next_multiple_of(2);
+                }
+            }
+            #[cfg(feature = "stats")]
+            {
+                self.packed += 1 + usize::from(p2.is_some());
+            }
+            self.pos = 0;
+        }
+        let byte = self.pair.as_ref()[self.pos];
+        self.pos += 1;
+        Some(byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use crate::sim;
+    use crate::Orientation;
+    use embedded_graphics_core::pixelcolor::Rgb888;
+    use embedded_graphics_core::prelude::{DrawTarget, Pixel, Point};
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    fn packs_pairs_and_odd_tails() {
+        let rgb111: Vec<u8> =
+            PackedPixels::<Rgb111Mode, _>::new([Rgb111::RED, Rgb111::CYAN, Rgb111::WHITE])
+                .collect();
+        assert_eq!(rgb111, [0b100_011, 0b111_100]);
+
+        let pixels = [Rgb666::new(63, 0, 0), Rgb666::new(1, 2, 3), Rgb666::WHITE];
+        let rgb666: Vec<u8> = PackedPixels::<Rgb666Mode, _>::new(pixels).collect();
+        assert_eq!(rgb666, [0xfc, 0, 0, 4, 8, 12, 0xfc, 0xfc, 0xfc]);
+        // The tail is padded to a whole word
+        let bus16: Vec<u8> = PackedPixels::<Rgb666Bus16Mode, _>::new(pixels).collect();
+        assert_eq!(bus16, [0xfc, 0, 0, 4, 8, 12, 0xfc, 0xfc, 0xfc, 0xfc]);
+
+        let rgb565: Vec<u8> = PackedPixels::<Rgb565Mode, _>::new([Rgb565::RED]).collect();
+        assert_eq!(rgb565, [0xf8, 0x00]);
+        assert_eq!(PackedPixels::<Rgb565Mode, _>::new([]).next(), None);
+    }
+
+    /// 18 bpp with every pixel padded to 4 bytes
+    #[derive(Clone, Copy)]
+    struct Rgb666Padded;
+
+    impl Ili9488PixelFormat for Rgb666Padded {
+        const DATA: u8 = 0x66;
+        const BPP: u8 = 18;
+    }
+
+    impl Ili9488PixelPacking for Rgb666Padded {
+        type Color = Rgb666;
+        type Pair = [u8; 8];
+
+        fn pack(pixels: [Rgb666; 2]) -> [u8; 8] {
+            let [r1, g1, b1, r2, g2, b2] = Rgb666Mode::pack(pixels);
+            [r1, g1, b1, 0, r2, g2, b2, 0]
+        }
+    }
+
+    impl WireFormat for Rgb666Padded {}
+
+    #[test]
+    fn custom_wire_format() {
+        let mut display = mock::display_with(Orientation::Portrait, Rgb666Padded);
+        display.interface.clear();
+        display
+            .draw_iter([
+                Pixel(Point::new(1, 2), Rgb666::RED),
+                Pixel(Point::new(2, 2), Rgb666::BLUE),
+                Pixel(Point::new(3, 2), Rgb666::GREEN),
+            ])
+            .unwrap();
+        assert_eq!(
+            display.interface.commands()[2],
+            (0x2c, vec![0xfc, 0, 0, 0, 0, 0, 0xfc, 0, 0, 0xfc, 0, 0])
+        );
+    }
+
+    #[test]
+    fn draws_bgr666() {
+        let mut display = sim::display_with(Orientation::Portrait, Bgr666Mode);
+        let pixels = [Bgr666::new(63, 0, 0), Bgr666::new(1, 2, 3), Bgr666::BLUE];
+        let bytes: Vec<u8> = PackedPixels::<Bgr666Mode, _>::new(pixels).collect();
+        assert_eq!(bytes, [0xfc, 0, 0, 4, 8, 12, 0, 0, 0xfc]);
+
+        display
+            .draw_iter([
+                Pixel(Point::new(1, 2), Bgr666::RED),
+                Pixel(Point::new(2, 2), Bgr666::BLUE),
+            ])
+            .unwrap();
+        assert_eq!(display.interface().pixel(1, 2), Some(Rgb888::RED));
+        assert_eq!(display.interface().pixel(2, 2), Some(Rgb888::BLUE));
+    }
+}
@@ -0,0 +1,193 @@
+use core::convert::Infallible;
+
+use display_interface::{DisplayError, WriteOnlyDataCommand};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{ErrorType, OutputPin};
+
+use crate::{DriverDelay, Ili9488, Ili9488PixelFormat, Mode, ModeState, ResetDriver, Result};
+
+/// Time for a switched supply rail to settle, in milliseconds
+const RAIL_SETTLE_MS: u32 = 10;
+
+/// Switched supply rails of boards that can cut the power of the display
+///
+/// The driver sequences them with the reset and initialization:
+///
+/// - [Ili9488::new_powered] and [Ili9488::power_up]: enable VDD, wait for it to settle, reset
+///   and initialize the controller. The backlight stays off, switch it on with
+///   [PowerPins::set_backlight] once the first frame is drawn, instead of showing whatever the
+///   display memory held.
+/// - [Ili9488::power_down]: the reverse, backlight off, display off and sleep in, hold the
+///   controller in reset and disable VDD.
+///
+/// Use [NoPin] for a rail that is not switched.
+///
+/// ```ignore
+/// let mut power = PowerPins::new(vdd_en, led_en);
+/// let mut display = Ili9488::new_powered(interface, reset, &mut power, &mut delay, Orientation::Portrait, Rgb666Mode)?;
+/// display.clear_screen(Rgb666::BLACK)?;
+/// power.set_backlight(ModeState::On)?;
+/// ```
+pub struct PowerPins<VDD, LED> {
+    pub vdd_en: VDD,
+    pub led_en: LED,
+}
+
+impl<VDD: OutputPin, LED: OutputPin> PowerPins<VDD, LED> {
+    pub fn new(vdd_en: VDD, led_en: LED) -> Self {
+        Self { vdd_en, led_en }
+    }
+
+    /// Switch the backlight supply
+    pub fn set_backlight(&mut self, state: ModeState) -> Result {
+        match state {
+            ModeState::On => self.led_en.set_high(),
+            ModeState::Off => self.led_en.set_low(),
+        }
+        .map_err(|_| DisplayError::RSError)
+    }
+
+    /// Give back the pins
+    pub fn release(self) -> (VDD, LED) {
+        (self.vdd_en, self.led_en)
+    }
+
+    /// Switch VDD on and wait for it to settle
+    fn vdd_on<D: DelayNs>(&mut self, delay: &mut D) -> Result {
+        self.vdd_en.set_high().map_err(|_| DisplayError::RSError)?;
+        delay.delay_ms(RAIL_SETTLE_MS);
+        Ok(())
+    }
+}
+
+/// A supply rail that is not switched, for [PowerPins]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoPin;
+
+impl ErrorType for NoPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for NoPin {
+    fn set_low(&mut self) -> core::result::Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> core::result::Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+impl<IFACE, RESET, PixelFormat> Ili9488<IFACE, RESET, PixelFormat>
+where
+    IFACE: WriteOnlyDataCommand,
+    RESET: ResetDriver,
+    PixelFormat: Ili9488PixelFormat,
+{
+    /// Like [Ili9488::new], but switch the display supply on first, see [PowerPins]
+    pub fn new_powered<VDD, LED, DELAY, MODE>(
+        interface: IFACE,
+        reset: RESET,
+        power: &mut PowerPins<VDD, LED>,
+        delay: &mut DELAY,
+        orientation: MODE,
+        pixel_format: PixelFormat,
+    ) -> Result<Self>
+    where
+        VDD: OutputPin,
+        LED: OutputPin,
+        DELAY: DelayNs,
+        MODE: Mode,
+    {
+        power.vdd_on(delay)?;
+        Self::new(interface, reset, delay, orientation, pixel_format)
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+where
+    IFACE: WriteOnlyDataCommand,
+    RESET: ResetDriver,
+    PixelFormat: Ili9488PixelFormat,
+    DELAY: DriverDelay,
+{
+    /// Switch the display supply off, see [PowerPins]
+    pub fn power_down<VDD, LED, D>(
+        &mut self,
+        power: &mut PowerPins<VDD, LED>,
+        delay: &mut D,
+    ) -> Result
+    where
+        VDD: OutputPin,
+        LED: OutputPin,
+        D: DelayNs,
+    {
+        power.set_backlight(ModeState::Off)?;
+        self.display_mode(ModeState::Off)?;
+        self.sleep_mode(ModeState::On)?;
+        // The booster and oscillator need up to 120 ms to stop
+        delay.delay_ms(120);
+        self.reset.hold()?;
+        power.vdd_en.set_low().map_err(|_| DisplayError::RSError)
+    }
+
+    /// Switch the display supply on again after [Ili9488::power_down] and initialize it like
+    /// [Ili9488::wake_from_deep_standby]. The backlight stays off.
+    pub fn power_up<VDD, LED, D>(
+        &mut self,
+        power: &mut PowerPins<VDD, LED>,
+        delay: &mut D,
+    ) -> Result
+    where
+        VDD: OutputPin,
+        LED: OutputPin,
+        D: DelayNs,
+    {
+        power.vdd_on(delay)?;
+        self.wake(&mut |_, ms| delay.delay_ms(ms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockDelay, MockInterface, MockPin};
+    use crate::{Orientation, Rgb666Mode};
+    use std::vec;
+
+    #[test]
+    fn power_sequence() {
+        let mut power = PowerPins::new(MockPin::default(), MockPin::default());
+        let mut delay = MockDelay::default();
+        let mut display = Ili9488::new_powered(
+            MockInterface::new(),
+            MockPin::default(),
+            &mut power,
+            &mut delay,
+            Orientation::Portrait,
+            Rgb666Mode,
+        )
+        .unwrap();
+        assert_eq!(power.vdd_en.states, [true]);
+        assert!(power.led_en.states.is_empty());
+        power.set_backlight(ModeState::On).unwrap();
+
+        display.interface.clear();
+        display.power_down(&mut power, &mut delay).unwrap();
+        assert_eq!(
+            display.interface.commands(),
+            [(0x28, vec![]), (0x10, vec![])]
+        );
+        assert_eq!(power.led_en.states, [true, false]);
+        assert_eq!(power.vdd_en.states, [true, false]);
+        assert_eq!(display.reset.states.last(), Some(&false));
+
+        display.interface.clear();
+        display.power_up(&mut power, &mut delay).unwrap();
+        assert_eq!(power.vdd_en.states, [true, false, true]);
+        // Reset and initialized again
+        assert_eq!(display.interface.commands()[1], (0x01, vec![]));
+        assert_eq!(display.reset.states.last(), Some(&true));
+    }
+}
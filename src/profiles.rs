@@ -0,0 +1,241 @@
+//! Initialization tables for known boards
+//!
+//! Every board needs slightly different gamma, power and VCOM settings. A [Profile] bundles
+//! the manufacturer commands of a board's vendor example code, select it with
+//! [Ili9488::new_with_profile] or [PanelVariant::Profile].
+//!
+//! ```ignore
+//! let display = Ili9488::new_with_profile(
+//!     Profile::Msp3520,
+//!     interface,
+//!     reset,
+//!     &mut delay,
+//!     Orientation::Landscape,
+//!     Rgb666Mode,
+//! )?;
+//! ```
+use display_interface::WriteOnlyDataCommand;
+use embedded_hal::delay::DelayNs;
+
+use crate::dcs::Instruction;
+use crate::gamma::presets;
+use crate::{
+    FrameRate, Ili9488, Ili9488PixelFormat, InitOp, Mode, PanelVariant, ResetDriver, Result,
+    INIT_SEQUENCE,
+};
+
+/// A command with its parameters in an initialization table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InitCommand {
+    pub instruction: u8,
+    pub params: &'static [u8],
+}
+
+impl InitCommand {
+    pub const fn new(instruction: Instruction, params: &'static [u8]) -> Self {
+        Self {
+            instruction: instruction as u8,
+            params,
+        }
+    }
+}
+
+/// Set Image Function, 0x00 turns the 24-bit data bus off
+const SET_IMAGE_FUNCTION: u8 = 0xe9;
+
+/// The commands of [INIT_SEQUENCE], which has no delays
+const TFT_ESPI: &[InitCommand] = &sequence_commands::<{ INIT_SEQUENCE.len() }>(INIT_SEQUENCE);
+
+const fn sequence_commands<const N: usize>(sequence: &[InitOp]) -> [InitCommand; N] {
+    let mut commands = [InitCommand {
+        instruction: 0,
+        params: &[],
+    }; N];
+    let mut i = 0;
+    while i < N {
+        commands[i] = match sequence[i] {
+            InitOp::Command(command) => command,
+            InitOp::Delay(_) => panic!("delay in the sequence"),
+        };
+        i += 1;
+    }
+    commands
+}
+
+const WAVESHARE_35: &[InitCommand] = &[
+    InitCommand::new(
+        Instruction::PositiveGammaControl,
+        &presets::TFT_ESPI.positive,
+    ),
+    InitCommand::new(
+        Instruction::NegativeGammaControl,
+        &presets::TFT_ESPI.negative,
+    ),
+    InitCommand::new(Instruction::PowerControl1, &[0x17, 0x15]),
+    InitCommand::new(Instruction::PowerControl2, &[0x41]),
+    InitCommand::new(Instruction::VCOMControl, &[0x00, 0x12, 0x80]),
+    InitCommand::new(Instruction::InterfaceModeControl, &[0x00]),
+    InitCommand::new(Instruction::DisplayInversionControl, &[0x02]),
+    InitCommand::new(Instruction::DisplayFunctionControl, &[0x02, 0x02]),
+    InitCommand {
+        instruction: SET_IMAGE_FUNCTION,
+        params: &[0x00],
+    },
+    InitCommand::new(Instruction::AdjustControl3, &[0xA9, 0x51, 0x2C, 0x82]),
+];
+
+const MSP3520: &[InitCommand] = &[
+    InitCommand::new(
+        Instruction::PositiveGammaControl,
+        &presets::DATASHEET.positive,
+    ),
+    InitCommand::new(
+        Instruction::NegativeGammaControl,
+        &presets::DATASHEET.negative,
+    ),
+    InitCommand::new(Instruction::PowerControl1, &[0x10, 0x10]),
+    InitCommand::new(Instruction::PowerControl2, &[0x41]),
+    InitCommand::new(Instruction::VCOMControl, &[0x00, 0x22, 0x80]),
+    InitCommand::new(Instruction::InterfaceModeControl, &[0x00]),
+    InitCommand::new(Instruction::DisplayInversionControl, &[0x02]),
+    InitCommand::new(Instruction::DisplayFunctionControl, &[0x02, 0x22]),
+    InitCommand::new(Instruction::EntryModeSet, &[0xC6]),
+    InitCommand {
+        instruction: SET_IMAGE_FUNCTION,
+        params: &[0x00],
+    },
+    InitCommand::new(Instruction::AdjustControl3, &[0xA9, 0x51, 0x2C, 0x82]),
+];
+
+const BUYDISPLAY_PARALLEL: &[InitCommand] = &[
+    InitCommand::new(Instruction::PositiveGammaControl, &presets::VIVID.positive),
+    InitCommand::new(Instruction::NegativeGammaControl, &presets::VIVID.negative),
+    InitCommand::new(Instruction::PowerControl1, &[0x18, 0x16]),
+    InitCommand::new(Instruction::PowerControl2, &[0x41]),
+    InitCommand::new(Instruction::VCOMControl, &[0x00, 0x1E, 0x80]),
+    InitCommand::new(Instruction::DisplayInversionControl, &[0x02]),
+    InitCommand::new(Instruction::DisplayFunctionControl, &[0x02, 0x02, 0x3B]),
+    InitCommand::new(Instruction::EntryModeSet, &[0xC6]),
+    InitCommand {
+        instruction: SET_IMAGE_FUNCTION,
+        params: &[0x00],
+    },
+    InitCommand::new(Instruction::AdjustControl3, &[0xA9, 0x51, 0x2C, 0x82]),
+];
+
+/// Boards with a known initialization table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Profile {
+    /// Generic SPI modules, the same as [PanelVariant::TftEspi]
+    TftEspi,
+    /// Waveshare 3.5" SPI modules with an IPS panel, which need display inversion
+    Waveshare35,
+    /// MSP3520 3.5" SPI modules, the sequence of the LCDWiki example code
+    Msp3520,
+    /// BuyDisplay ER-TFTM035 modules on the 8/16-bit parallel bus
+    BuyDisplayParallel,
+}
+
+impl Profile {
+    /// Manufacturer commands sent before sleep out, pixel format and orientation
+    pub const fn init_table(self) -> &'static [InitCommand] {
+        match self {
+            Self::TftEspi => TFT_ESPI,
+            Self::Waveshare35 => WAVESHARE_35,
+            Self::Msp3520 => MSP3520,
+            Self::BuyDisplayParallel => BUYDISPLAY_PARALLEL,
+        }
+    }
+
+    /// Normal mode frame rate, without clock division
+    pub const fn frame_rate(self) -> FrameRate {
+        match self {
+            Self::Msp3520 => FrameRate::FrameRate68,
+            Self::TftEspi | Self::Waveshare35 | Self::BuyDisplayParallel => FrameRate::FrameRate60,
+        }
+    }
+
+    /// Whether display inversion has to be on for colors to look right
+    pub const fn inverted(self) -> bool {
+        matches!(self, Self::Waveshare35)
+    }
+}
+
+impl<IFACE, RESET, PixelFormat> Ili9488<IFACE, RESET, PixelFormat>
+where
+    IFACE: WriteOnlyDataCommand,
+    RESET: ResetDriver,
+    PixelFormat: Ili9488PixelFormat,
+{
+    /// Like [Ili9488::new], with the initialization table of a known board
+    pub fn new_with_profile<DELAY, MODE>(
+        profile: Profile,
+        interface: IFACE,
+        reset: RESET,
+        delay: &mut DELAY,
+        orientation: MODE,
+        pixel_format: PixelFormat,
+    ) -> Result<Self>
+    where
+        DELAY: DelayNs,
+        MODE: Mode,
+    {
+        Self::begin_init(interface, reset, orientation, pixel_format)
+            .panel_variant(PanelVariant::Profile(profile))
+            .finish(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockDelay, MockInterface, MockPin};
+    use crate::{Orientation, Rgb666Mode};
+    use std::vec::Vec;
+
+    fn init_commands(variant: PanelVariant) -> Vec<(u8, Vec<u8>)> {
+        let display = Ili9488::begin_init(
+            MockInterface::new(),
+            MockPin::default(),
+            Orientation::Portrait,
+            Rgb666Mode,
+        )
+        .panel_variant(variant)
+        .finish(&mut MockDelay::default())
+        .unwrap();
+        display.interface().commands()
+    }
+
+    #[test]
+    fn tft_espi_profile_matches_default() {
+        assert_eq!(
+            init_commands(PanelVariant::TftEspi),
+            init_commands(PanelVariant::Profile(Profile::TftEspi))
+        );
+        assert_eq!(Profile::TftEspi.init_table().len(), INIT_SEQUENCE.len());
+
+        let commands = init_commands(PanelVariant::Profile(Profile::Msp3520));
+        assert_eq!(commands[2], (0xe0, presets::DATASHEET.positive.to_vec()));
+        assert_eq!(commands[3], (0xe1, presets::DATASHEET.negative.to_vec()));
+    }
+
+    #[test]
+    fn new_with_profile() {
+        let display = Ili9488::new_with_profile(
+            Profile::Waveshare35,
+            MockInterface::new(),
+            MockPin::default(),
+            &mut MockDelay::default(),
+            Orientation::Portrait,
+            Rgb666Mode,
+        )
+        .unwrap();
+        let commands = display.interface().commands();
+        assert!(commands.contains(&(0xe9, std::vec![0x00])));
+        assert!(commands.contains(&(0x21, Vec::new())));
+        assert!(display.is_inverted());
+        assert_eq!(commands.last(), Some(&(0x29, Vec::new())));
+    }
+}
@@ -0,0 +1,242 @@
+//! Images in the [Quite OK Image format](https://qoiformat.org), for UI assets stored in flash
+//!
+//! QOI compresses about as well as PNG but decodes with a small state machine and a table of
+//! 64 colors, so images are decoded while they are written. Alpha is ignored, transparent
+//! pixels are drawn with their color. Images are drawn with [crate::Ili9488::draw_qoi].
+use display_interface::{DisplayError, WriteOnlyDataCommand};
+use embedded_graphics_core::pixelcolor::Rgb888;
+use embedded_graphics_core::prelude::{Point, Size};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::{Ili9488, Ili9488MemoryWrite, Ili9488PixelFormat, Result};
+
+const MAGIC: &[u8; 4] = b"qoif";
+const OP_RGB: u8 = 0xfe;
+const OP_RGBA: u8 = 0xff;
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_RUN: u8 = 0xc0;
+
+/// Iterator over the pixels of a QOI image
+pub struct QoiDecoder<'a> {
+    data: &'a [u8],
+    index: [[u8; 4]; 64],
+    pixel: [u8; 4],
+    run: u8,
+    remaining: usize,
+}
+
+impl<'a> QoiDecoder<'a> {
+    /// Read the header of `image`, returns the image size and a decoder for its pixels
+    ///
+    /// Returns `None` when `image` has no QOI header or its pixel count overflows `usize`. The
+    /// decoder stops early when the data runs out, see [QoiDecoder::remaining].
+    pub fn new(image: &'a [u8]) -> Option<(Size, Self)> {
+        let (header, data) = image.split_first_chunk::<14>()?;
+        if &header[..4] != MAGIC {
+            return None;
+        }
+        let width = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        let height = u32::from_be_bytes([header[8], header[9], header[10], header[11]]);
+        let remaining = (width as usize).checked_mul(height as usize)?;
+        let decoder = Self {
+            data,
+            index: [[0; 4]; 64],
+            pixel: [0, 0, 0, 255],
+            run: 0,
+            remaining,
+        };
+        Some((Size::new(width, height), decoder))
+    }
+
+    /// Number of pixels not decoded yet, more than 0 after the end when the data was truncated
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    fn next_byte(&mut self) -> Option<u8> {
+        let (&byte, data) = self.data.split_first()?;
+        self.data = data;
+        Some(byte)
+    }
+
+    fn decode(&mut self) -> Option<()> {
+        let op = self.next_byte()?;
+        let [r, g, b, a] = &mut self.pixel;
+        match op {
+            OP_RGB => {
+                let (rgb, data) = self.data.split_first_chunk::<3>()?;
+                [*r, *g, *b] = *rgb;
+                self.data = data;
+            }
+            OP_RGBA => {
+                let (rgba, data) = self.data.split_first_chunk::<4>()?;
+                [*r, *g, *b, *a] = *rgba;
+                self.data = data;
+            }
+            op => match op & 0xc0 {
+                OP_INDEX => self.pixel = self.index[op as usize],
+                OP_DIFF => {
+                    *r = r.wrapping_add((op >> 4) & 0x03).wrapping_sub(2);
+                    *g = g.wrapping_add((op >> 2) & 0x03).wrapping_sub(2);
+                    *b = b.wrapping_add(op & 0x03).wrapping_sub(2);
+                }
+                OP_LUMA => {
+                    let (&diff, data) = self.data.split_first()?;
+                    self.data = data;
+                    let dg = (op & 0x3f).wrapping_sub(32);
+                    *r = r.wrapping_add(dg).wrapping_add(diff >> 4).wrapping_sub(8);
+                    *g = g.wrapping_add(dg);
+                    *b = b.wrapping_add(dg).wrapping_add(diff & 0x0f).wrapping_sub(8);
+                }
+                // The pixel is repeated
+                _ => self.run = op - OP_RUN,
+            },
+        }
+        let [r, g, b, a] = self.pixel;
+        let hash = r as usize * 3 + g as usize * 5 + b as usize * 7 + a as usize * 11;
+        self.index[hash % 64] = self.pixel;
+        Some(())
+    }
+}
+
+impl Iterator for QoiDecoder<'_> {
+    type Item = Rgb888;
+
+    fn next(&mut self) -> Option<Rgb888> {
+        if self.remaining == 0 {
+            return None;
+        }
+        if self.run > 0 {
+            self.run -= 1;
+        } else if self.decode().is_none() {
+            // Truncated, don't decode the rest of the chunk as new ops
+            self.data = &[];
+            return None;
+        }
+        self.remaining -= 1;
+        let [r, g, b, _] = self.pixel;
+        Some(Rgb888::new(r, g, b))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+where
+    Self: Ili9488MemoryWrite,
+    <Self as Ili9488MemoryWrite>::PixelFormat: From<Rgb888>,
+    IFACE: WriteOnlyDataCommand,
+    PixelFormat: Ili9488PixelFormat,
+{
+    /// Draw a QOI image, see [crate::qoi], with its top left corner at (x, y).
+    ///
+    /// The image is decoded while it is written, in a single window. Fails with
+    /// [DisplayError::InvalidFormatError] when `image` does not start with a QOI header, or
+    /// when it ends before all pixels are decoded. The pixels decoded until then are drawn.
+    pub fn draw_qoi(&mut self, x: u16, y: u16, image: &[u8]) -> Result {
+        let (size, mut pixels) = QoiDecoder::new(image).ok_or(DisplayError::InvalidFormatError)?;
+        let region = Rectangle::new(Point::new(x.into(), y.into()), size);
+        self.set_region_window(&region)?;
+        self.write_iter(pixels.by_ref().map(Into::into))?;
+        if pixels.remaining() > 0 {
+            return Err(DisplayError::InvalidFormatError);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim;
+    use embedded_graphics_core::pixelcolor::RgbColor;
+    use std::vec::Vec;
+
+    fn image(width: u32, height: u32, chunks: &[u8]) -> Vec<u8> {
+        let mut image = MAGIC.to_vec();
+        image.extend(width.to_be_bytes());
+        image.extend(height.to_be_bytes());
+        image.extend([3, 0]);
+        image.extend(chunks);
+        image.extend([0, 0, 0, 0, 0, 0, 0, 1]);
+        image
+    }
+
+    #[test]
+    fn decode_all_ops() {
+        let chunks = [
+            &[OP_RGB, 100, 150, 200][..],
+            // dr +1, dg -1, db 0
+            &[OP_DIFF | 0b11_01_10],
+            // dg +8, dr - dg +5, db - dg +2
+            &[OP_LUMA | 40, 0xda],
+            // 2 more
+            &[OP_RUN | 1],
+            &[OP_RGBA, 10, 20, 30, 0],
+            // The pixel after the diff
+            &[OP_INDEX | 5],
+        ];
+        let image = image(7, 1, &chunks.concat());
+        let (size, decoder) = QoiDecoder::new(&image).unwrap();
+        assert_eq!(size, Size::new(7, 1));
+        let diff = Rgb888::new(101, 149, 200);
+        let luma = Rgb888::new(114, 157, 210);
+        // The end marker is not decoded as pixels
+        assert!(decoder.eq([
+            Rgb888::new(100, 150, 200),
+            diff,
+            luma,
+            luma,
+            luma,
+            Rgb888::new(10, 20, 30),
+            diff,
+        ]));
+        assert!(QoiDecoder::new(&image[1..]).is_none());
+    }
+
+    #[test]
+    fn draw_qoi() {
+        let mut display = sim::display();
+        let image = image(2, 2, &[OP_RGB, 255, 0, 0, OP_RUN | 1, OP_RGB, 0, 0, 255]);
+        display.draw_qoi(5, 6, &image).unwrap();
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(6, 6), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(5, 7), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(6, 7), Some(Rgb888::BLUE));
+        assert!(display.draw_qoi(319, 0, &image).is_err());
+        assert!(matches!(
+            display.draw_qoi(0, 0, &image[1..]),
+            Err(DisplayError::InvalidFormatError)
+        ));
+    }
+
+    #[test]
+    fn truncated_image() {
+        let mut display = sim::display();
+        let image = image(2, 2, &[OP_RGB, 255, 0, 0, OP_RUN | 1, OP_RGB, 0, 0, 255]);
+        // Cut in the middle of the last OP_RGB chunk
+        let truncated = &image[..image.len() - 10];
+        let (_, mut decoder) = QoiDecoder::new(truncated).unwrap();
+        assert_eq!(decoder.by_ref().count(), 3);
+        assert_eq!(decoder.remaining(), 1);
+        assert_eq!(decoder.next(), None);
+        assert!(matches!(
+            display.draw_qoi(5, 6, truncated),
+            Err(DisplayError::InvalidFormatError)
+        ));
+        assert_eq!(display.interface().pixel(5, 7), Some(Rgb888::RED));
+
+        let mut huge = image.clone();
+        huge[4..12].fill(0xff);
+        assert_eq!(
+            QoiDecoder::new(&huge).is_none(),
+            (u32::MAX as usize).checked_mul(u32::MAX as usize).is_none()
+        );
+    }
+}
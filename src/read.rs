@@ -0,0 +1,554 @@
+use display_interface::{DisplayError, WriteOnlyDataCommand};
+use embedded_graphics_core::pixelcolor::{Rgb666, RgbColor};
+use embedded_graphics_core::prelude::{Dimensions, Point};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::dcs::Instruction;
+#[cfg(feature = "rgb666")]
+use crate::dcs::WriteMemoryStart;
+use crate::{Ili9488, InterfaceRef, Result, Rgb666Mode};
+
+/// Result of [Ili9488::self_diagnostic]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DiagnosticReport {
+    /// The factory defaults were loaded into the registers after reset (RELD)
+    pub register_loading_ok: bool,
+    /// The internal functionality check passed (FUND)
+    pub functionality_ok: bool,
+    /// The booster voltage is on (BSTON)
+    pub booster_ok: bool,
+    /// The controller is out of sleep mode
+    pub sleep_out: bool,
+    /// The display is on
+    pub display_on: bool,
+    /// Idle mode is on
+    pub idle_mode: bool,
+    /// Display inversion is on
+    pub inverted: bool,
+    /// Memory access control (MADCTL) bits
+    pub madctl: u8,
+    /// Bits per pixel setting of the MCU interface, 0x5 for 16 and 0x6 for 18 bits
+    pub pixel_format: u8,
+}
+
+impl DiagnosticReport {
+    /// Whether the self checks passed and the panel is awake and showing the frame memory
+    pub fn is_ok(&self) -> bool {
+        self.register_loading_ok
+            && self.functionality_ok
+            && self.booster_ok
+            && self.sleep_out
+            && self.display_on
+    }
+}
+
+/// The first pixel that read back differently, found by [Ili9488::verify_region]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PixelMismatch {
+    /// Screen coordinates of the pixel
+    pub point: Point,
+    /// The color that was drawn
+    pub expected: Rgb666,
+    /// The color in the display memory
+    pub actual: Rgb666,
+}
+
+// Point and Rgb666 do not implement defmt::Format
+#[cfg(feature = "defmt")]
+impl defmt::Format for PixelMismatch {
+    fn format(&self, f: defmt::Formatter) {
+        let (expected, actual) = (self.expected, self.actual);
+        defmt::write!(
+            f,
+            "PixelMismatch {{ point: ({}, {}), expected: ({}, {}, {}), actual: ({}, {}, {}) }}",
+            self.point.x,
+            self.point.y,
+            expected.r(),
+            expected.g(),
+            expected.b(),
+            actual.r(),
+            actual.g(),
+            actual.b()
+        )
+    }
+}
+
+/// Identification of the controller, read with [Ili9488::read_controller_id]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ControllerId {
+    /// Manufacturer ID, module version and module ID of Read Display Identification (RDDID)
+    pub display_id: [u8; 3],
+    /// IC version and model of Read ID4, `[0x00, 0x94, 0x88]` on an ILI9488
+    pub ic_id: [u8; 3],
+}
+
+impl ControllerId {
+    /// Whether the controller reports itself as an ILI9488.
+    ///
+    /// All zeros or all ones usually mean the data output is not connected or DC/CS are
+    /// miswired, `0x94 0x86` is an ILI9486.
+    pub fn is_ili9488(&self) -> bool {
+        self.ic_id[1..] == [0x94, 0x88]
+    }
+}
+
+/// Errors of [Ili9488::new_verified]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum VerifyError {
+    /// The controller did not identify as an ILI9488
+    UnsupportedController(ControllerId),
+    /// Communication with the display failed
+    Display(DisplayError),
+}
+
+impl From<DisplayError> for VerifyError {
+    fn from(error: DisplayError) -> Self {
+        Self::Display(error)
+    }
+}
+
+/// Display interfaces that can also read data back from the controller
+///
+/// `display-interface` only covers writing, implement this trait for interfaces where
+/// the data output of the controller (SDO, or the bidirectional data bus) is connected.
+pub trait ReadDataCommand: WriteOnlyDataCommand {
+    /// Send `command` and fill `buf` with the bytes the controller answers with.
+    ///
+    /// The raw bytes are returned, dummy bytes are skipped by the driver.
+    fn read_data(&mut self, command: u8, buf: &mut [u8]) -> Result;
+}
+
+impl<IFACE: ReadDataCommand> ReadDataCommand for InterfaceRef<'_, IFACE> {
+    fn read_data(&mut self, command: u8, buf: &mut [u8]) -> Result {
+        self.0.read_data(command, buf)
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+where
+    IFACE: ReadDataCommand,
+{
+    /// Read the identification registers of the controller (RDDID and ID4)
+    pub fn read_controller_id(&mut self) -> Result<ControllerId> {
+        // Both reads start with a dummy byte
+        let mut display_id = [0; 4];
+        self.interface
+            .read_data(Instruction::ReadDisplayId as u8, &mut display_id)?;
+        let mut ic_id = [0; 4];
+        self.interface
+            .read_data(Instruction::ReadId4 as u8, &mut ic_id)?;
+        let ([_, id1, id2, id3], [_, ic1, ic2, ic3]) = (display_id, ic_id);
+        Ok(ControllerId {
+            display_id: [id1, id2, id3],
+            ic_id: [ic1, ic2, ic3],
+        })
+    }
+
+    /// Read the self-diagnostic result (RDDSDR) and the display status (RDDST), to check
+    /// the panel is alive, e.g. in production test firmware.
+    ///
+    /// Only meaningful 5 ms after sleep out, when the self checks have run.
+    pub fn self_diagnostic(&mut self) -> Result<DiagnosticReport> {
+        // Both reads start with a dummy byte
+        let mut diagnostic = [0; 2];
+        self.interface
+            .read_data(Instruction::ReadSelfDiagnostic as u8, &mut diagnostic)?;
+        let mut status = [0; 5];
+        self.interface
+            .read_data(Instruction::ReadDisplayStatus as u8, &mut status)?;
+        let [_, diagnostic] = diagnostic;
+        let [_, status1, status2, status3, _] = status;
+
+        Ok(DiagnosticReport {
+            register_loading_ok: diagnostic & 0x80 != 0,
+            functionality_ok: diagnostic & 0x40 != 0,
+            booster_ok: status1 & 0x80 != 0,
+            // MY, MX, MV, ML, BGR and MH in the order of MADCTL, one bit lower
+            madctl: (status1 << 1) & 0xfc,
+            pixel_format: (status2 >> 4) & 0x07,
+            idle_mode: status2 & 0x08 != 0,
+            sleep_out: status2 & 0x02 != 0,
+            inverted: status3 & 0x20 != 0,
+            display_on: status3 & 0x04 != 0,
+        })
+    }
+}
+
+impl<IFACE, RESET, const BURST: usize, DELAY> Ili9488<IFACE, RESET, Rgb666Mode, BURST, DELAY>
+where
+    IFACE: ReadDataCommand,
+{
+    /// Copy the `src` rectangle of the screen so its top left corner ends up at `dst`.
+    ///
+    /// The display memory is read back and rewritten one line at a time, so no frame buffer
+    /// is needed. `line_buffer` must hold at least `3 * src.size.width + 1` bytes.
+    /// Overlapping source and destination areas are handled.
+    #[cfg(feature = "rgb666")]
+    pub fn copy_region(&mut self, src: Rectangle, dst: Point, line_buffer: &mut [u8]) -> Result {
+        let dst_area = Rectangle::new(dst, src.size);
+        let screen = self.bounding_box();
+        let (Some(src_bottom_right), Some(_)) = (src.bottom_right(), dst_area.bottom_right())
+        else {
+            return Ok(());
+        };
+        if screen.intersection(&src) != src || screen.intersection(&dst_area) != dst_area {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        // One dummy byte, then 3 bytes per pixel
+        let len = src.size.width as usize * 3 + 1;
+        let line_buffer = line_buffer
+            .get_mut(..len)
+            .ok_or(DisplayError::OutOfBoundsError)?;
+
+        let (x0, x1) = (src.top_left.x as u16, src_bottom_right.x as u16);
+        let dx = dst.x as u16;
+        let lines = src.size.height as u16;
+        for line in 0..lines {
+            // Copy bottom up when moving down, so lines are read before they are overwritten
+            let line = if dst.y > src.top_left.y {
+                lines - 1 - line
+            } else {
+                line
+            };
+            let sy = src.top_left.y as u16 + line;
+            let dy = dst.y as u16 + line;
+
+            self.set_window(x0, sy, x1, sy)?;
+            self.interface
+                .read_data(Instruction::MemoryRead as u8, line_buffer)?;
+            let line = &line_buffer[1..];
+            self.retrying(|display| {
+                display.set_window(dx, dy, dx + (x1 - x0), dy)?;
+                display.write_command(WriteMemoryStart)?;
+                display.write_pixel_bytes(line)
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Read the pixels of `region` into `buf`, row by row, to draw them back later with
+    /// [Ili9488::restore_region], e.g. before showing a popup or menu over it.
+    ///
+    /// `buf` must hold at least as many pixels as `region`.
+    pub fn save_region(&mut self, region: Rectangle, buf: &mut [Rgb666]) -> Result {
+        let Some(bottom_right) = region.bottom_right() else {
+            return Ok(());
+        };
+        if self.bounding_box().intersection(&region) != region {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        self.read_region(
+            region.top_left.x as u16,
+            region.top_left.y as u16,
+            bottom_right.x as u16,
+            bottom_right.y as u16,
+            buf,
+        )
+    }
+
+    /// Read `region` back and compare it with `expected`, given row by row like
+    /// [Region::pixels](crate::Region::pixels), returning the first pixel that differs.
+    ///
+    /// A debugging aid for hardware bring-up: draw a test pattern and verify it to qualify
+    /// an SPI clock rate or a long cable. Pixels after the end of `expected` are not checked.
+    pub fn verify_region<I>(
+        &mut self,
+        region: Rectangle,
+        expected: I,
+    ) -> Result<Option<PixelMismatch>>
+    where
+        I: IntoIterator<Item = Rgb666>,
+    {
+        let Some(bottom_right) = region.bottom_right() else {
+            return Ok(None);
+        };
+        if self.bounding_box().intersection(&region) != region {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        let (x0, y0) = (region.top_left.x as u16, region.top_left.y as u16);
+        let (x1, y1) = (bottom_right.x as u16, bottom_right.y as u16);
+        let mut expected = expected.into_iter();
+        let mut pixels = [Rgb666::BLACK; READ_CHUNK_PIXELS];
+        for y in y0..=y1 {
+            for x in (x0..=x1).step_by(READ_CHUNK_PIXELS) {
+                let end = x1.min(x + READ_CHUNK_PIXELS as u16 - 1);
+                let pixels = &mut pixels[..usize::from(end - x) + 1];
+                self.read_region(x, y, end, y, pixels)?;
+                for (px, &actual) in (x..).zip(pixels.iter()) {
+                    let Some(expected) = expected.next() else {
+                        return Ok(None);
+                    };
+                    if actual != expected {
+                        return Ok(Some(PixelMismatch {
+                            point: Point::new(px.into(), y.into()),
+                            expected,
+                            actual,
+                        }));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Blend `color` over the pixel at (x, y) with opacity `alpha`, 255 being opaque.
+    ///
+    /// The pixel is read back from the display, so no frame buffer is needed.
+    #[cfg(feature = "rgb666")]
+    pub fn blend_pixel(&mut self, x: u16, y: u16, color: Rgb666, alpha: u8) -> Result {
+        let mut pixel = [Rgb666::BLACK];
+        self.read_region(x, y, x, y, &mut pixel)?;
+        self.draw_raw_slice(x, y, x, y, &[blend(color, pixel[0], alpha)])
+    }
+
+    /// Blend `color` over `region` with opacity `alpha`, 255 being opaque, e.g. to dim the
+    /// screen behind a dialog or draw a translucent toast.
+    ///
+    /// The region is read back and rewritten in chunks of a few pixels, so no frame buffer
+    /// is needed.
+    #[cfg(feature = "rgb666")]
+    pub fn blend_region(&mut self, region: Rectangle, color: Rgb666, alpha: u8) -> Result {
+        let Some(bottom_right) = region.bottom_right() else {
+            return Ok(());
+        };
+        if self.bounding_box().intersection(&region) != region {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        let (x0, y0) = (region.top_left.x as u16, region.top_left.y as u16);
+        let (x1, y1) = (bottom_right.x as u16, bottom_right.y as u16);
+        let mut pixels = [Rgb666::BLACK; READ_CHUNK_PIXELS];
+        for y in y0..=y1 {
+            for x in (x0..=x1).step_by(READ_CHUNK_PIXELS) {
+                let end = x1.min(x + READ_CHUNK_PIXELS as u16 - 1);
+                let pixels = &mut pixels[..usize::from(end - x) + 1];
+                self.read_region(x, y, end, y, pixels)?;
+                for pixel in pixels.iter_mut() {
+                    *pixel = blend(color, *pixel, alpha);
+                }
+                self.draw_raw_slice(x, y, end, y, pixels)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the pixels of the window (x0, y0) to (x1, y1) back from the display into `buf`,
+    /// row by row.
+    ///
+    /// Useful for screenshots or checking rendered output in hardware-in-the-loop tests.
+    /// `buf` must hold at least as many pixels as the window.
+    pub fn read_region(
+        &mut self,
+        x0: u16,
+        y0: u16,
+        x1: u16,
+        y1: u16,
+        buf: &mut [Rgb666],
+    ) -> Result {
+        if x1 < x0 || y1 < y0 {
+            return Ok(());
+        }
+        let len = (x1 - x0 + 1) as usize * (y1 - y0 + 1) as usize;
+        let buf = buf.get_mut(..len).ok_or(DisplayError::OutOfBoundsError)?;
+        self.set_window(x0, y0, x1, y1)?;
+
+        // Every read starts with a dummy byte, followed by one byte per channel
+        let mut raw = [0; 1 + READ_CHUNK_PIXELS * 3];
+        let mut command = Instruction::MemoryRead;
+        for pixels in buf.chunks_mut(READ_CHUNK_PIXELS) {
+            let raw = &mut raw[..1 + pixels.len() * 3];
+            self.interface.read_data(command as u8, raw)?;
+            for (pixel, rgb) in pixels.iter_mut().zip(raw[1..].chunks_exact(3)) {
+                *pixel = Rgb666::new(rgb[0] >> 2, rgb[1] >> 2, rgb[2] >> 2);
+            }
+            command = Instruction::MemoryReadContinue;
+        }
+        Ok(())
+    }
+}
+
+/// Mix `color` over `background`, `alpha` 255 is opaque
+#[cfg(feature = "rgb666")]
+fn blend(color: Rgb666, background: Rgb666, alpha: u8) -> Rgb666 {
+    let (alpha, rest) = (u16::from(alpha), 255 - u16::from(alpha));
+    let mix = |c: u8, b: u8| ((u16::from(c) * alpha + u16::from(b) * rest + 127) / 255) as u8;
+    Rgb666::new(
+        mix(color.r(), background.r()),
+        mix(color.g(), background.g()),
+        mix(color.b(), background.b()),
+    )
+}
+
+/// Number of pixels read from the display with one command
+const READ_CHUNK_PIXELS: usize = 32;
+
+#[cfg(test)]
+mod tests {
+    use super::{DiagnosticReport, PixelMismatch};
+    use crate::mock;
+    use crate::sim;
+    use crate::{Orientation, Rgb666Mode};
+    use display_interface::DisplayError;
+    use embedded_graphics_core::pixelcolor::{Rgb666, Rgb888, RgbColor};
+    use embedded_graphics_core::prelude::{DrawTarget, Point, Size};
+    use embedded_graphics_core::primitives::Rectangle;
+    use std::vec::Vec;
+
+    #[test]
+    fn self_diagnostic() {
+        let mut display = mock::display();
+        display.interface.push_read(&[0x00, 0xc0]);
+        display.interface.push_read(&[0x00, 0xa4, 0x63, 0x04, 0x00]);
+
+        let report = display.self_diagnostic().unwrap();
+        assert_eq!(
+            report,
+            DiagnosticReport {
+                register_loading_ok: true,
+                functionality_ok: true,
+                booster_ok: true,
+                sleep_out: true,
+                display_on: true,
+                idle_mode: false,
+                inverted: false,
+                madctl: 0x48,
+                pixel_format: 0x06,
+            }
+        );
+        assert!(report.is_ok());
+
+        // Nothing answering reads back as zeros
+        assert!(!display.self_diagnostic().unwrap().is_ok());
+    }
+
+    #[test]
+    fn blend_region() {
+        let mut display = sim::display_with(Orientation::Landscape, Rgb666Mode);
+        display.clear_screen(Rgb666::new(0, 0, 60)).unwrap();
+        let region = Rectangle::new(Point::new(10, 10), Size::new(40, 2));
+        display.blend_region(region, Rgb666::RED, 128).unwrap();
+        display.blend_pixel(5, 5, Rgb666::WHITE, 255).unwrap();
+
+        let sim = display.interface();
+        // 63 * 128 / 255 = 32 red and 60 * 127 / 255 = 30 blue, expanded to 8 bits
+        let blended = Rgb888::new(130, 0, 121);
+        assert_eq!(sim.pixel(10, 10), Some(blended));
+        assert_eq!(sim.pixel(49, 11), Some(blended));
+        assert_eq!(sim.pixel(50, 11), Some(Rgb888::new(0, 0, 243)));
+        assert_eq!(sim.pixel(5, 5), Some(Rgb888::WHITE));
+    }
+
+    #[test]
+    fn verify_region() {
+        let mut display = sim::display_with(Orientation::Landscape, Rgb666Mode);
+        let area = Rectangle::new(Point::new(440, 100), Size::new(40, 3));
+        let pattern = || (0..120).map(|i| Rgb666::new(i % 64, 63 - i % 64, 7));
+        display.fill_contiguous(&area, pattern()).unwrap();
+        assert_eq!(display.verify_region(area, pattern()).unwrap(), None);
+
+        display
+            .draw_raw_slice(475, 101, 475, 101, &[Rgb666::RED])
+            .unwrap();
+        assert_eq!(
+            display.verify_region(area, pattern()).unwrap(),
+            Some(PixelMismatch {
+                point: Point::new(475, 101),
+                expected: Rgb666::new(11, 52, 7),
+                actual: Rgb666::RED,
+            })
+        );
+        // The broken pixel is past the expected ones
+        assert_eq!(
+            display.verify_region(area, pattern().take(60)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn save_and_restore_region() {
+        let mut display = sim::display_with(Orientation::Landscape, Rgb666Mode);
+        display.clear_screen(Rgb666::GREEN).unwrap();
+        display
+            .draw_raw_slice(20, 20, 20, 20, &[Rgb666::RED])
+            .unwrap();
+
+        let popup = Rectangle::new(Point::new(10, 15), Size::new(30, 10));
+        let mut underneath = [Rgb666::BLACK; 300];
+        display.save_region(popup, &mut underneath).unwrap();
+        display.fill_solid(&popup, Rgb666::WHITE).unwrap();
+        assert_eq!(display.interface().pixel(20, 20), Some(Rgb888::WHITE));
+        display.restore_region(popup, &underneath).unwrap();
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(20, 20), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(10, 15), Some(Rgb888::GREEN));
+        assert_eq!(sim.pixel(39, 24), Some(Rgb888::GREEN));
+
+        let outside = Rectangle::new(Point::new(470, 0), Size::new(20, 1));
+        assert!(matches!(
+            display.save_region(outside, &mut underneath),
+            Err(DisplayError::OutOfBoundsError)
+        ));
+        assert!(matches!(
+            display.restore_region(popup, &underneath[..299]),
+            Err(DisplayError::OutOfBoundsError)
+        ));
+    }
+
+    #[test]
+    fn copy_region_overlapping() {
+        let mut display = sim::display_with(Orientation::Landscape, Rgb666Mode);
+        display
+            .draw_raw_slice(
+                10,
+                10,
+                11,
+                11,
+                &[Rgb666::RED, Rgb666::GREEN, Rgb666::BLUE, Rgb666::WHITE],
+            )
+            .unwrap();
+
+        let mut buf = [0; 7];
+        let src = Rectangle::new(Point::new(10, 10), Size::new(2, 2));
+        display
+            .copy_region(src, Point::new(11, 11), &mut buf)
+            .unwrap();
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(10, 10), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(11, 11), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(12, 11), Some(Rgb888::GREEN));
+        assert_eq!(sim.pixel(11, 12), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(12, 12), Some(Rgb888::WHITE));
+    }
+
+    #[test]
+    fn read_region_in_chunks() {
+        let mut display = sim::display_with(Orientation::Landscape, Rgb666Mode);
+        let colors: Vec<Rgb666> = (0..100u8)
+            .map(|i| Rgb666::new(i % 64, 63 - i % 64, i / 2))
+            .collect();
+        display.draw_raw_slice(5, 5, 14, 14, &colors).unwrap();
+
+        let mut buf = [Rgb666::BLACK; 100];
+        display.read_region(5, 5, 14, 14, &mut buf).unwrap();
+        assert_eq!(buf[..], colors[..]);
+
+        assert!(display.read_region(0, 0, 10, 10, &mut buf).is_err());
+    }
+
+    #[test]
+    fn copy_region_checks_bounds() {
+        let mut display = sim::display_with(Orientation::Landscape, Rgb666Mode);
+        let src = Rectangle::new(Point::new(0, 0), Size::new(2, 2));
+        assert!(display
+            .copy_region(src, Point::new(479, 0), &mut [0; 7])
+            .is_err());
+        assert!(display
+            .copy_region(src, Point::new(2, 0), &mut [0; 6])
+            .is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use embedded_graphics_core::prelude::{Dimensions, Point, PointsIter, Size};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::Ili9488;
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+{
+    /// The part of `area` that is on screen, with the coordinates of its pixels in the order
+    /// the controller fills a window.
+    ///
+    /// The orientation set with [Ili9488::set_orientation] is already applied to the window, so
+    /// pixels are always filled row by row, left to right. Custom renderers can produce their
+    /// colors in the order of [Region::pixels] and hand them to
+    /// [DrawTarget::fill_contiguous](embedded_graphics_core::draw_target::DrawTarget::fill_contiguous)
+    /// or [Ili9488::draw_raw_iter].
+    ///
+    /// ```ignore
+    /// let region = display.region(Rectangle::new(Point::new(10, 10), Size::new(100, 50)));
+    /// let colors = region.pixels().map(|point| shade(point));
+    /// display.fill_contiguous(&region.area(), colors)?;
+    /// ```
+    pub fn region(&self, area: Rectangle) -> Region {
+        Region {
+            area: area.intersection(&self.bounding_box()),
+        }
+    }
+}
+
+/// An on-screen area of the display, obtained with [Ili9488::region]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region {
+    area: Rectangle,
+}
+
+// Rectangle does not implement defmt::Format
+#[cfg(feature = "defmt")]
+impl defmt::Format for Region {
+    fn format(&self, f: defmt::Formatter) {
+        let Rectangle { top_left, size } = self.area;
+        defmt::write!(
+            f,
+            "Region {{ x: {}, y: {}, width: {}, height: {} }}",
+            top_left.x,
+            top_left.y,
+            size.width,
+            size.height
+        )
+    }
+}
+
+impl Region {
+    /// The area, empty when it was entirely off screen
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    /// Coordinates of every pixel, in the order they are sent when the area is one window
+    pub fn pixels(&self) -> impl Iterator<Item = Point> {
+        self.area.points()
+    }
+
+    /// The rows of the area, top to bottom, in the order they are sent when the area is one
+    /// window
+    pub fn rows(&self) -> impl Iterator<Item = Rectangle> {
+        let Rectangle { top_left, size } = self.area;
+        (0..size.height as i32)
+            .map(move |y| Rectangle::new(top_left + Point::new(0, y), Size::new(size.width, 1)))
+    }
+
+    /// The columns of the area, left to right. A column has to be its own window, its pixels
+    /// are then sent top to bottom.
+    pub fn columns(&self) -> impl Iterator<Item = Rectangle> {
+        let Rectangle { top_left, size } = self.area;
+        (0..size.width as i32)
+            .map(move |x| Rectangle::new(top_left + Point::new(x, 0), Size::new(1, size.height)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sim;
+    use crate::{Orientation, Rgb666Mode};
+    use embedded_graphics_core::pixelcolor::{Rgb666, Rgb888, RgbColor};
+    use embedded_graphics_core::prelude::DrawTarget;
+    use std::vec::Vec;
+
+    #[test]
+    fn region_in_streaming_order() {
+        let mut display = sim::display_with(Orientation::LandscapeFlipped, Rgb666Mode);
+        let region = display.region(Rectangle::new(Point::new(478, 318), Size::new(4, 4)));
+        assert_eq!(
+            region.area(),
+            Rectangle::new(Point::new(478, 318), Size::new(2, 2))
+        );
+        let pixels: Vec<Point> = region.pixels().collect();
+        assert_eq!(
+            pixels,
+            [
+                Point::new(478, 318),
+                Point::new(479, 318),
+                Point::new(478, 319),
+                Point::new(479, 319),
+            ]
+        );
+        assert_eq!(region.rows().count(), 2);
+        assert_eq!(
+            region.columns().last(),
+            Some(Rectangle::new(Point::new(479, 318), Size::new(1, 2)))
+        );
+
+        // Colors made in the order of the pixels end up where they belong
+        let colors = region.pixels().map(|point| {
+            if point.x == 479 {
+                Rgb666::RED
+            } else {
+                Rgb666::BLUE
+            }
+        });
+        display.fill_contiguous(&region.area(), colors).unwrap();
+        let sim = display.interface();
+        assert_eq!(sim.pixel(479, 318), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(479, 319), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(478, 319), Some(Rgb888::BLUE));
+
+        let off_screen = display.region(Rectangle::new(Point::new(-10, 0), Size::new(5, 5)));
+        assert_eq!(off_screen.pixels().count(), 0);
+        assert_eq!(off_screen.rows().count(), 0);
+    }
+}
@@ -0,0 +1,87 @@
+use display_interface::DisplayError;
+use embedded_hal::digital::OutputPin;
+
+use crate::Result;
+
+/// Drives the hardware reset (RESX) of the controller during initialization
+///
+/// Every [OutputPin] is a reset driver for an active-low reset, the way RESX is wired on
+/// most boards. Pins behind an I/O expander work as well, as long as their driver implements
+/// [OutputPin]. Wrap the pin in [ActiveHigh] when the line goes through an inverter, or use
+/// [NoReset] when RESX is tied to the supply or the MCU reset.
+pub trait ResetDriver {
+    /// Hold the controller in reset
+    fn hold(&mut self) -> Result;
+
+    /// Let the controller run
+    fn release(&mut self) -> Result;
+}
+
+impl<P: OutputPin> ResetDriver for P {
+    fn hold(&mut self) -> Result {
+        self.set_low().map_err(|_| DisplayError::RSError)
+    }
+
+    fn release(&mut self) -> Result {
+        self.set_high().map_err(|_| DisplayError::RSError)
+    }
+}
+
+/// Reset through an inverter, the controller is held in reset while the pin is high
+pub struct ActiveHigh<P>(pub P);
+
+impl<P: OutputPin> ResetDriver for ActiveHigh<P> {
+    fn hold(&mut self) -> Result {
+        self.0.set_high().map_err(|_| DisplayError::RSError)
+    }
+
+    fn release(&mut self) -> Result {
+        self.0.set_low().map_err(|_| DisplayError::RSError)
+    }
+}
+
+/// No reset line, the controller is only reset by software
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoReset;
+
+impl ResetDriver for NoReset {
+    fn hold(&mut self) -> Result {
+        Ok(())
+    }
+
+    fn release(&mut self) -> Result {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockDelay, MockInterface, MockPin};
+    use crate::{Ili9488, Orientation, Rgb666Mode};
+
+    #[test]
+    fn reset_drivers() {
+        let display = Ili9488::new(
+            MockInterface::new(),
+            ActiveHigh(MockPin::default()),
+            &mut MockDelay::default(),
+            Orientation::Portrait,
+            Rgb666Mode,
+        )
+        .unwrap();
+        let (_, ActiveHigh(pin)) = display.release();
+        assert_eq!(pin.states, [false, true, false]);
+
+        let display = Ili9488::new(
+            MockInterface::new(),
+            NoReset,
+            &mut MockDelay::default(),
+            Orientation::Portrait,
+            Rgb666Mode,
+        )
+        .unwrap();
+        // The software reset still runs
+        assert_eq!(display.release().0.commands()[1], (0x01, std::vec![]));
+    }
+}
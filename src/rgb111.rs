@@ -1,8 +1,10 @@
-use embedded_graphics_core::pixelcolor::IntoStorage;
+use embedded_graphics_core::pixelcolor::raw::{RawData, RawU4};
+use embedded_graphics_core::pixelcolor::{BinaryColor, Rgb565, Rgb666, Rgb888};
 use embedded_graphics_core::prelude::{PixelColor, RgbColor};
 
 /// Represents Rgb111 colors
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Rgb111 {
     BLACK,
     RED,
@@ -29,14 +31,22 @@ impl Rgb111 {
         }
     }
 }
-impl IntoStorage for Rgb111 {
-    type Storage = u8;
-    fn into_storage(self) -> Self::Storage {
-        self.raw()
+/// Raw data is `0b0rgb`, also returned by `into_storage`, so images with two pixels per byte work with the
+/// embedded-graphics image types, e.g. `ImageRaw<Rgb111>`
+impl PixelColor for Rgb111 {
+    type Raw = RawU4;
+}
+impl From<RawU4> for Rgb111 {
+    /// Uses the lower 3 bits, `0bxrgb`
+    fn from(raw: RawU4) -> Self {
+        let bits = raw.into_inner();
+        Self::from_bits(bits & 0b100 != 0, bits & 0b010 != 0, bits & 0b001 != 0)
     }
 }
-impl PixelColor for Rgb111 {
-    type Raw = ();
+impl From<Rgb111> for RawU4 {
+    fn from(color: Rgb111) -> Self {
+        RawU4::new(color.raw())
+    }
 }
 impl RgbColor for Rgb111 {
     const MAX_R: u8 = 1;
@@ -69,3 +79,116 @@ impl RgbColor for Rgb111 {
         }
     }
 }
+impl Rgb111 {
+    /// The color with the given channels turned on
+    pub(crate) fn from_bits(r: bool, g: bool, b: bool) -> Self {
+        match (r, g, b) {
+            (false, false, false) => Self::BLACK,
+            (false, false, true) => Self::BLUE,
+            (false, true, false) => Self::GREEN,
+            (false, true, true) => Self::CYAN,
+            (true, false, false) => Self::RED,
+            (true, false, true) => Self::MAGENTA,
+            (true, true, false) => Self::YELLOW,
+            (true, true, true) => Self::WHITE,
+        }
+    }
+
+    /// The nearest [Rgb111] color to any RGB color, keeping the most significant bit of
+    /// every channel.
+    ///
+    /// Works with the [WebColors](embedded_graphics_core::pixelcolor::WebColors) constants
+    /// as well, e.g. `Rgb111::nearest(Rgb888::CSS_ORANGE)` is yellow.
+    pub fn nearest<C: RgbColor>(color: C) -> Self {
+        let msb = |value: u8, max: u8| value > max / 2;
+        Self::from_bits(
+            msb(color.r(), C::MAX_R),
+            msb(color.g(), C::MAX_G),
+            msb(color.b(), C::MAX_B),
+        )
+    }
+
+    /// The [Rgb111] color equal to `color`, if every channel is either off or at its maximum
+    pub fn exact<C: RgbColor>(color: C) -> Option<Self> {
+        let nearest = Self::nearest(color);
+        (nearest.to_color::<C>() == color).then_some(nearest)
+    }
+
+    /// The same color in another RGB color type, channels either off or at their maximum
+    pub fn to_color<C: RgbColor>(self) -> C {
+        match self {
+            Self::BLACK => C::BLACK,
+            Self::RED => C::RED,
+            Self::GREEN => C::GREEN,
+            Self::BLUE => C::BLUE,
+            Self::YELLOW => C::YELLOW,
+            Self::MAGENTA => C::MAGENTA,
+            Self::CYAN => C::CYAN,
+            Self::WHITE => C::WHITE,
+        }
+    }
+}
+
+macro_rules! impl_conversions {
+    ($($color:ty),*) => {
+        $(
+            impl From<$color> for Rgb111 {
+                /// Keeps the most significant bit of every channel, see [Rgb111::nearest]
+                fn from(color: $color) -> Self {
+                    Self::nearest(color)
+                }
+            }
+
+            impl From<Rgb111> for $color {
+                fn from(color: Rgb111) -> Self {
+                    color.to_color()
+                }
+            }
+        )*
+    };
+}
+
+impl_conversions!(Rgb565, Rgb666, Rgb888);
+
+impl From<BinaryColor> for Rgb111 {
+    /// [BinaryColor::On] is white, [BinaryColor::Off] black
+    fn from(color: BinaryColor) -> Self {
+        match color {
+            BinaryColor::On => Self::WHITE,
+            BinaryColor::Off => Self::BLACK,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics_core::pixelcolor::WebColors;
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Rgb111::from(Rgb666::new(32, 31, 63)), Rgb111::MAGENTA);
+        assert_eq!(Rgb111::from(Rgb565::new(15, 32, 16)), Rgb111::CYAN);
+        assert_eq!(Rgb111::from(Rgb888::CSS_ORANGE), Rgb111::YELLOW);
+        assert_eq!(Rgb111::from(Rgb888::CSS_NAVY), Rgb111::BLUE);
+        assert_eq!(Rgb111::from(BinaryColor::On), Rgb111::WHITE);
+
+        for color in [Rgb111::BLACK, Rgb111::RED, Rgb111::CYAN, Rgb111::WHITE] {
+            assert_eq!(Rgb111::from(Rgb666::from(color)), color);
+            assert_eq!(Rgb111::from(Rgb565::from(color)), color);
+            assert_eq!(Rgb111::from(Rgb888::from(color)), color);
+        }
+        assert_eq!(Rgb666::from(Rgb111::YELLOW), Rgb666::new(63, 63, 0));
+    }
+
+    #[test]
+    fn raw_data() {
+        assert_eq!(Rgb111::from(RawU4::new(0b0110)), Rgb111::YELLOW);
+        assert_eq!(Rgb111::from(RawU4::new(0b1001)), Rgb111::BLUE);
+        assert_eq!(RawU4::from(Rgb111::MAGENTA).into_inner(), 0b101);
+
+        // Two pixels per byte, high nibble first like ImageRaw<Rgb111>
+        let pixels = [0x4e_u8].map(|b| [b >> 4, b & 0xf].map(|n| Rgb111::from(RawU4::new(n))));
+        assert_eq!(pixels, [[Rgb111::RED, Rgb111::YELLOW]]);
+    }
+}
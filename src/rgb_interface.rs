@@ -0,0 +1,218 @@
+use display_interface::WriteOnlyDataCommand;
+use embedded_hal::delay::DelayNs;
+
+use crate::dcs::Instruction;
+use crate::{
+    FrameRate, FrameRateClockDivision, Ili9488, Ili9488PixelFormat, ImCtl, Mode, ResetDriver,
+    Result,
+};
+
+/// Display Function Control: the display memory is written through the RGB interface (RM)
+const RAM_ACCESS_RGB: u8 = 0x20;
+
+/// Display Function Control: the display is operated by the RGB interface (DM)
+const DISPLAY_MODE_RGB: u8 = 0x10;
+
+/// Display Function Control: SYNC mode instead of DE mode (RCM)
+const RGB_SYNC_MODE: u8 = 0x40;
+
+/// Display Function Control: source and gate outputs in the non-display area (PTG, PT),
+/// as set by [Ili9488::new]
+const DISPLAY_FUNCTION: [u8; 3] = [0x02, 0x02, 0x3B];
+
+/// 16 bpp over the parallel RGB (DPI) interface
+///
+/// The frames come from the RGB interface, e.g. an LTDC peripheral, the driver only
+/// configures the controller. See [Ili9488::init_for_rgb_interface].
+#[derive(Copy, Clone)]
+pub struct Dpi16Mode;
+impl Ili9488PixelFormat for Dpi16Mode {
+    const DATA: u8 = 0x55;
+    const BPP: u8 = 16;
+}
+impl RgbInterfacePixelFormat for Dpi16Mode {}
+
+/// 18 bpp over the parallel RGB (DPI) interface
+///
+/// See [Dpi16Mode].
+#[derive(Copy, Clone)]
+pub struct Dpi18Mode;
+impl Ili9488PixelFormat for Dpi18Mode {
+    const DATA: u8 = 0x66;
+    const BPP: u8 = 18;
+}
+impl RgbInterfacePixelFormat for Dpi18Mode {}
+
+/// 24 bpp over the parallel RGB (DPI) interface
+///
+/// See [Dpi16Mode].
+#[derive(Copy, Clone)]
+pub struct Dpi24Mode;
+impl Ili9488PixelFormat for Dpi24Mode {
+    const DATA: u8 = 0x76;
+    const BPP: u8 = 24;
+}
+impl RgbInterfacePixelFormat for Dpi24Mode {}
+
+/// Pixel formats of the parallel RGB (DPI) interface
+///
+/// They have no [Ili9488PixelPacking](crate::Ili9488PixelPacking) implementation, so
+/// nothing can be drawn through the command interface.
+pub trait RgbInterfacePixelFormat: Ili9488PixelFormat {}
+
+/// Blanking Porch Control settings, in lines and DOTCLK cycles
+///
+/// Only used in SYNC mode, they have to match the timings of the RGB interface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BlankingPorches {
+    /// Vertical front porch, 2 to 255 lines
+    pub vertical_front: u8,
+    /// Vertical back porch, 2 to 255 lines
+    pub vertical_back: u8,
+    /// Horizontal front porch, 2 to 31 DOTCLK cycles
+    pub horizontal_front: u8,
+    /// Horizontal back porch, 2 to 31 DOTCLK cycles
+    pub horizontal_back: u8,
+}
+
+impl Default for BlankingPorches {
+    /// The reset defaults of the controller
+    fn default() -> Self {
+        Self {
+            vertical_front: 0x02,
+            vertical_back: 0x02,
+            horizontal_front: 0x0a,
+            horizontal_back: 0x14,
+        }
+    }
+}
+
+/// Settings for driving the panel over the parallel RGB (DPI) interface
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RgbInterfaceConfig {
+    /// Polarities of VSYNC, HSYNC, DOTCLK and ENABLE
+    pub signals: ImCtl,
+    /// SYNC mode, where the frame is timed by VSYNC, HSYNC and the porches instead of ENABLE
+    pub sync_mode: bool,
+    /// Porches for SYNC mode
+    pub porches: BlankingPorches,
+    /// Clock division of the internal oscillator
+    pub clock_division: FrameRateClockDivision,
+    /// Frame rate of the internal oscillator
+    pub frame_rate: FrameRate,
+}
+
+impl Default for RgbInterfaceConfig {
+    /// DE mode with the default signal polarities, 60 Hz
+    fn default() -> Self {
+        Self {
+            signals: ImCtl::default(),
+            sync_mode: false,
+            porches: BlankingPorches::default(),
+            clock_division: FrameRateClockDivision::Fosc,
+            frame_rate: FrameRate::FrameRate60,
+        }
+    }
+}
+
+impl<IFACE, RESET, PixelFormat> Ili9488<IFACE, RESET, PixelFormat>
+where
+    IFACE: WriteOnlyDataCommand,
+    RESET: ResetDriver,
+    PixelFormat: RgbInterfacePixelFormat,
+{
+    /// Initialize the display for a panel driven over the parallel RGB (DPI) interface,
+    /// e.g. by an LTDC or LCDC peripheral, with `interface` (usually SPI) only used for
+    /// configuration.
+    ///
+    /// Runs the usual initialization, then hands display memory access and display
+    /// operation over to the RGB interface. Start the RGB interface afterwards. Settings
+    /// like the orientation, inversion or brightness can still be changed, drawing through
+    /// the driver is not available.
+    ///
+    /// ```ignore
+    /// let display = Ili9488::init_for_rgb_interface(
+    ///     spi_interface,
+    ///     reset,
+    ///     &mut delay,
+    ///     Orientation::Portrait,
+    ///     Dpi16Mode,
+    ///     RgbInterfaceConfig::default(),
+    /// )?;
+    /// ltdc.enable();
+    /// ```
+    pub fn init_for_rgb_interface<DELAY, MODE>(
+        interface: IFACE,
+        reset: RESET,
+        delay: &mut DELAY,
+        orientation: MODE,
+        pixel_format: PixelFormat,
+        config: RgbInterfaceConfig,
+    ) -> Result<Self>
+    where
+        DELAY: DelayNs,
+        MODE: Mode,
+    {
+        let mut display = Self::new(interface, reset, delay, orientation, pixel_format)?;
+        display
+            .interface_mode_control(config.signals)?
+            .normal_mode_frame_rate(config.clock_division, config.frame_rate)?;
+        let porches = config.porches;
+        display.command(
+            Instruction::BlankingPorchControl,
+            &[
+                porches.vertical_front,
+                porches.vertical_back,
+                porches.horizontal_front,
+                porches.horizontal_back,
+            ],
+        )?;
+
+        let mut function = DISPLAY_FUNCTION;
+        function[0] |= RAM_ACCESS_RGB | DISPLAY_MODE_RGB;
+        if config.sync_mode {
+            function[0] |= RGB_SYNC_MODE;
+        }
+        display.command(Instruction::DisplayFunctionControl, &function)?;
+        Ok(display)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockDelay, MockInterface, MockPin};
+    use crate::Orientation;
+    use std::vec;
+
+    #[test]
+    fn init_for_rgb_interface() {
+        let config = RgbInterfaceConfig {
+            signals: ImCtl {
+                dotclk_falling_edge: true,
+                ..ImCtl::default()
+            },
+            sync_mode: true,
+            ..RgbInterfaceConfig::default()
+        };
+        let display = Ili9488::init_for_rgb_interface(
+            MockInterface::new(),
+            MockPin::default(),
+            &mut MockDelay::default(),
+            Orientation::Portrait,
+            Dpi16Mode,
+            config,
+        )
+        .unwrap();
+
+        let commands = display.interface().commands();
+        assert!(commands.contains(&(0x3a, vec![0x55])));
+        let rgb_setup = &commands[commands.len() - 4..];
+        assert_eq!(rgb_setup[0], (0xb0, vec![0x02]));
+        assert_eq!(rgb_setup[1], (0xb1, vec![0xa0, 0x11]));
+        assert_eq!(rgb_setup[2], (0xb5, vec![0x02, 0x02, 0x0a, 0x14]));
+        assert_eq!(rgb_setup[3], (0xb6, vec![0x72, 0x02, 0x3b]));
+    }
+}
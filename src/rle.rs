@@ -0,0 +1,170 @@
+//! Run-length encoded images, for splash screens and icons stored in flash
+//!
+//! An image starts with its width and height as little endian `u16`s, followed by packets.
+//! Every packet starts with a header byte `n`:
+//!
+//! - `n & 0x80 != 0`: the next pixel is repeated `(n & 0x7f) + 1` times
+//! - otherwise: the next `n + 1` pixels are stored as is
+//!
+//! Pixels are [Rgb565], big endian. Images are drawn with [crate::Ili9488::draw_rle_image] and
+//! created with [encode].
+#[cfg(any(test, feature = "std"))]
+use embedded_graphics_core::pixelcolor::IntoStorage;
+#[cfg(any(test, feature = "std"))]
+use std::vec::Vec;
+
+use display_interface::{DisplayError, WriteOnlyDataCommand};
+use embedded_graphics_core::pixelcolor::raw::RawU16;
+use embedded_graphics_core::pixelcolor::Rgb565;
+use embedded_graphics_core::prelude::{Point, Size};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::{Ili9488, Ili9488MemoryWrite, Ili9488PixelFormat, Result};
+
+/// Longest run or literal packet
+#[cfg(any(test, feature = "std"))]
+const MAX_PACKET: usize = 128;
+
+/// Iterator over the pixels of an RLE image
+pub struct RleDecoder<'a> {
+    data: &'a [u8],
+    repeat: Option<Rgb565>,
+    remaining: usize,
+}
+
+impl<'a> RleDecoder<'a> {
+    /// Read the header of `image`, returns the image size and a decoder for its pixels
+    pub fn new(image: &'a [u8]) -> Option<(Size, Self)> {
+        let (header, data) = image.split_first_chunk::<4>()?;
+        let width = u16::from_le_bytes([header[0], header[1]]);
+        let height = u16::from_le_bytes([header[2], header[3]]);
+        let decoder = Self {
+            data,
+            repeat: None,
+            remaining: 0,
+        };
+        Some((Size::new(width.into(), height.into()), decoder))
+    }
+
+    fn next_pixel(&mut self) -> Option<Rgb565> {
+        let (pixel, data) = self.data.split_first_chunk::<2>()?;
+        self.data = data;
+        Some(RawU16::new(u16::from_be_bytes(*pixel)).into())
+    }
+}
+
+impl Iterator for RleDecoder<'_> {
+    type Item = Rgb565;
+
+    fn next(&mut self) -> Option<Rgb565> {
+        if self.remaining == 0 {
+            let (&header, data) = self.data.split_first()?;
+            self.data = data;
+            self.remaining = (header & 0x7f) as usize + 1;
+            self.repeat = if header & 0x80 != 0 {
+                Some(self.next_pixel()?)
+            } else {
+                None
+            };
+        }
+        self.remaining -= 1;
+        match self.repeat {
+            Some(pixel) => Some(pixel),
+            None => self.next_pixel(),
+        }
+    }
+}
+
+/// Encode `pixels`, in rows of `width`, as an RLE image
+#[cfg(any(test, feature = "std"))]
+pub fn encode(width: u16, pixels: &[Rgb565]) -> Vec<u8> {
+    let height = (pixels.len() / width.max(1) as usize) as u16;
+    let mut out = Vec::new();
+    out.extend_from_slice(&width.to_le_bytes());
+    out.extend_from_slice(&height.to_le_bytes());
+
+    let pixels = &pixels[..width as usize * height as usize];
+    let mut literal_start = 0;
+    let mut i = 0;
+    while i < pixels.len() {
+        let run = pixels[i..]
+            .iter()
+            .take(MAX_PACKET)
+            .take_while(|&&p| p == pixels[i])
+            .count();
+        // A run of 2 pixels takes as many bytes as a literal, only use runs from 3 pixels
+        if run < 3 {
+            i += 1;
+            continue;
+        }
+        push_literals(&mut out, &pixels[literal_start..i]);
+        out.push(0x80 | (run - 1) as u8);
+        out.extend_from_slice(&pixels[i].into_storage().to_be_bytes());
+        i += run;
+        literal_start = i;
+    }
+    push_literals(&mut out, &pixels[literal_start..]);
+    out
+}
+
+#[cfg(any(test, feature = "std"))]
+fn push_literals(out: &mut Vec<u8>, pixels: &[Rgb565]) {
+    for chunk in pixels.chunks(MAX_PACKET) {
+        out.push((chunk.len() - 1) as u8);
+        for pixel in chunk {
+            out.extend_from_slice(&pixel.into_storage().to_be_bytes());
+        }
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+where
+    Self: Ili9488MemoryWrite,
+    <Self as Ili9488MemoryWrite>::PixelFormat: From<Rgb565>,
+    IFACE: WriteOnlyDataCommand,
+    PixelFormat: Ili9488PixelFormat,
+{
+    /// Draw an RLE image, see [crate::rle], with its top left corner at (x, y).
+    ///
+    /// The image is decompressed while it is written, in a single window.
+    pub fn draw_rle_image(&mut self, x: u16, y: u16, image: &[u8]) -> Result {
+        let (size, pixels) = RleDecoder::new(image).ok_or(DisplayError::OutOfBoundsError)?;
+        let region = Rectangle::new(Point::new(x.into(), y.into()), size);
+        self.set_region_window(&region)?;
+        self.write_iter(pixels.map(Into::into))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim;
+    use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+    use std::vec;
+
+    #[test]
+    fn encode_round_trip() {
+        let mut pixels = vec![Rgb565::RED; 300];
+        pixels.extend([Rgb565::BLUE, Rgb565::GREEN, Rgb565::GREEN, Rgb565::WHITE]);
+        let image = encode(8, &pixels);
+        // Header, three runs of red, then the literals
+        assert_eq!(image.len(), 4 + 3 * 3 + 1 + 4 * 2);
+
+        let (size, decoder) = RleDecoder::new(&image).unwrap();
+        assert_eq!(size, Size::new(8, 38));
+        assert!(decoder.eq(pixels));
+    }
+
+    #[test]
+    fn draw_rle_image() {
+        let mut display = sim::display();
+        let image = encode(2, &[Rgb565::RED, Rgb565::RED, Rgb565::RED, Rgb565::BLUE]);
+        display.draw_rle_image(5, 6, &image).unwrap();
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(6, 6), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(6, 7), Some(Rgb888::BLUE));
+        assert!(display.draw_rle_image(319, 0, &image).is_err());
+    }
+}
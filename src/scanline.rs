@@ -0,0 +1,150 @@
+use core::convert::Infallible;
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_graphics_core::pixelcolor::Rgb666;
+use embedded_graphics_core::prelude::{Dimensions, DrawTarget, Pixel, Point, RgbColor, Size};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::dcs::WriteMemoryStart;
+use crate::{Ili9488, Result, Rgb666Mode};
+
+/// Renders full frames one horizontal band at a time, for devices without the RAM for a
+/// frame buffer.
+///
+/// The frame is drawn by a closure that is called once per band with a [Band] draw target
+/// covering only that band's rows. Whatever falls outside of the band is dropped, so the
+/// closure simply draws the whole frame every time. The band is sent to the display once the
+/// closure returns.
+///
+/// ```ignore
+/// // 16 rows of 480 pixels
+/// let mut buf = [0; 480 * 16 * 3];
+/// ScanlineRenderer::new(&mut buf).render(&mut display, |band| {
+///     band.clear(Rgb666::BLACK)?;
+///     Text::new("Hello", Point::new(10, 100), style).draw(band)?;
+///     Ok(())
+/// })?;
+/// ```
+pub struct ScanlineRenderer<'a> {
+    buf: &'a mut [u8],
+}
+
+impl<'a> ScanlineRenderer<'a> {
+    /// Render with bands of as many rows as fit in `buf`, at 3 bytes per pixel
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf }
+    }
+
+    /// Render a frame to `display`, calling `draw` for every band top to bottom
+    pub fn render<IFACE, RESET, const BURST: usize, DELAY, F>(
+        &mut self,
+        display: &mut Ili9488<IFACE, RESET, Rgb666Mode, BURST, DELAY>,
+        mut draw: F,
+    ) -> Result
+    where
+        IFACE: WriteOnlyDataCommand,
+        F: FnMut(&mut Band<'_>) -> core::result::Result<(), Infallible>,
+    {
+        let (width, height) = (display.width(), display.height());
+        let rows = (self.buf.len() / (width * 3)).min(height);
+        if rows == 0 {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        for top in (0..height).step_by(rows) {
+            let rows = rows.min(height - top);
+            let mut band = Band {
+                buf: &mut self.buf[..width * rows * 3],
+                area: Rectangle::new(
+                    Point::new(0, top as i32),
+                    Size::new(width as u32, rows as u32),
+                ),
+            };
+            let Ok(()) = draw(&mut band);
+
+            display.set_window(0, top as u16, width as u16 - 1, (top + rows - 1) as u16)?;
+            display.write_command(WriteMemoryStart)?;
+            display.interface.send_data(DataFormat::U8(band.buf))?;
+        }
+        Ok(())
+    }
+}
+
+/// Draw target for one band of a [ScanlineRenderer], in screen coordinates
+pub struct Band<'a> {
+    buf: &'a mut [u8],
+    area: Rectangle,
+}
+
+impl Dimensions for Band<'_> {
+    fn bounding_box(&self) -> Rectangle {
+        self.area
+    }
+}
+
+impl DrawTarget for Band<'_> {
+    type Color = Rgb666;
+
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> core::result::Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let width = self.area.size.width as usize;
+        for Pixel(point, color) in pixels {
+            if self.area.contains(point) {
+                let y = (point.y - self.area.top_left.y) as usize;
+                let i = (y * width + point.x as usize) * 3;
+                self.buf[i..i + 3].copy_from_slice(&[
+                    color.r() << 2,
+                    color.g() << 2,
+                    color.b() << 2,
+                ]);
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> core::result::Result<(), Self::Error> {
+        for pixel in self.buf.chunks_exact_mut(3) {
+            pixel.copy_from_slice(&[color.r() << 2, color.g() << 2, color.b() << 2]);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sim;
+    use crate::Orientation;
+    use embedded_graphics_core::pixelcolor::Rgb888;
+    use std::vec;
+
+    #[test]
+    fn renders_in_bands() {
+        let mut display = sim::display_with(Orientation::Landscape, Rgb666Mode);
+
+        // 7 rows per band, 320 rows do not divide evenly
+        let mut buf = vec![0; 480 * 7 * 3 + 5];
+        let mut bands = 0;
+        ScanlineRenderer::new(&mut buf)
+            .render(&mut display, |band| {
+                bands += 1;
+                band.clear(Rgb666::BLUE)?;
+                band.fill_solid(
+                    &Rectangle::new(Point::new(100, 5), Size::new(10, 300)),
+                    Rgb666::RED,
+                )
+            })
+            .unwrap();
+        assert_eq!(bands, 46);
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(0, 319), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(100, 5), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(109, 304), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(109, 305), Some(Rgb888::BLUE));
+    }
+}
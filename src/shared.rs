@@ -0,0 +1,222 @@
+//! Drawing to one display from several tasks or interrupt handlers
+//!
+//! [SharedIli9488] keeps the display behind a [critical_section::Mutex], so it can live in a
+//! `static` and be used through `&` references. Each part of a split-screen UI gets its own
+//! [SharedRegion], a [DrawTarget] with its own origin that cannot draw outside of its area.
+//! Every draw call takes the lock for its whole memory write, so the windows of different
+//! regions never interleave.
+//!
+//! ```ignore
+//! static DISPLAY: StaticCell<SharedIli9488<...>> = StaticCell::new();
+//! let shared = DISPLAY.init(SharedIli9488::new(display));
+//!
+//! let status_bar = shared.region(Rectangle::new(Point::zero(), Size::new(480, 24)));
+//! let plot = shared.region(Rectangle::new(Point::new(0, 24), Size::new(480, 296)));
+//! spawner.spawn(status_task(status_bar))?;
+//! spawner.spawn(plot_task(plot))?;
+//! ```
+//!
+//! Interrupts stay disabled while a draw call runs, keep draw calls small where interrupt
+//! latency matters, e.g. by drawing a few rows at a time.
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+use display_interface::DisplayError;
+use embedded_graphics_core::prelude::{Dimensions, DrawTarget, Pixel, Point, PointsIter};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::{Ili9488, NoDelay};
+
+/// An [Ili9488] shared between tasks, see [crate::shared]
+pub struct SharedIli9488<IFACE, RESET, PixelFormat, const BURST: usize = 64, DELAY = NoDelay> {
+    display: Mutex<RefCell<Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>>>,
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    SharedIli9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+{
+    pub const fn new(display: Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>) -> Self {
+        Self {
+            display: Mutex::new(RefCell::new(display)),
+        }
+    }
+
+    /// Run `f` with exclusive access to the display, e.g. to change its orientation or
+    /// brightness.
+    ///
+    /// # Panics
+    ///
+    /// When called again from within `f`.
+    pub fn lock<R>(
+        &self,
+        f: impl FnOnce(&mut Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>) -> R,
+    ) -> R {
+        critical_section::with(|cs| f(&mut self.display.borrow_ref_mut(cs)))
+    }
+
+    /// Give back the display
+    pub fn into_inner(self) -> Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY> {
+        self.display.into_inner().into_inner()
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    SharedIli9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+where
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>: DrawTarget,
+{
+    /// A [DrawTarget] for the part of the screen covered by `area`, with `area.top_left` as its
+    /// origin. The area is cut to the screen.
+    pub fn region(
+        &self,
+        area: Rectangle,
+    ) -> SharedRegion<'_, IFACE, RESET, PixelFormat, BURST, DELAY> {
+        let area = area.intersection(&self.lock(|display| display.bounding_box()));
+        SharedRegion { shared: self, area }
+    }
+}
+
+/// Part of a [SharedIli9488], obtained with [SharedIli9488::region]
+///
+/// Drawing is relative to the top left corner of the area and cut to it.
+pub struct SharedRegion<'a, IFACE, RESET, PixelFormat, const BURST: usize = 64, DELAY = NoDelay> {
+    shared: &'a SharedIli9488<IFACE, RESET, PixelFormat, BURST, DELAY>,
+    area: Rectangle,
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    SharedRegion<'_, IFACE, RESET, PixelFormat, BURST, DELAY>
+{
+    /// The area on the screen
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    /// `area` on the screen, cut to the region
+    fn to_screen(&self, area: &Rectangle) -> Rectangle {
+        Rectangle::new(area.top_left + self.area.top_left, area.size).intersection(&self.area)
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY> Clone
+    for SharedRegion<'_, IFACE, RESET, PixelFormat, BURST, DELAY>
+{
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared,
+            area: self.area,
+        }
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY> Dimensions
+    for SharedRegion<'_, IFACE, RESET, PixelFormat, BURST, DELAY>
+{
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(Point::zero(), self.area.size)
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY> DrawTarget
+    for SharedRegion<'_, IFACE, RESET, PixelFormat, BURST, DELAY>
+where
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>: DrawTarget<Error = DisplayError>,
+{
+    type Color = <Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY> as DrawTarget>::Color;
+
+    type Error = DisplayError;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let area = self.area;
+        let pixels = pixels
+            .into_iter()
+            .map(|Pixel(point, color)| Pixel(point + area.top_left, color))
+            .filter(|Pixel(point, _)| area.contains(*point));
+        self.shared.lock(|display| display.draw_iter(pixels))
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let visible = self.to_screen(area);
+        if visible.is_zero_sized() {
+            return Ok(());
+        }
+        let area = Rectangle::new(area.top_left + self.area.top_left, area.size);
+        let colors = area
+            .points()
+            .zip(colors)
+            .filter(|(point, _)| visible.contains(*point))
+            .map(|(_, color)| color);
+        self.shared
+            .lock(|display| display.fill_contiguous(&visible, colors))
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let visible = self.to_screen(area);
+        self.shared
+            .lock(|display| display.fill_solid(&visible, color))
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let area = self.area;
+        self.shared.lock(|display| display.fill_solid(&area, color))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sim;
+    use crate::{Orientation, Rgb666Mode};
+    use embedded_graphics_core::pixelcolor::{Rgb666, Rgb888, RgbColor};
+    use embedded_graphics_core::prelude::Size;
+
+    #[test]
+    fn regions_draw_in_their_area() {
+        let display = sim::display_with(Orientation::Landscape, Rgb666Mode);
+        let shared = SharedIli9488::new(display);
+        let mut top = shared.region(Rectangle::new(Point::zero(), Size::new(480, 24)));
+        // Cut to the screen
+        let mut bottom = shared.region(Rectangle::new(Point::new(470, 24), Size::new(20, 20)));
+        assert_eq!(bottom.bounding_box().size, Size::new(10, 20));
+
+        top.clear(Rgb666::BLUE).unwrap();
+        bottom
+            .draw_iter([
+                Pixel(Point::new(1, 2), Rgb666::RED),
+                Pixel(Point::new(-1, 2), Rgb666::RED),
+                Pixel(Point::new(10, 2), Rgb666::RED),
+            ])
+            .unwrap();
+        // Hangs off the left edge of the region
+        bottom
+            .fill_contiguous(
+                &Rectangle::new(Point::new(-1, 5), Size::new(2, 2)),
+                [Rgb666::RED, Rgb666::GREEN, Rgb666::WHITE, Rgb666::YELLOW],
+            )
+            .unwrap();
+        top.fill_solid(
+            &Rectangle::new(Point::new(0, 20), Size::new(2, 10)),
+            Rgb666::WHITE,
+        )
+        .unwrap();
+
+        let display = shared.into_inner();
+        let sim = display.interface();
+        assert_eq!(sim.pixel(0, 0), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(479, 23), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(471, 26), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(469, 26), Some(Rgb888::BLACK));
+        assert_eq!(sim.pixel(470, 29), Some(Rgb888::GREEN));
+        assert_eq!(sim.pixel(470, 30), Some(Rgb888::YELLOW));
+        assert_eq!(sim.pixel(469, 29), Some(Rgb888::BLACK));
+        assert_eq!(sim.pixel(1, 23), Some(Rgb888::WHITE));
+        assert_eq!(sim.pixel(1, 24), Some(Rgb888::BLACK));
+    }
+}
@@ -0,0 +1,89 @@
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+/// Display interface adapter for modules that put the ILI9488's 16-bit parallel bus behind
+/// a shift register fed over SPI, like the Waveshare 3.5" RPi LCD (C).
+///
+/// The shift register latches 16 bits at a time, so every command and parameter byte is sent
+/// as a 16-bit word with a zero high byte, while pixel data, which the driver already sends
+/// as words, is passed on unchanged. Use it with [Rgb565Mode](crate::Rgb565Mode), the only
+/// pixel format that sends one pixel per word.
+///
+/// ```ignore
+/// let interface = ShiftRegisterInterface::new(SPIInterface::new(spi, dc));
+/// let display = Ili9488::new_with_profile(
+///     Profile::Waveshare35,
+///     interface,
+///     reset,
+///     &mut delay,
+///     Orientation::Landscape,
+///     Rgb565Mode,
+/// )?;
+/// ```
+pub struct ShiftRegisterInterface<DI> {
+    interface: DI,
+}
+
+impl<DI: WriteOnlyDataCommand> ShiftRegisterInterface<DI> {
+    pub fn new(interface: DI) -> Self {
+        Self { interface }
+    }
+
+    /// Give back the wrapped interface
+    pub fn release(self) -> DI {
+        self.interface
+    }
+
+    /// Send `format` with every byte widened to a word
+    fn widened(
+        format: DataFormat<'_>,
+        send: impl FnOnce(DataFormat<'_>) -> Result<(), DisplayError>,
+    ) -> Result<(), DisplayError> {
+        match format {
+            DataFormat::U8(bytes) => send(DataFormat::U16BEIter(
+                &mut bytes.iter().map(|&byte| u16::from(byte)),
+            )),
+            DataFormat::U8Iter(bytes) => send(DataFormat::U16BEIter(&mut bytes.map(u16::from))),
+            words => send(words),
+        }
+    }
+}
+
+impl<DI: WriteOnlyDataCommand> WriteOnlyDataCommand for ShiftRegisterInterface<DI> {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        Self::widened(cmd, |cmd| self.interface.send_commands(cmd))
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        Self::widened(buf, |buf| self.interface.send_data(buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, MockInterface, Transaction};
+    use crate::{Orientation, Rgb565Mode};
+    use embedded_graphics_core::pixelcolor::{Rgb565, RgbColor};
+    use std::vec;
+
+    #[test]
+    fn widens_bytes_to_words() {
+        let mut display = mock::display_on(
+            ShiftRegisterInterface::new(MockInterface::new()),
+            Orientation::Portrait,
+            Rgb565Mode,
+        );
+        display.interface.interface.clear();
+        display.draw_raw_slice(1, 2, 1, 2, &[Rgb565::RED]).unwrap();
+
+        assert_eq!(
+            display.release().0.release().transactions()[..4],
+            [
+                Transaction::Command(vec![0x00, 0x2a]),
+                Transaction::Data(vec![0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01]),
+                Transaction::Command(vec![0x00, 0x2b]),
+                Transaction::Data(vec![0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,323 @@
+//! Simulated ILI9488 panel for developing and screenshot-testing UIs on a desktop
+//!
+//! [SimulatedIli9488] is a display interface that decodes the command and data stream sent
+//! by the driver into an in-memory RGB888 frame buffer, honouring the address window,
+//! memory access control and pixel format commands like the real controller.
+//!
+//! ```ignore
+//! let mut display = Ili9488::new(
+//!     SimulatedIli9488::new(),
+//!     MockPin::default(),
+//!     &mut MockDelay::default(),
+//!     Orientation::Landscape,
+//!     Rgb666Mode,
+//! )?;
+//! // ... draw to `display` ...
+//!
+//! let mut window_display = SimulatorDisplay::<Rgb888>::new(Size::new(480, 320));
+//! display.interface().draw(&mut window_display)?;
+//! ```
+use std::vec;
+use std::vec::Vec;
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+use embedded_graphics_core::prelude::{DrawTarget, OriginDimensions, Point, Size};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::mock::to_bytes;
+#[cfg(test)]
+use crate::mock::{self, MockPin};
+use crate::{DisplaySize, DisplaySize320x480, ReadDataCommand};
+#[cfg(test)]
+use crate::{Ili9488, Ili9488PixelFormat, Orientation, Rgb666Mode};
+
+const WIDTH: usize = DisplaySize320x480::WIDTH;
+const HEIGHT: usize = DisplaySize320x480::HEIGHT;
+
+/// A display on a [SimulatedIli9488], initialized in portrait with [Rgb666Mode]
+#[cfg(test)]
+pub(crate) fn display() -> Ili9488<SimulatedIli9488, MockPin, Rgb666Mode> {
+    display_with(Orientation::Portrait, Rgb666Mode)
+}
+
+/// A display on a [SimulatedIli9488], initialized with `orientation` and `pixel_format`
+#[cfg(test)]
+pub(crate) fn display_with<P: Ili9488PixelFormat>(
+    orientation: Orientation,
+    pixel_format: P,
+) -> Ili9488<SimulatedIli9488, MockPin, P> {
+    mock::display_on(SimulatedIli9488::new(), orientation, pixel_format)
+}
+
+/// A display interface that renders into an RGB888 frame buffer instead of a panel.
+///
+/// Pixels are read back in the coordinates of the orientation that is currently set,
+/// so a screenshot looks like what was drawn.
+pub struct SimulatedIli9488 {
+    frame: Vec<Rgb888>,
+    command: u8,
+    params: Vec<u8>,
+    madctl: u8,
+    pixel_format: u8,
+    columns: (u16, u16),
+    pages: (u16, u16),
+    cursor: (u16, u16),
+    partial: Vec<u8>,
+}
+
+impl Default for SimulatedIli9488 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulatedIli9488 {
+    pub fn new() -> Self {
+        Self {
+            frame: vec![Rgb888::BLACK; WIDTH * HEIGHT],
+            command: 0,
+            params: Vec::new(),
+            madctl: 0,
+            pixel_format: 0x66,
+            columns: (0, WIDTH as u16 - 1),
+            pages: (0, HEIGHT as u16 - 1),
+            cursor: (0, 0),
+            partial: Vec::new(),
+        }
+    }
+
+    fn exchanged(&self) -> bool {
+        self.madctl & 0x20 != 0
+    }
+
+    /// Map logical coordinates in the current orientation to an index in the frame buffer
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        let (mut column, mut row) = if self.exchanged() { (y, x) } else { (x, y) };
+        if column >= WIDTH || row >= HEIGHT {
+            return None;
+        }
+        if self.madctl & 0x40 != 0 {
+            column = WIDTH - 1 - column;
+        }
+        if self.madctl & 0x80 != 0 {
+            row = HEIGHT - 1 - row;
+        }
+        Some(row * WIDTH + column)
+    }
+
+    /// Color of the pixel at (x, y) in the current orientation
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Rgb888> {
+        self.index(x, y).map(|i| self.frame[i])
+    }
+
+    /// Draw the frame buffer, as seen in the current orientation, to any RGB888 draw target,
+    /// e.g. an `embedded-graphics-simulator` `SimulatorDisplay`
+    pub fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb888>,
+    {
+        let size = self.size();
+        target.fill_contiguous(
+            &Rectangle::new(Point::zero(), size),
+            (0..size.height as usize).flat_map(|y| {
+                (0..size.width as usize).map(move |x| self.pixel(x, y).unwrap_or(Rgb888::BLACK))
+            }),
+        )
+    }
+
+    fn write_pixel(&mut self, color: Rgb888) {
+        let (x, y) = self.cursor;
+        if let Some(i) = self.index(x as usize, y as usize) {
+            self.frame[i] = color;
+        }
+        self.advance();
+    }
+
+    fn read_pixel(&mut self) -> Rgb888 {
+        let (x, y) = self.cursor;
+        let color = self.pixel(x as usize, y as usize).unwrap_or(Rgb888::BLACK);
+        self.advance();
+        color
+    }
+
+    /// Move the memory cursor to the next pixel in the window
+    fn advance(&mut self) {
+        let (x, y) = self.cursor;
+        self.cursor = if x < self.columns.1 {
+            (x + 1, y)
+        } else if y < self.pages.1 {
+            (self.columns.0, y + 1)
+        } else {
+            (self.columns.0, self.pages.0)
+        };
+    }
+
+    fn memory_write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            match self.pixel_format {
+                0x01 => {
+                    self.write_pixel(rgb111(byte >> 3));
+                    self.write_pixel(rgb111(byte));
+                }
+                0x55 => {
+                    self.partial.push(byte);
+                    if let [high, low] = self.partial[..] {
+                        let raw = u16::from_be_bytes([high, low]);
+                        self.partial.clear();
+                        self.write_pixel(Rgb888::new(
+                            expand((raw >> 11) as u8, 5),
+                            expand((raw >> 5) as u8, 6),
+                            expand(raw as u8, 5),
+                        ));
+                    }
+                }
+                _ => {
+                    self.partial.push(byte);
+                    if let [r, g, b] = self.partial[..] {
+                        self.partial.clear();
+                        self.write_pixel(Rgb888::new(
+                            expand(r >> 2, 6),
+                            expand(g >> 2, 6),
+                            expand(b >> 2, 6),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    fn parameters(&mut self, bytes: &[u8]) {
+        self.params.extend_from_slice(bytes);
+        let range = |p: &[u8]| {
+            (
+                u16::from_be_bytes([p[0], p[1]]),
+                u16::from_be_bytes([p[2], p[3]]),
+            )
+        };
+        match (self.command, self.params.len()) {
+            (0x2a, 4) => self.columns = range(&self.params),
+            (0x2b, 4) => self.pages = range(&self.params),
+            (0x36, 1) => self.madctl = self.params[0],
+            (0x3a, 1) => self.pixel_format = self.params[0] & 0x77,
+            _ => {}
+        }
+    }
+}
+
+/// Expand a color channel of `bits` bits to 8 bits
+fn expand(value: u8, bits: u32) -> u8 {
+    let value = value & ((1 << bits) - 1) as u8;
+    (value << (8 - bits)) | (value >> (2 * bits - 8))
+}
+
+fn rgb111(bits: u8) -> Rgb888 {
+    let channel = |bit: u8| if bits & bit != 0 { 0xff } else { 0x00 };
+    Rgb888::new(channel(0b100), channel(0b010), channel(0b001))
+}
+
+impl WriteOnlyDataCommand for SimulatedIli9488 {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        for command in to_bytes(cmd)? {
+            self.command = command;
+            self.params.clear();
+            self.partial.clear();
+            match command {
+                // MemoryWrite starts over at the top left corner of the window
+                0x2c => self.cursor = (self.columns.0, self.pages.0),
+                // SoftwareReset
+                0x01 => *self = Self::new(),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        let bytes = to_bytes(buf)?;
+        match self.command {
+            // MemoryWrite and MemoryWriteContinue
+            0x2c | 0x3c => self.memory_write(&bytes),
+            _ => self.parameters(&bytes),
+        }
+        Ok(())
+    }
+}
+
+impl ReadDataCommand for SimulatedIli9488 {
+    /// Memory reads answer with a dummy byte followed by 18-bit pixels, one byte per channel
+    fn read_data(&mut self, command: u8, buf: &mut [u8]) -> Result<(), DisplayError> {
+        self.send_commands(DataFormat::U8(&[command]))?;
+        buf.fill(0);
+        match command {
+            // MemoryRead starts over at the top left corner of the window
+            0x2e => self.cursor = (self.columns.0, self.pages.0),
+            // MemoryReadContinue
+            0x3e => {}
+            _ => return Ok(()),
+        }
+        if let Some((_, pixels)) = buf.split_first_mut() {
+            for chunk in pixels.chunks_mut(3) {
+                let color = self.read_pixel();
+                let channels = [color.r(), color.g(), color.b()].map(|c| c & 0xfc);
+                chunk.copy_from_slice(&channels[..chunk.len()]);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl OriginDimensions for SimulatedIli9488 {
+    fn size(&self) -> Size {
+        if self.exchanged() {
+            Size::new(HEIGHT as u32, WIDTH as u32)
+        } else {
+            Size::new(WIDTH as u32, HEIGHT as u32)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Orientation, Rgb111, Rgb111Mode, Rgb565Mode, Rgb666Mode};
+    use embedded_graphics_core::pixelcolor::{Rgb565, Rgb666};
+
+    #[test]
+    fn draws_in_every_orientation() {
+        for orientation in [
+            Orientation::Portrait,
+            Orientation::PortraitFlipped,
+            Orientation::Landscape,
+            Orientation::LandscapeFlipped,
+        ] {
+            let mut display = display_with(orientation, Rgb666Mode);
+            display
+                .draw_raw_slice(10, 20, 11, 20, &[Rgb666::RED, Rgb666::BLUE])
+                .unwrap();
+
+            let sim = display.interface();
+            assert_eq!(sim.size().width as usize, display.width());
+            assert_eq!(sim.pixel(10, 20), Some(Rgb888::RED));
+            assert_eq!(sim.pixel(11, 20), Some(Rgb888::BLUE));
+            assert_eq!(sim.pixel(12, 20), Some(Rgb888::BLACK));
+        }
+    }
+
+    #[test]
+    fn decodes_pixel_formats() {
+        let mut display = display_with(Orientation::Portrait, Rgb565Mode);
+        display
+            .draw_raw_slice(0, 0, 0, 0, &[Rgb565::new(31, 0, 31)])
+            .unwrap();
+        assert_eq!(display.interface().pixel(0, 0), Some(Rgb888::MAGENTA));
+
+        let mut display = display.change_pixel_format(Rgb111Mode).unwrap();
+        display
+            .draw_raw_slice(0, 1, 1, 1, &[Rgb111::YELLOW, Rgb111::CYAN])
+            .unwrap();
+        assert_eq!(display.interface().pixel(0, 1), Some(Rgb888::YELLOW));
+        assert_eq!(display.interface().pixel(1, 1), Some(Rgb888::CYAN));
+    }
+}
@@ -0,0 +1,174 @@
+use embedded_graphics_core::prelude::{Dimensions, DrawTarget, Pixel, Point, PointsIter, Size};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::{Ili9488, Rotation};
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+where
+    Self: DrawTarget,
+{
+    /// Get a [DrawTarget] that rotates the picture clockwise by `rotation` in software,
+    /// relative to the current orientation.
+    ///
+    /// Some clone panels show mirrored or garbled pictures in some memory access control
+    /// (MADCTL) rotations. Keep the display in an orientation that works and rotate with
+    /// this instead, every orientation is available then.
+    ///
+    /// Coordinates and areas are transformed, rows of [DrawTarget::fill_contiguous] are
+    /// streamed in the order of the panel, which needs more windows than a hardware rotation.
+    ///
+    /// ```ignore
+    /// let mut display = Ili9488::new(iface, reset, &mut delay, Orientation::Portrait, Rgb666Mode)?;
+    /// let mut landscape = display.software_rotation(Rotation::Deg90);
+    /// Text::new("Hello", Point::new(10, 20), style).draw(&mut landscape)?;
+    /// ```
+    pub fn software_rotation(&mut self, rotation: Rotation) -> SoftwareRotation<'_, Self> {
+        SoftwareRotation {
+            target: self,
+            rotation,
+        }
+    }
+}
+
+/// [DrawTarget] adapter rotating the picture of the wrapped target in software,
+/// obtained with [Ili9488::software_rotation]
+pub struct SoftwareRotation<'a, D> {
+    target: &'a mut D,
+    rotation: Rotation,
+}
+
+/// The point of a target of `size` that shows `point` when rotated by `rotation`
+fn transform(rotation: Rotation, size: Size, point: Point) -> Point {
+    let (right, bottom) = (size.width as i32 - 1, size.height as i32 - 1);
+    match rotation {
+        Rotation::Deg0 => point,
+        Rotation::Deg90 => Point::new(right - point.y, point.x),
+        Rotation::Deg180 => Point::new(right - point.x, bottom - point.y),
+        Rotation::Deg270 => Point::new(point.y, bottom - point.x),
+    }
+}
+
+impl<D: DrawTarget> Dimensions for SoftwareRotation<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        let size = self.target.bounding_box().size;
+        match self.rotation {
+            Rotation::Deg0 | Rotation::Deg180 => Rectangle::new(Point::zero(), size),
+            Rotation::Deg90 | Rotation::Deg270 => {
+                Rectangle::new(Point::zero(), Size::new(size.height, size.width))
+            }
+        }
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for SoftwareRotation<'_, D> {
+    type Color = D::Color;
+
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let (bounding_box, rotation) = (self.bounding_box(), self.rotation);
+        let size = self.target.bounding_box().size;
+        let pixels = pixels
+            .into_iter()
+            .filter(|Pixel(point, _)| bounding_box.contains(*point))
+            .map(|Pixel(point, color)| Pixel(transform(rotation, size, point), color));
+        self.target.draw_iter(pixels)
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        // The target batches the pixels of every row, which are a line on the panel,
+        // into windows
+        let pixels = area
+            .points()
+            .zip(colors)
+            .map(|(point, color)| Pixel(point, color));
+        self.draw_iter(pixels)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+        let Some(bottom_right) = area.bottom_right() else {
+            return Ok(());
+        };
+        let size = self.target.bounding_box().size;
+        let area = Rectangle::with_corners(
+            transform(self.rotation, size, area.top_left),
+            transform(self.rotation, size, bottom_right),
+        );
+        self.target.fill_solid(&area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.target.clear(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::sim;
+    use crate::Rotation;
+    use embedded_graphics_core::pixelcolor::{Rgb666, Rgb888, RgbColor};
+    use embedded_graphics_core::prelude::{Dimensions, DrawTarget, Pixel, Point, Size};
+    use embedded_graphics_core::primitives::Rectangle;
+
+    #[test]
+    fn rotated_by_90() {
+        let mut display = sim::display();
+        let mut target = display.software_rotation(Rotation::Deg90);
+        assert_eq!(target.bounding_box().size, Size::new(480, 320));
+
+        let area = Rectangle::new(Point::zero(), Size::new(2, 2));
+        let colors = [Rgb666::RED, Rgb666::GREEN, Rgb666::BLUE, Rgb666::WHITE];
+        target.fill_contiguous(&area, colors).unwrap();
+        target
+            .fill_solid(
+                &Rectangle::new(Point::new(470, 310), Size::new(20, 20)),
+                Rgb666::CYAN,
+            )
+            .unwrap();
+        target
+            .draw_iter([Pixel(Point::new(480, 0), Rgb666::YELLOW)])
+            .unwrap();
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(319, 0), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(319, 1), Some(Rgb888::GREEN));
+        assert_eq!(sim.pixel(318, 0), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(318, 1), Some(Rgb888::WHITE));
+        assert_eq!(sim.pixel(0, 470), Some(Rgb888::CYAN));
+        assert_eq!(sim.pixel(9, 479), Some(Rgb888::CYAN));
+        assert_eq!(sim.pixel(10, 479), Some(Rgb888::BLACK));
+        // The yellow pixel was off screen, right of (479, 0)
+        assert_eq!(sim.pixel(319, 479), Some(Rgb888::BLACK));
+    }
+
+    #[test]
+    fn rotated_by_180_and_270() {
+        let mut display = sim::display();
+        display
+            .software_rotation(Rotation::Deg180)
+            .draw_iter([Pixel(Point::new(0, 0), Rgb666::RED)])
+            .unwrap();
+        display
+            .software_rotation(Rotation::Deg270)
+            .fill_contiguous(
+                &Rectangle::new(Point::new(0, 0), Size::new(3, 1)),
+                [Rgb666::GREEN, Rgb666::BLUE, Rgb666::WHITE],
+            )
+            .unwrap();
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(319, 479), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(0, 479), Some(Rgb888::GREEN));
+        assert_eq!(sim.pixel(0, 478), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(0, 477), Some(Rgb888::WHITE));
+    }
+}
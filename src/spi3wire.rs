@@ -0,0 +1,125 @@
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_hal::spi::SpiDevice;
+
+/// 9-bit words sent per SPI transfer, a multiple of 8 so every transfer ends on a whole byte
+const WORDS_PER_TRANSFER: usize = 64;
+
+/// Display interface for the 3-wire serial mode, for boards where the D/CX pin is not
+/// broken out (IM2:0 = 101).
+///
+/// Every byte is sent as a 9-bit word, a D/CX bit (0 for commands, 1 for data) followed by
+/// the byte, MSB first. The words are packed into 8-bit SPI transfers, so any `SpiDevice`
+/// in SPI mode 0 works. A transfer that does not end on a whole word is padded with zeros,
+/// which the controller drops when chip select goes high.
+///
+/// ```ignore
+/// let interface = Spi3WireInterface::new(spi_device);
+/// let display = Ili9488::new(interface, reset, &mut delay, Orientation::Portrait, Rgb666Mode)?;
+/// ```
+pub struct Spi3WireInterface<SPI> {
+    spi: SPI,
+}
+
+impl<SPI: SpiDevice> Spi3WireInterface<SPI> {
+    pub fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+
+    /// Give back the SPI device
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+
+    /// Send every byte of `format` with the D/CX bit `dc`
+    fn send(&mut self, dc: bool, format: DataFormat<'_>) -> Result<(), DisplayError> {
+        match format {
+            DataFormat::U8(bytes) => self.send_bytes(dc, bytes.iter().copied()),
+            DataFormat::U16(words) => {
+                self.send_bytes(dc, words.iter().flat_map(|w| w.to_be_bytes()))
+            }
+            DataFormat::U16BE(words) => {
+                self.send_bytes(dc, words.iter().flat_map(|w| w.to_be_bytes()))
+            }
+            DataFormat::U16LE(words) => {
+                self.send_bytes(dc, words.iter().flat_map(|w| w.to_le_bytes()))
+            }
+            DataFormat::U8Iter(bytes) => self.send_bytes(dc, bytes),
+            DataFormat::U16BEIter(words) => {
+                self.send_bytes(dc, words.flat_map(|w| w.to_be_bytes()))
+            }
+            DataFormat::U16LEIter(words) => {
+                self.send_bytes(dc, words.flat_map(|w| w.to_le_bytes()))
+            }
+            _ => Err(DisplayError::DataFormatNotImplemented),
+        }
+    }
+
+    fn send_bytes(
+        &mut self,
+        dc: bool,
+        bytes: impl IntoIterator<Item = u8>,
+    ) -> Result<(), DisplayError> {
+        let mut buf = [0; WORDS_PER_TRANSFER * 9 / 8];
+        let mut bits = 0;
+        for byte in bytes {
+            let word = u16::from(dc) << 8 | u16::from(byte);
+            for bit in (0..9).rev() {
+                if word >> bit & 1 != 0 {
+                    buf[bits / 8] |= 0x80 >> (bits % 8);
+                }
+                bits += 1;
+            }
+            if bits == buf.len() * 8 {
+                self.write(&buf)?;
+                buf.fill(0);
+                bits = 0;
+            }
+        }
+        if bits > 0 {
+            self.write(&buf[..bits.div_ceil(8)])?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<(), DisplayError> {
+        self.spi.write(buf).map_err(|_| DisplayError::BusWriteError)
+    }
+}
+
+impl<SPI: SpiDevice> WriteOnlyDataCommand for Spi3WireInterface<SPI> {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send(false, cmd)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send(true, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSpi;
+    use std::vec;
+
+    #[test]
+    fn packs_nine_bit_words() {
+        let mut interface = Spi3WireInterface::new(MockSpi::default());
+        interface.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+        interface
+            .send_data(DataFormat::U16BE(&mut [0xf800]))
+            .unwrap();
+        interface
+            .send_data(DataFormat::U8Iter(&mut core::iter::repeat_n(0xff, 65)))
+            .unwrap();
+
+        let writes = interface.release().writes;
+        // 0 0010 1100, padded
+        assert_eq!(writes[0], vec![0x16, 0x00]);
+        // 1 1111 1000, 1 0000 0000, padded
+        assert_eq!(writes[1], vec![0xfc, 0x40, 0x00]);
+        // 64 words of all ones in 72 bytes, then the last word
+        assert_eq!(writes[2], vec![0xff; 72]);
+        assert_eq!(writes[3], vec![0xff, 0x80]);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::dcs::Instruction;
+use crate::Ili9488;
+
+/// What the driver wrote since [Ili9488::reset_stats]
+///
+/// With the `stats` feature the driver counts what it writes, without wrapping the interface
+/// like the `bench` module does. Compare the totals before and after a change to notice
+/// drawing that falls off the fast path, e.g. a fill that suddenly takes a window per pixel.
+///
+/// ```ignore
+/// display.reset_stats();
+/// let start = Instant::now();
+/// draw_frame(&mut display)?;
+/// let stats = display.stats();
+/// let elapsed_us = start.elapsed().as_micros();
+/// info!("{} pixels/s, {} pixels per window", stats.pixels_per_second(elapsed_us), stats.pixels_per_write());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WriteStats {
+    /// Pixels sent after Memory Write and Memory Write Continue, a write that is aborted
+    /// only counts the pixels sent before
+    pub pixels: usize,
+    /// Bytes of those pixels as they were sent, plus the bytes of all commands and their
+    /// parameters
+    pub bytes: usize,
+    /// Memory writes started, one for every window drawn
+    pub memory_writes: usize,
+}
+
+impl WriteStats {
+    /// Pixels written per second, when the writes took `elapsed_us` microseconds
+    pub fn pixels_per_second(&self, elapsed_us: u64) -> u64 {
+        per_second(self.pixels, elapsed_us)
+    }
+
+    /// Bytes written per second, when the writes took `elapsed_us` microseconds
+    pub fn bytes_per_second(&self, elapsed_us: u64) -> u64 {
+        per_second(self.bytes, elapsed_us)
+    }
+
+    /// Average pixels per window, low values mean many small windows
+    pub fn pixels_per_write(&self) -> usize {
+        self.pixels.checked_div(self.memory_writes).unwrap_or(0)
+    }
+}
+
+fn per_second(count: usize, elapsed_us: u64) -> u64 {
+    (count as u64 * 1_000_000)
+        .checked_div(elapsed_us)
+        .unwrap_or(0)
+}
+
+/// Counters of the driver
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Stats {
+    totals: WriteStats,
+    /// Last Interface Pixel Format sent, the size of the pixels written afterwards
+    pixel_format: u8,
+}
+
+impl Stats {
+    /// Count the command `instruction`, Memory Write starts a memory write
+    pub(crate) fn command(&mut self, instruction: u8, params: &[u8]) {
+        self.totals.bytes += 1 + params.len();
+        if instruction == Instruction::PixelFormatSet as u8 {
+            if let Some(&format) = params.first() {
+                self.pixel_format = format;
+            }
+        }
+        if instruction == Instruction::MemoryWrite as u8 {
+            self.totals.memory_writes += 1;
+        }
+    }
+
+    /// Count `pixels` sent after Memory Write or Memory Write Continue
+    pub(crate) fn pixels(&mut self, pixels: usize) {
+        self.totals.pixels += pixels;
+    }
+
+    /// Count `bytes` of pixel data sent
+    pub(crate) fn data(&mut self, bytes: usize) {
+        self.totals.bytes += bytes;
+    }
+
+    /// Pixels in `bytes` of pixel data already packed for the last Interface Pixel Format
+    pub(crate) fn pixels_in(&self, bytes: usize) -> usize {
+        match self.pixel_format & 0x07 {
+            // 3 bpp, two pixels per byte
+            0x01 => bytes * 2,
+            0x05 => bytes / 2,
+            _ => bytes / 3,
+        }
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+{
+    /// Totals since the display was created or [Ili9488::reset_stats]
+    pub fn stats(&self) -> WriteStats {
+        self.state.stats.totals
+    }
+
+    /// Return the totals so far and start counting from zero
+    pub fn reset_stats(&mut self) -> WriteStats {
+        core::mem::take(&mut self.state.stats.totals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use crate::Rgb111;
+    use embedded_graphics_core::pixelcolor::{Rgb666, RgbColor};
+    use embedded_graphics_core::prelude::{DrawTarget, Pixel, Point, Size};
+    use embedded_graphics_core::primitives::Rectangle;
+
+    #[test]
+    fn counts_writes() {
+        let mut display = mock::display();
+        display.reset_stats();
+
+        display.fill_rect(0, 0, 9, 9, Rgb666::RED).unwrap();
+        assert_eq!(
+            display.reset_stats(),
+            WriteStats {
+                pixels: 100,
+                // Column and page address with 4 parameters, Memory Write and the pixels
+                bytes: 5 + 5 + 1 + 300,
+                memory_writes: 1,
+            }
+        );
+
+        // Pixels far apart take a window each
+        display
+            .draw_iter([
+                Pixel(Point::new(0, 0), Rgb666::RED),
+                Pixel(Point::new(100, 100), Rgb666::RED),
+            ])
+            .unwrap();
+        let stats = display.reset_stats();
+        assert_eq!((stats.pixels, stats.memory_writes), (2, 2));
+        assert_eq!(stats.pixels_per_write(), 1);
+
+        display.clear_screen_fast(Rgb111::BLUE).unwrap();
+        let stats = display.stats();
+        assert_eq!(stats.pixels, 320 * 480);
+        // Two Interface Pixel Format commands around a window of 3 bpp pixels
+        assert_eq!(stats.bytes, 2 * 2 + 5 + 5 + 1 + 320 * 480 / 2);
+        assert_eq!(stats.pixels_per_second(1_000_000), 320 * 480);
+    }
+
+    #[test]
+    fn counts_pixels_sent() {
+        let mut display = mock::display();
+        let region = Rectangle::new(Point::zero(), Size::new(10, 10));
+        display.reset_stats();
+
+        let mut session = display
+            .write_session(region, core::iter::repeat_n(Rgb666::RED, 100))
+            .unwrap();
+        session.poll(40).unwrap();
+        session.poll(30).unwrap();
+        session.abort().unwrap();
+        assert_eq!(
+            display.reset_stats(),
+            WriteStats {
+                pixels: 70,
+                // Window, Memory Write, Memory Write Continue, NOP and the pixels sent
+                bytes: 5 + 5 + 1 + 1 + 1 + 70 * 3,
+                memory_writes: 1,
+            }
+        );
+
+        // Dropping the session ends the write as well
+        let mut session = display
+            .write_session(region, core::iter::repeat_n(Rgb666::RED, 100))
+            .unwrap();
+        session.poll(10).unwrap();
+        drop(session);
+        assert_eq!(display.reset_stats().pixels, 10);
+    }
+}
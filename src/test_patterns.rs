@@ -0,0 +1,126 @@
+use display_interface::WriteOnlyDataCommand;
+use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+
+use crate::{Color, Ili9488, Ili9488MemoryWrite, Ili9488PixelFormat, Result};
+
+/// Test patterns for bringing up new boards.
+///
+/// The patterns are streamed straight to the display, no buffer is needed.
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+where
+    Self: Ili9488MemoryWrite,
+    IFACE: WriteOnlyDataCommand,
+    PixelFormat: Ili9488PixelFormat,
+{
+    /// Fill the screen with eight vertical bars, white, yellow, cyan, green, magenta, red,
+    /// blue and black from left to right.
+    ///
+    /// Swapped red and blue bars point to a wrong color order (BGR) setting.
+    pub fn draw_color_bars(&mut self) -> Result {
+        let colors = [
+            RgbColor::WHITE,
+            RgbColor::YELLOW,
+            RgbColor::CYAN,
+            RgbColor::GREEN,
+            RgbColor::MAGENTA,
+            RgbColor::RED,
+            RgbColor::BLUE,
+            RgbColor::BLACK,
+        ];
+        self.draw_rows(|x, _, width| colors[x * colors.len() / width])
+    }
+
+    /// Fill the screen with four horizontal ramps from black on the left to full red, green,
+    /// blue and white on the right, from top to bottom.
+    ///
+    /// Steps or bands in the ramps show the gamma curve and color depth.
+    pub fn draw_gradient(&mut self) -> Result
+    where
+        Color<IFACE, RESET, PixelFormat, BURST, DELAY>: From<Rgb888>,
+    {
+        let height = self.state.height;
+        self.draw_rows(|x, y, width| {
+            let value = (x * 255 / (width - 1).max(1)) as u8;
+            let color = match y * 4 / height {
+                0 => Rgb888::new(value, 0, 0),
+                1 => Rgb888::new(0, value, 0),
+                2 => Rgb888::new(0, 0, value),
+                _ => Rgb888::new(value, value, value),
+            };
+            color.into()
+        })
+    }
+
+    /// Fill the screen with black and white squares of `cell_size` pixels, starting with white
+    /// in the top left corner.
+    ///
+    /// The corner shows the orientation, and cells cut off at the right or bottom edge
+    /// show the screen size.
+    pub fn draw_checkerboard(&mut self, cell_size: u16) -> Result {
+        let cell_size = usize::from(cell_size.max(1));
+        self.draw_rows(|x, y, _| {
+            if (x / cell_size + y / cell_size) % 2 == 0 {
+                RgbColor::WHITE
+            } else {
+                RgbColor::BLACK
+            }
+        })
+    }
+
+    /// Fill the screen in raster order with `pixel(x, y, width)`
+    fn draw_rows<F>(&mut self, pixel: F) -> Result
+    where
+        F: Fn(usize, usize, usize) -> Color<IFACE, RESET, PixelFormat, BURST, DELAY>,
+    {
+        let (width, height) = (self.state.width, self.state.height);
+        let pixel = &pixel;
+        self.draw_raw_iter(
+            0,
+            0,
+            width as u16 - 1,
+            height as u16 - 1,
+            (0..height).flat_map(move |y| (0..width).map(move |x| pixel(x, y, width))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::sim;
+    use crate::{Orientation, Rgb111Mode, Rgb666Mode};
+    use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+
+    #[test]
+    fn color_bars() {
+        let mut display = sim::display_with(Orientation::Landscape, Rgb111Mode);
+        display.draw_color_bars().unwrap();
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(0, 0), Some(Rgb888::WHITE));
+        assert_eq!(sim.pixel(59, 319), Some(Rgb888::WHITE));
+        assert_eq!(sim.pixel(60, 0), Some(Rgb888::YELLOW));
+        assert_eq!(sim.pixel(300, 100), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(479, 319), Some(Rgb888::BLACK));
+    }
+
+    #[test]
+    fn gradient_and_checkerboard() {
+        let mut display = sim::display_with(Orientation::Landscape, Rgb666Mode);
+        display.draw_gradient().unwrap();
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(0, 0), Some(Rgb888::BLACK));
+        assert_eq!(sim.pixel(479, 0), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(479, 100), Some(Rgb888::GREEN));
+        assert_eq!(sim.pixel(479, 200), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(479, 319), Some(Rgb888::WHITE));
+
+        display.draw_checkerboard(16).unwrap();
+        let sim = display.interface();
+        assert_eq!(sim.pixel(15, 15), Some(Rgb888::WHITE));
+        assert_eq!(sim.pixel(16, 15), Some(Rgb888::BLACK));
+        assert_eq!(sim.pixel(16, 16), Some(Rgb888::WHITE));
+    }
+}
@@ -0,0 +1,106 @@
+//! Word-wrapped text with [embedded-text](https://crates.io/crates/embedded-text)
+//!
+//! [Ili9488::render_text_box] renders a [TextBox] with [Ili9488::render_tiled], so the
+//! background is written together with the text in a few large windows and old text under the
+//! box is cleared on the way. [Ili9488::draw_text_box] is a convenience wrapper that builds the
+//! box from a string and a [MonoTextStyle]:
+//!
+//! ```ignore
+//! let style = MonoTextStyle::new(&FONT_6X10, Rgb666::WHITE);
+//! let bounds = Rectangle::new(Point::new(10, 10), Size::new(300, 200));
+//! let rest = display.draw_text_box(bounds, "Lorem ipsum dolor sit amet, ...", style)?;
+//! ```
+use display_interface::WriteOnlyDataCommand;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Rgb666;
+use embedded_graphics::prelude::{Drawable, RgbColor};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::renderer::{CharacterStyle, TextRenderer};
+use embedded_graphics_core::prelude::DrawTarget;
+use embedded_text::TextBox;
+
+use crate::{Ili9488, Result, Rgb666Mode};
+
+/// Width of the tiles text boxes are rendered in
+pub const TEXT_TILE_WIDTH: usize = 160;
+/// Height of the tiles text boxes are rendered in, a tile takes 3.75 KiB of stack
+pub const TEXT_TILE_HEIGHT: usize = 8;
+
+impl<IFACE, RESET, const BURST: usize, DELAY> Ili9488<IFACE, RESET, Rgb666Mode, BURST, DELAY>
+where
+    IFACE: WriteOnlyDataCommand,
+{
+    /// Render `text_box` on `background`, in tiles of [TEXT_TILE_WIDTH] x [TEXT_TILE_HEIGHT]
+    /// pixels.
+    ///
+    /// The whole bounds of the box are written, so it can be redrawn over old text. Returns
+    /// the text that did not fit, to continue on the next page.
+    pub fn render_text_box<'a, S>(
+        &mut self,
+        text_box: &TextBox<'a, S>,
+        background: Rgb666,
+    ) -> Result<&'a str>
+    where
+        S: TextRenderer<Color = Rgb666> + CharacterStyle<Color = Rgb666>,
+    {
+        let mut rest = text_box.text;
+        self.render_tiled::<TEXT_TILE_WIDTH, TEXT_TILE_HEIGHT>(text_box.bounds, |tile| {
+            tile.clear(background)?;
+            rest = text_box.draw(tile)?;
+            Ok(())
+        })?;
+        Ok(rest)
+    }
+
+    /// Render `text` word-wrapped in `bounds` with `style`, see [Ili9488::render_text_box].
+    ///
+    /// The box is filled with the background color of `style`, black without one. Returns the
+    /// text that did not fit.
+    pub fn draw_text_box<'a>(
+        &mut self,
+        bounds: Rectangle,
+        text: &'a str,
+        style: MonoTextStyle<'_, Rgb666>,
+    ) -> Result<&'a str> {
+        let background = style.background_color.unwrap_or(Rgb666::BLACK);
+        self.render_text_box(&TextBox::new(text, bounds, style), background)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sim;
+    use embedded_graphics::mono_font::ascii::FONT_6X10;
+    use embedded_graphics::prelude::{Dimensions, Point, Size};
+
+    #[test]
+    fn renders_like_drawing_directly() {
+        let text = "The quick brown fox jumps over the lazy dog";
+        let bounds = Rectangle::new(Point::new(300, 5), Size::new(60, 30));
+        let style = MonoTextStyle::new(&FONT_6X10, Rgb666::YELLOW);
+
+        let mut tiled = sim::display();
+        // Only three lines fit, the box is cut to the screen as well
+        assert_eq!(
+            tiled.draw_text_box(bounds, text, style).unwrap(),
+            "the lazy dog"
+        );
+
+        let mut direct = sim::display();
+        direct
+            .fill_solid(&bounds.intersection(&direct.bounding_box()), Rgb666::BLACK)
+            .unwrap();
+        TextBox::new(text, bounds, style).draw(&mut direct).unwrap();
+        for y in 0..40 {
+            for x in 295..320 {
+                assert_eq!(
+                    tiled.interface().pixel(x, y),
+                    direct.interface().pixel(x, y),
+                    "({x}, {y})"
+                );
+            }
+        }
+    }
+}
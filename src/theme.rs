@@ -0,0 +1,252 @@
+//! Colors for user interfaces drawn with [Rgb111Mode](crate::Rgb111Mode)
+//!
+//! At 3 bits per pixel only the 8 [Rgb111] colors are available. A [Theme] maps the roles
+//! of UI elements onto them, and ordered dithering mixes them to simulate the shades in
+//! between, e.g. a gray track for a slider:
+//!
+//! ```ignore
+//! let theme = Theme::DARK;
+//! display.clear_screen(theme.color(Role::Background))?;
+//! let gray = Rgb666::new(32, 32, 32);
+//! let track = area.points().map(|p| Pixel(p, dither(gray, p, BayerMatrix::Bayer4x4)));
+//! ```
+//!
+//! [DitheredRgb111Writer] dithers whole streams of pixels, e.g. images previewed in
+//! [Rgb111Mode](crate::Rgb111Mode).
+#[cfg(feature = "rgb111")]
+use display_interface::WriteOnlyDataCommand;
+use embedded_graphics_core::pixelcolor::Rgb666;
+use embedded_graphics_core::prelude::{Pixel, PixelColor, Point, RgbColor};
+#[cfg(feature = "rgb111")]
+use embedded_graphics_core::primitives::{PointsIter, Rectangle};
+
+use crate::Rgb111;
+#[cfg(feature = "rgb111")]
+use crate::{Ili9488, Ili9488MemoryWrite, Result, Rgb111Mode};
+
+/// What a color is used for in a user interface
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Role {
+    Background,
+    Foreground,
+    Accent,
+    Warning,
+}
+
+/// [Rgb111] colors for every [Role]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Theme {
+    pub background: Rgb111,
+    pub foreground: Rgb111,
+    pub accent: Rgb111,
+    pub warning: Rgb111,
+}
+
+impl Theme {
+    /// White text on black with cyan accents
+    pub const DARK: Self = Self {
+        background: Rgb111::BLACK,
+        foreground: Rgb111::WHITE,
+        accent: Rgb111::CYAN,
+        warning: Rgb111::YELLOW,
+    };
+
+    /// Black text on white with blue accents
+    pub const LIGHT: Self = Self {
+        background: Rgb111::WHITE,
+        foreground: Rgb111::BLACK,
+        accent: Rgb111::BLUE,
+        warning: Rgb111::RED,
+    };
+
+    /// The color of `role`
+    pub fn color(&self, role: Role) -> Rgb111 {
+        match role {
+            Role::Background => self.background,
+            Role::Foreground => self.foreground,
+            Role::Accent => self.accent,
+            Role::Warning => self.warning,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+/// Threshold matrix for ordered dithering
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BayerMatrix {
+    /// 2x2 matrix, 5 levels per channel with a coarse pattern
+    Bayer2x2,
+    /// 4x4 matrix, 17 levels per channel with a finer pattern
+    Bayer4x4,
+}
+
+const BAYER_2X2: [[u8; 2]; 2] = [[0, 2], [3, 1]];
+
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+impl BayerMatrix {
+    /// Rank of `point` in the matrix and the number of cells
+    fn rank(self, point: Point) -> (u32, u32) {
+        let (x, y) = (
+            point.x.rem_euclid(4) as usize,
+            point.y.rem_euclid(4) as usize,
+        );
+        match self {
+            Self::Bayer2x2 => (BAYER_2X2[y % 2][x % 2].into(), 4),
+            Self::Bayer4x4 => (BAYER_4X4[y % 4][x % 4].into(), 16),
+        }
+    }
+}
+
+/// The [Rgb111] color to draw at `point` so the area around it looks like `color`
+pub fn dither(color: Rgb666, point: Point, matrix: BayerMatrix) -> Rgb111 {
+    let (rank, cells) = matrix.rank(point);
+    // A channel is on when its share of the maximum exceeds the cell's threshold,
+    // (rank + 1/2) / cells
+    let on = |value: u8| 2 * cells * u32::from(value) >= (2 * rank + 1) * u32::from(Rgb666::MAX_R);
+    Rgb111::from_bits(on(color.r()), on(color.g()), on(color.b()))
+}
+
+/// Iterator adapter turning pixels of color `C` at their positions into Bayer-dithered
+/// [Rgb111] colors, so full color images stay recognizable when previewed in [Rgb111Mode].
+///
+/// ```ignore
+/// let pixels = area.points().zip(photo_row_iter).map(|(p, c)| Pixel(p, c));
+/// display.draw_raw_iter(x0, y0, x1, y1, DitheredRgb111Writer::new(pixels, BayerMatrix::Bayer4x4))?;
+/// ```
+///
+/// See [Ili9488::draw_dithered] for the common case of filling a region.
+pub struct DitheredRgb111Writer<I> {
+    pixels: I,
+    matrix: BayerMatrix,
+}
+
+impl<I> DitheredRgb111Writer<I> {
+    pub fn new<C>(pixels: impl IntoIterator<IntoIter = I>, matrix: BayerMatrix) -> Self
+    where
+        C: PixelColor,
+        I: Iterator<Item = Pixel<C>>,
+    {
+        Self {
+            pixels: pixels.into_iter(),
+            matrix,
+        }
+    }
+}
+
+impl<I, C> Iterator for DitheredRgb111Writer<I>
+where
+    I: Iterator<Item = Pixel<C>>,
+    C: PixelColor + Into<Rgb666>,
+{
+    type Item = Rgb111;
+
+    fn next(&mut self) -> Option<Rgb111> {
+        let Pixel(point, color) = self.pixels.next()?;
+        Some(dither(color.into(), point, self.matrix))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.pixels.size_hint()
+    }
+}
+
+#[cfg(feature = "rgb111")]
+impl<IFACE, RESET, const BURST: usize, DELAY> Ili9488<IFACE, RESET, Rgb111Mode, BURST, DELAY>
+where
+    IFACE: WriteOnlyDataCommand,
+{
+    /// Fill `region` with `colors` in raster order, dithered to [Rgb111] with `matrix`.
+    ///
+    /// `colors` can be any color that converts to [Rgb666],
+    /// e.g. [Rgb565](embedded_graphics_core::pixelcolor::Rgb565).
+    pub fn draw_dithered<C, I>(
+        &mut self,
+        region: Rectangle,
+        colors: I,
+        matrix: BayerMatrix,
+    ) -> Result
+    where
+        C: PixelColor + Into<Rgb666>,
+        I: IntoIterator<Item = C>,
+    {
+        self.set_region_window(&region)?;
+        let pixels = region
+            .points()
+            .zip(colors)
+            .map(|(point, color)| Pixel(point, color));
+        self.write_iter(DitheredRgb111Writer::new(pixels, matrix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sim;
+    use crate::Orientation;
+    use embedded_graphics_core::pixelcolor::{Rgb565, Rgb888};
+    use embedded_graphics_core::prelude::Size;
+
+    fn count_white(color: Rgb666, matrix: BayerMatrix) -> usize {
+        (0..4)
+            .flat_map(|y| (0..4).map(move |x| Point::new(x, y)))
+            .filter(|&p| dither(color, p, matrix) == Rgb111::WHITE)
+            .count()
+    }
+
+    #[test]
+    fn dither_levels() {
+        assert_eq!(count_white(Rgb666::BLACK, BayerMatrix::Bayer4x4), 0);
+        assert_eq!(count_white(Rgb666::WHITE, BayerMatrix::Bayer4x4), 16);
+        assert_eq!(
+            count_white(Rgb666::new(32, 32, 32), BayerMatrix::Bayer4x4),
+            8
+        );
+        assert_eq!(
+            count_white(Rgb666::new(16, 16, 16), BayerMatrix::Bayer2x2),
+            4
+        );
+        assert_eq!(
+            dither(
+                Rgb666::new(63, 63, 0),
+                Point::new(3, 7),
+                BayerMatrix::Bayer2x2
+            ),
+            Rgb111::YELLOW
+        );
+    }
+
+    #[test]
+    fn dithered_writer() {
+        let mut display = sim::display_with(Orientation::Portrait, Rgb111Mode);
+        let area = Rectangle::new(Point::new(8, 8), Size::new(4, 4));
+        let colors = core::iter::repeat_n(Rgb565::new(16, 32, 16), 16);
+        display
+            .draw_dithered(area, colors, BayerMatrix::Bayer2x2)
+            .unwrap();
+
+        let sim = display.interface();
+        let white = area
+            .points()
+            .filter(|p| sim.pixel(p.x as usize, p.y as usize) == Some(Rgb888::WHITE))
+            .count();
+        assert_eq!(white, 8);
+        assert_eq!(sim.pixel(8, 8), Some(Rgb888::WHITE));
+        assert_eq!(sim.pixel(9, 8), Some(Rgb888::BLACK));
+    }
+
+    #[test]
+    fn theme_roles() {
+        assert_eq!(Theme::default().color(Role::Accent), Rgb111::CYAN);
+        assert_eq!(Theme::LIGHT.color(Role::Background), Rgb111::WHITE);
+    }
+}
@@ -0,0 +1,171 @@
+use core::convert::Infallible;
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_graphics_core::pixelcolor::Rgb666;
+use embedded_graphics_core::prelude::{Dimensions, DrawTarget, Pixel, Point, RgbColor, Size};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::dcs::WriteMemoryStart;
+use crate::{Ili9488, Result, Rgb666Mode};
+
+impl<IFACE, RESET, const BURST: usize, DELAY> Ili9488<IFACE, RESET, Rgb666Mode, BURST, DELAY>
+where
+    IFACE: WriteOnlyDataCommand,
+{
+    /// Render `region` in tiles of `TW` x `TH` pixels, buffered on the stack.
+    ///
+    /// `draw` is called once per tile, left to right and top to bottom, with a [Tile] draw
+    /// target covering only that tile. Whatever falls outside of the tile is dropped, so the
+    /// closure simply draws the whole scene every time. The tile is sent to the display in
+    /// its own window once the closure returns. Pixels the closure does not draw keep the
+    /// contents of the previous tile, start with a background.
+    ///
+    /// Tiles at the right and bottom edge are cut to `region`, which is cut to the screen.
+    ///
+    /// ```ignore
+    /// // 3 KiB on the stack
+    /// display.render_tiled::<32, 32>(display.bounding_box(), |tile| {
+    ///     tile.clear(Rgb666::BLACK)?;
+    ///     Text::new("Hello", Point::new(10, 100), style).draw(tile)?;
+    ///     Ok(())
+    /// })?;
+    /// ```
+    pub fn render_tiled<const TW: usize, const TH: usize>(
+        &mut self,
+        region: Rectangle,
+        mut draw: impl FnMut(&mut Tile<'_>) -> core::result::Result<(), Infallible>,
+    ) -> Result {
+        if TW == 0 || TH == 0 {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        let region = region.intersection(&self.bounding_box());
+        if region.bottom_right().is_none() {
+            return Ok(());
+        }
+        let mut buf = [[[0u8; 3]; TW]; TH];
+        let (left, top) = (region.top_left.x, region.top_left.y);
+        for y in (0..region.size.height).step_by(TH) {
+            for x in (0..region.size.width).step_by(TW) {
+                let size = Size::new(
+                    (TW as u32).min(region.size.width - x),
+                    (TH as u32).min(region.size.height - y),
+                );
+                let area = Rectangle::new(Point::new(left + x as i32, top + y as i32), size);
+                let pixels = size.width as usize * size.height as usize;
+                let mut tile = Tile {
+                    buf: &mut buf.as_flattened_mut()[..pixels],
+                    area,
+                };
+                let Ok(()) = draw(&mut tile);
+
+                self.set_region_window(&area)?;
+                self.write_command(WriteMemoryStart)?;
+                self.interface
+                    .send_data(DataFormat::U8(tile.buf.as_flattened()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Draw target for one tile of [Ili9488::render_tiled], in screen coordinates
+pub struct Tile<'a> {
+    buf: &'a mut [[u8; 3]],
+    area: Rectangle,
+}
+
+impl Dimensions for Tile<'_> {
+    fn bounding_box(&self) -> Rectangle {
+        self.area
+    }
+}
+
+impl DrawTarget for Tile<'_> {
+    type Color = Rgb666;
+
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> core::result::Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let width = self.area.size.width as usize;
+        for Pixel(point, color) in pixels {
+            if self.area.contains(point) {
+                let offset = point - self.area.top_left;
+                self.buf[offset.y as usize * width + offset.x as usize] =
+                    [color.r() << 2, color.g() << 2, color.b() << 2];
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(
+        &mut self,
+        area: &Rectangle,
+        color: Self::Color,
+    ) -> core::result::Result<(), Self::Error> {
+        let area = area.intersection(&self.area);
+        let Some(bottom_right) = area.bottom_right() else {
+            return Ok(());
+        };
+        let wire = [color.r() << 2, color.g() << 2, color.b() << 2];
+        let width = self.area.size.width as usize;
+        let (x0, x1) = (
+            (area.top_left.x - self.area.top_left.x) as usize,
+            (bottom_right.x - self.area.top_left.x) as usize,
+        );
+        let (y0, y1) = (
+            (area.top_left.y - self.area.top_left.y) as usize,
+            (bottom_right.y - self.area.top_left.y) as usize,
+        );
+        for row in self.buf.chunks_exact_mut(width).take(y1 + 1).skip(y0) {
+            row[x0..=x1].fill(wire);
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> core::result::Result<(), Self::Error> {
+        self.buf
+            .fill([color.r() << 2, color.g() << 2, color.b() << 2]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim;
+    use embedded_graphics_core::pixelcolor::Rgb888;
+
+    #[test]
+    fn renders_in_tiles() {
+        let mut display = sim::display();
+
+        // 50x30 pixels in tiles of 16x16, the edge tiles are cut
+        let region = Rectangle::new(Point::new(300, 10), Size::new(50, 30));
+        let mut tiles = 0;
+        display
+            .render_tiled::<16, 16>(region, |tile| {
+                tiles += 1;
+                tile.clear(Rgb666::BLUE)?;
+                tile.fill_solid(
+                    &Rectangle::new(Point::new(310, 12), Size::new(5, 20)),
+                    Rgb666::RED,
+                )?;
+                tile.draw_iter([Pixel(Point::new(319, 39), Rgb666::GREEN)])
+            })
+            .unwrap();
+        // Cut to the 20 columns on screen
+        assert_eq!(tiles, 4);
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(299, 10), Some(Rgb888::BLACK));
+        assert_eq!(sim.pixel(300, 10), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(310, 12), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(314, 31), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(315, 31), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(319, 39), Some(Rgb888::GREEN));
+        assert_eq!(sim.pixel(319, 40), Some(Rgb888::BLACK));
+    }
+}
@@ -0,0 +1,349 @@
+//! Touch controllers found on ILI9488 modules
+//!
+//! Raw readings are mapped to the display's memory through a [Calibration], then to the
+//! coordinates of the display's current orientation, so touch points keep matching what is
+//! drawn after [Ili9488::set_orientation].
+//!
+//! ```ignore
+//! let mut touch = Touch::new(Xpt2046::new(touch_spi), Calibration::default());
+//! touch.calibrate(raw_points, screen_points, &display);
+//!
+//! if let Some(TouchEvent::Pressed(point)) = touch.poll(&display)? {
+//!     // ...
+//! }
+//! ```
+use embedded_graphics_core::prelude::Point;
+use embedded_hal::i2c::I2c;
+use embedded_hal::spi::SpiDevice;
+
+use crate::{DisplaySize, DisplaySize320x480, Ili9488};
+
+/// A touch controller that reports raw touch coordinates
+pub trait TouchController {
+    type Error;
+
+    /// The raw position being touched, or `None` when the panel is not touched
+    fn read_raw(&mut self) -> Result<Option<Point>, Self::Error>;
+}
+
+/// XPT2046 resistive touch controller, on SPI
+pub struct Xpt2046<SPI> {
+    spi: SPI,
+    pressure_threshold: u16,
+}
+
+impl<SPI: SpiDevice> Xpt2046<SPI> {
+    pub fn new(spi: SPI) -> Self {
+        Self {
+            spi,
+            pressure_threshold: 400,
+        }
+    }
+
+    /// Set the minimum pressure, from 0 to 4095, that counts as a touch
+    pub fn set_pressure_threshold(&mut self, threshold: u16) {
+        self.pressure_threshold = threshold;
+    }
+
+    /// Consumes the controller, gives back the SPI device
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+
+    /// Read a 12 bit conversion of `channel`
+    fn read_channel(&mut self, channel: u8) -> Result<u16, SPI::Error> {
+        let mut buf = [channel, 0, 0];
+        self.spi.transfer_in_place(&mut buf)?;
+        Ok(u16::from_be_bytes([buf[1], buf[2]]) >> 3)
+    }
+}
+
+impl<SPI: SpiDevice> TouchController for Xpt2046<SPI> {
+    type Error = SPI::Error;
+
+    fn read_raw(&mut self) -> Result<Option<Point>, Self::Error> {
+        let z1 = self.read_channel(0xb0)?;
+        let z2 = self.read_channel(0xc0)?;
+        if z1 + 4095 - z2 < self.pressure_threshold {
+            return Ok(None);
+        }
+        let x = self.read_channel(0xd0)?;
+        let y = self.read_channel(0x90)?;
+        Ok(Some(Point::new(x.into(), y.into())))
+    }
+}
+
+/// FT6236 capacitive touch controller, on I2C
+pub struct Ft6236<I2C> {
+    i2c: I2C,
+    address: u8,
+}
+
+impl<I2C: I2c> Ft6236<I2C> {
+    pub fn new(i2c: I2C) -> Self {
+        Self { i2c, address: 0x38 }
+    }
+
+    /// Consumes the controller, gives back the I2C bus
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+impl<I2C: I2c> TouchController for Ft6236<I2C> {
+    type Error = I2C::Error;
+
+    fn read_raw(&mut self) -> Result<Option<Point>, Self::Error> {
+        // Number of touches, then the position of the first touch
+        let mut buf = [0; 5];
+        self.i2c.write_read(self.address, &[0x02], &mut buf)?;
+        if !(1..=2).contains(&(buf[0] & 0x0f)) {
+            return Ok(None);
+        }
+        let x = u16::from_be_bytes([buf[1] & 0x0f, buf[2]]);
+        let y = u16::from_be_bytes([buf[3] & 0x0f, buf[4]]);
+        Ok(Some(Point::new(x.into(), y.into())))
+    }
+}
+
+/// Affine transform from raw touch coordinates to columns and rows of the display's memory
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Calibration {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32,
+    f: f32,
+}
+
+impl Default for Calibration {
+    /// Raw coordinates are already columns and rows, like the FT6236 reports them
+    fn default() -> Self {
+        Self::new(1.0, 0.0, 0.0, 0.0, 1.0, 0.0)
+    }
+}
+
+impl Calibration {
+    /// `column = a * x + b * y + c` and `row = d * x + e * y + f`
+    pub const fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    /// Solve the transform mapping three `raw` touch points to three points of the display's
+    /// memory. Returns `None` when the points are on a line.
+    pub fn from_points(raw: [Point; 3], memory: [Point; 3]) -> Option<Self> {
+        let [(x1, y1), (x2, y2), (x3, y3)] = raw.map(|p| (p.x as f32, p.y as f32));
+        let det = (x1 - x3) * (y2 - y3) - (x2 - x3) * (y1 - y3);
+        if det == 0.0 {
+            return None;
+        }
+        let solve = |u1: f32, u2: f32, u3: f32| {
+            let a = ((u1 - u3) * (y2 - y3) - (u2 - u3) * (y1 - y3)) / det;
+            let b = ((x1 - x3) * (u2 - u3) - (x2 - x3) * (u1 - u3)) / det;
+            (a, b, u3 - a * x3 - b * y3)
+        };
+        let [u1, u2, u3] = memory.map(|p| p.x as f32);
+        let (a, b, c) = solve(u1, u2, u3);
+        let [v1, v2, v3] = memory.map(|p| p.y as f32);
+        let (d, e, f) = solve(v1, v2, v3);
+        Some(Self::new(a, b, c, d, e, f))
+    }
+
+    /// Map a raw touch point to the display's memory
+    pub fn apply(&self, raw: Point) -> Point {
+        let (x, y) = (raw.x as f32, raw.y as f32);
+        Point::new(
+            round(self.a * x + self.b * y + self.c),
+            round(self.d * x + self.e * y + self.f),
+        )
+    }
+}
+
+fn round(value: f32) -> i32 {
+    if value < 0.0 {
+        (value - 0.5) as i32
+    } else {
+        (value + 0.5) as i32
+    }
+}
+
+/// A change of the touch state, in the coordinates of the display's current orientation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TouchEvent {
+    Pressed(Point),
+    Moved(Point),
+    /// The panel was released, at the last position touched
+    Released(Point),
+}
+
+// Point does not implement defmt::Format
+#[cfg(feature = "defmt")]
+impl defmt::Format for TouchEvent {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Self::Pressed(point) => defmt::write!(f, "Pressed({}, {})", point.x, point.y),
+            Self::Moved(point) => defmt::write!(f, "Moved({}, {})", point.x, point.y),
+            Self::Released(point) => defmt::write!(f, "Released({}, {})", point.x, point.y),
+        }
+    }
+}
+
+/// A calibrated touch controller that reports [TouchEvent]s
+pub struct Touch<T> {
+    controller: T,
+    calibration: Calibration,
+    last: Option<Point>,
+}
+
+impl<T: TouchController> Touch<T> {
+    pub fn new(controller: T, calibration: Calibration) -> Self {
+        Self {
+            controller,
+            calibration,
+            last: None,
+        }
+    }
+
+    pub fn calibration(&self) -> Calibration {
+        self.calibration
+    }
+
+    /// Calibrate from three `raw` readings taken while touching `screen` points drawn on
+    /// `display`. Returns `false`, keeping the old calibration, when the points are on a line.
+    pub fn calibrate<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>(
+        &mut self,
+        raw: [Point; 3],
+        screen: [Point; 3],
+        display: &Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>,
+    ) -> bool {
+        match Calibration::from_points(raw, screen.map(|p| display.screen_to_memory(p))) {
+            Some(calibration) => {
+                self.calibration = calibration;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Read the controller and report what changed since the last poll
+    pub fn poll<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>(
+        &mut self,
+        display: &Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>,
+    ) -> Result<Option<TouchEvent>, T::Error> {
+        let point = self
+            .controller
+            .read_raw()?
+            .map(|raw| display.memory_to_screen(self.calibration.apply(raw)));
+        let event = match (self.last, point) {
+            (None, Some(point)) => Some(TouchEvent::Pressed(point)),
+            (Some(last), Some(point)) if last != point => Some(TouchEvent::Moved(point)),
+            (Some(last), None) => Some(TouchEvent::Released(last)),
+            _ => None,
+        };
+        self.last = point;
+        Ok(event)
+    }
+
+    /// Consumes the touch handler, gives back the controller
+    pub fn release(self) -> T {
+        self.controller
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+{
+    /// Map a column and row of the display's memory to the current orientation
+    fn memory_to_screen(&self, point: Point) -> Point {
+        let (mut column, mut row) = (point.x, point.y);
+        if self.state.madctl & 0x40 != 0 {
+            column = DisplaySize320x480::WIDTH as i32 - 1 - column;
+        }
+        if self.state.madctl & 0x80 != 0 {
+            row = DisplaySize320x480::HEIGHT as i32 - 1 - row;
+        }
+        if self.state.madctl & 0x20 != 0 {
+            Point::new(row, column)
+        } else {
+            Point::new(column, row)
+        }
+    }
+
+    /// Map a point in the current orientation to a column and row of the display's memory
+    fn screen_to_memory(&self, point: Point) -> Point {
+        let (mut column, mut row) = if self.state.madctl & 0x20 != 0 {
+            (point.y, point.x)
+        } else {
+            (point.x, point.y)
+        };
+        if self.state.madctl & 0x40 != 0 {
+            column = DisplaySize320x480::WIDTH as i32 - 1 - column;
+        }
+        if self.state.madctl & 0x80 != 0 {
+            row = DisplaySize320x480::HEIGHT as i32 - 1 - row;
+        }
+        Point::new(column, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use crate::Orientation;
+    use std::vec::Vec;
+
+    /// Replays a list of raw readings
+    struct Replay(Vec<Option<Point>>);
+
+    impl TouchController for Replay {
+        type Error = ();
+
+        fn read_raw(&mut self) -> Result<Option<Point>, ()> {
+            Ok(self.0.remove(0))
+        }
+    }
+
+    #[test]
+    fn calibration_from_points() {
+        let raw = [
+            Point::new(300, 200),
+            Point::new(3800, 250),
+            Point::new(350, 3900),
+        ];
+        let memory = [Point::new(10, 10), Point::new(310, 10), Point::new(10, 470)];
+        let calibration = Calibration::from_points(raw, memory).unwrap();
+        for (raw, memory) in raw.iter().zip(memory) {
+            assert_eq!(calibration.apply(*raw), memory);
+        }
+        assert!(Calibration::from_points([Point::zero(); 3], memory).is_none());
+    }
+
+    #[test]
+    fn events_track_orientation() {
+        let mut display = mock::display();
+        let screen = [Point::new(20, 30), Point::new(300, 40), Point::new(50, 400)];
+        let mut touch = Touch::new(Replay(Vec::new()), Calibration::default());
+        assert!(touch.calibrate(screen, screen, &display));
+
+        display
+            .set_orientation(Orientation::LandscapeFlipped)
+            .unwrap();
+        let raw = Point::new(20, 30);
+        touch.controller.0 = [Some(raw), Some(raw), None].into();
+        // Portrait mirrors columns, landscape flipped exchanges and mirrors both
+        let landscape = Point::new(449, 20);
+        assert_eq!(display.screen_to_memory(landscape), Point::new(299, 30));
+        assert_eq!(
+            touch.poll(&display).unwrap(),
+            Some(TouchEvent::Pressed(landscape))
+        );
+        assert_eq!(touch.poll(&display).unwrap(), None);
+        assert_eq!(
+            touch.poll(&display).unwrap(),
+            Some(TouchEvent::Released(landscape))
+        );
+    }
+}
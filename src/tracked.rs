@@ -0,0 +1,164 @@
+use embedded_graphics_core::prelude::{Dimensions, DrawTarget, Pixel, Point};
+use embedded_graphics_core::primitives::Rectangle;
+
+/// [DrawTarget] adapter recording the bounding box of everything drawn through it
+///
+/// Draw with embedded-graphics as usual and take the damage afterwards, to flush or erase
+/// only that part:
+///
+/// ```ignore
+/// let mut tracked = TrackedTarget::new(&mut display);
+/// Text::new(&label, Point::new(10, 20), style).draw(&mut tracked)?;
+/// let damage = tracked.take_damage();
+/// // Later, erase the label again
+/// if let Some(damage) = damage {
+///     display.fill_solid(&damage, BACKGROUND)?;
+/// }
+/// ```
+pub struct TrackedTarget<'a, D> {
+    target: &'a mut D,
+    damage: Option<Rectangle>,
+}
+
+impl<'a, D: DrawTarget> TrackedTarget<'a, D> {
+    pub fn new(target: &'a mut D) -> Self {
+        Self {
+            target,
+            damage: None,
+        }
+    }
+
+    /// Bounding box of everything drawn since creation or the last [TrackedTarget::take_damage],
+    /// cut to the target
+    pub fn damage(&self) -> Option<Rectangle> {
+        self.damage
+    }
+
+    /// Get the damage and start tracking anew
+    pub fn take_damage(&mut self) -> Option<Rectangle> {
+        self.damage.take()
+    }
+
+    fn add_damage(&mut self, area: &Rectangle) {
+        let area = area.intersection(&self.target.bounding_box());
+        let Some(bottom_right) = area.bottom_right() else {
+            return;
+        };
+        self.damage = Some(match self.damage {
+            Some(damage) => Rectangle::with_corners(
+                damage.top_left.component_min(area.top_left),
+                damage
+                    .bottom_right()
+                    .map_or(bottom_right, |corner| corner.component_max(bottom_right)),
+            ),
+            None => area,
+        });
+    }
+}
+
+impl<D: DrawTarget> Dimensions for TrackedTarget<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for TrackedTarget<'_, D> {
+    type Color = D::Color;
+
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounding_box = self.target.bounding_box();
+        let mut corners: Option<(Point, Point)> = None;
+        let pixels = pixels.into_iter().inspect(|&Pixel(point, _)| {
+            if bounding_box.contains(point) {
+                corners = Some(match corners {
+                    Some((min, max)) => (min.component_min(point), max.component_max(point)),
+                    None => (point, point),
+                });
+            }
+        });
+        let result = self.target.draw_iter(pixels);
+        // Pixels drawn before an error are damage too
+        if let Some((min, max)) = corners {
+            self.add_damage(&Rectangle::with_corners(min, max));
+        }
+        result
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.add_damage(area);
+        self.target.fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.add_damage(area);
+        self.target.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.add_damage(&self.target.bounding_box());
+        self.target.clear(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim;
+    use embedded_graphics_core::pixelcolor::{Rgb666, Rgb888, RgbColor};
+    use embedded_graphics_core::prelude::Size;
+
+    #[test]
+    fn tracks_damage() {
+        let mut display = sim::display();
+        let mut tracked = TrackedTarget::new(&mut display);
+        assert_eq!(tracked.damage(), None);
+
+        tracked
+            .draw_iter([
+                Pixel(Point::new(10, 20), Rgb666::RED),
+                Pixel(Point::new(4, 25), Rgb666::RED),
+                Pixel(Point::new(-1, 0), Rgb666::RED),
+            ])
+            .unwrap();
+        assert_eq!(
+            tracked.damage(),
+            Some(Rectangle::new(Point::new(4, 20), Size::new(7, 6)))
+        );
+        // Cut to the screen
+        tracked
+            .fill_solid(
+                &Rectangle::new(Point::new(315, 22), Size::new(10, 2)),
+                Rgb666::BLUE,
+            )
+            .unwrap();
+        assert_eq!(
+            tracked.take_damage(),
+            Some(Rectangle::new(Point::new(4, 20), Size::new(316, 6)))
+        );
+        assert_eq!(tracked.take_damage(), None);
+
+        tracked
+            .fill_contiguous(
+                &Rectangle::new(Point::new(0, 500), Size::new(2, 2)),
+                [Rgb666::GREEN; 4],
+            )
+            .unwrap();
+        assert_eq!(tracked.damage(), None);
+        tracked.clear(Rgb666::BLACK).unwrap();
+        assert_eq!(tracked.damage(), Some(display.bounding_box()));
+
+        let mut tracked = TrackedTarget::new(&mut display);
+        tracked
+            .draw_iter([Pixel(Point::new(1, 1), Rgb666::WHITE)])
+            .unwrap();
+        assert_eq!(display.interface().pixel(1, 1), Some(Rgb888::WHITE));
+    }
+}
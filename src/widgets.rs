@@ -0,0 +1,391 @@
+//! Bars that redraw only what changed
+//!
+//! A [ProgressBar] or [BarGauge] remembers how far it is filled on screen. Setting a new
+//! value fills just the strip between the old and the new end, which the display writes
+//! through a single window, instead of redrawing the whole widget on every update.
+//!
+//! ```ignore
+//! let mut bar = ProgressBar::new(area, 100, Rgb666::GREEN, Rgb666::BLACK);
+//! for percent in 0..=100 {
+//!     bar.set_value(&mut display, percent)?;
+//! }
+//! ```
+//!
+//! A [Ticker] scrolls status text through the hardware scroll area, drawing only the lines
+//! that come into view.
+//!
+//! Enable the `widgets` feature to use them.
+use display_interface::{DisplayError, WriteOnlyDataCommand};
+use embedded_graphics_core::prelude::{DrawTarget, PixelColor, Point, Size};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::{Ili9488, Ili9488MemoryWrite, Ili9488PixelFormat, ScrollError, Scroller};
+
+/// Bar filled along one axis, with what is currently on screen
+struct Bar<C> {
+    area: Rectangle,
+    /// Filling bottom up instead of left to right
+    vertical: bool,
+    max: u32,
+    value: u32,
+    background: C,
+    /// Filled length and color on screen, `None` before the first draw
+    drawn: Option<(u32, C)>,
+}
+
+impl<C: PixelColor> Bar<C> {
+    fn new(area: Rectangle, vertical: bool, max: u32, background: C) -> Self {
+        Self {
+            area,
+            vertical,
+            max,
+            value: 0,
+            background,
+            drawn: None,
+        }
+    }
+
+    fn extent(&self) -> u32 {
+        if self.vertical {
+            self.area.size.height
+        } else {
+            self.area.size.width
+        }
+    }
+
+    /// Filled length for `value`
+    fn length(&self, value: u32) -> u32 {
+        let filled = u64::from(value.min(self.max)) * u64::from(self.extent());
+        (filled / u64::from(self.max.max(1))) as u32
+    }
+
+    /// The part of the bar from `from` to `to` along the axis
+    fn strip(&self, from: u32, to: u32) -> Rectangle {
+        let Rectangle { top_left, size } = self.area;
+        if self.vertical {
+            let top = top_left.y + (size.height - to) as i32;
+            Rectangle::new(
+                Point::new(top_left.x, top),
+                Size::new(size.width, to - from),
+            )
+        } else {
+            let left = top_left.x + from as i32;
+            Rectangle::new(
+                Point::new(left, top_left.y),
+                Size::new(to - from, size.height),
+            )
+        }
+    }
+
+    fn fill<D>(&self, display: &mut D, from: u32, to: u32, color: C) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        if from < to {
+            display.fill_solid(&self.strip(from, to), color)?;
+        }
+        Ok(())
+    }
+
+    /// Show `value` filled with `color`, drawing only the strips that changed
+    fn update<D>(&mut self, display: &mut D, value: u32, color: C) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let length = self.length(value);
+        let (start, end) = match self.drawn {
+            Some((drawn, drawn_color)) if drawn_color == color => (drawn.min(length), drawn),
+            Some((drawn, _)) => (0, drawn),
+            None => (0, self.extent()),
+        };
+        self.fill(display, start, length, color)?;
+        self.fill(display, length, end, self.background)?;
+        self.value = value;
+        self.drawn = Some((length, color));
+        Ok(())
+    }
+}
+
+/// Horizontal bar filling from left to right as the value goes from 0 to `max`
+pub struct ProgressBar<C> {
+    bar: Bar<C>,
+    fill: C,
+}
+
+impl<C: PixelColor> ProgressBar<C> {
+    /// Bar covering `area`, nothing is drawn until the first [ProgressBar::set_value]
+    pub fn new(area: Rectangle, max: u32, fill: C, background: C) -> Self {
+        Self {
+            bar: Bar::new(area, false, max, background),
+            fill,
+        }
+    }
+
+    /// Current value
+    pub fn value(&self) -> u32 {
+        self.bar.value
+    }
+
+    /// Show `value`, values above `max` show a full bar
+    pub fn set_value<D>(&mut self, display: &mut D, value: u32) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        self.bar.update(display, value, self.fill)
+    }
+
+    /// Draw the whole bar again, e.g. after the screen was cleared
+    pub fn redraw<D>(&mut self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        self.bar.drawn = None;
+        self.set_value(display, self.bar.value)
+    }
+}
+
+/// Vertical bar filling from the bottom up as the value goes from 0 to `max`, like a level
+/// meter, optionally changing color above a warning threshold
+pub struct BarGauge<C> {
+    bar: Bar<C>,
+    fill: C,
+    warning: Option<(u32, C)>,
+}
+
+impl<C: PixelColor> BarGauge<C> {
+    /// Gauge covering `area`, nothing is drawn until the first [BarGauge::set_value]
+    pub fn new(area: Rectangle, max: u32, fill: C, background: C) -> Self {
+        Self {
+            bar: Bar::new(area, true, max, background),
+            fill,
+            warning: None,
+        }
+    }
+
+    /// Fill the gauge with `color` instead while the value is at or above `threshold`
+    pub fn with_warning(mut self, threshold: u32, color: C) -> Self {
+        self.warning = Some((threshold, color));
+        self
+    }
+
+    /// Current value
+    pub fn value(&self) -> u32 {
+        self.bar.value
+    }
+
+    /// Show `value`, values above `max` show a full gauge.
+    ///
+    /// Crossing the warning threshold redraws the filled part in the other color.
+    pub fn set_value<D>(&mut self, display: &mut D, value: u32) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let color = match self.warning {
+            Some((threshold, color)) if value >= threshold => color,
+            _ => self.fill,
+        };
+        self.bar.update(display, value, color)
+    }
+
+    /// Draw the whole gauge again, e.g. after the screen was cleared
+    pub fn redraw<D>(&mut self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        self.bar.drawn = None;
+        self.set_value(display, self.bar.value)
+    }
+}
+
+/// Text scrolling up through the hardware scroll area, like a news ticker.
+///
+/// The text is a 1 bit per pixel bitmap as wide as the screen, in the layout of
+/// [Ili9488::blit_mono_bitmap], e.g. rendered once with a font into a static buffer.
+/// The controller scrolls the area, so every step only sends the few lines that enter
+/// at the bottom. The text starts over after its last line.
+///
+/// Hardware scrolling needs a portrait orientation, see [Ili9488::configure_vertical_scroll].
+///
+/// ```ignore
+/// let mut ticker = Ticker::new(&mut display, 440, 0, Rgb666::WHITE, Rgb666::BLACK)?;
+/// loop {
+///     ticker.advance(&mut display, 1, &TEXT_BITS)?;
+///     delay.delay_ms(30);
+/// }
+/// ```
+pub struct Ticker<C> {
+    scroller: Scroller,
+    fg: C,
+    bg: C,
+    /// Lines scrolled since the start of the scroll area
+    position: u16,
+    /// Next line of the text to draw
+    text_line: usize,
+}
+
+impl<C: Copy> Ticker<C> {
+    /// Configure the area between `fixed_top_lines` and `fixed_bottom_lines` for scrolling
+    /// and clear it to `bg`
+    pub fn new<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>(
+        display: &mut Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>,
+        fixed_top_lines: u16,
+        fixed_bottom_lines: u16,
+        fg: C,
+        bg: C,
+    ) -> Result<Self, ScrollError>
+    where
+        Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>: Ili9488MemoryWrite<PixelFormat = C>,
+        IFACE: WriteOnlyDataCommand,
+        PixelFormat: Ili9488PixelFormat,
+    {
+        let scroller = display.configure_vertical_scroll(fixed_top_lines, fixed_bottom_lines)?;
+        let (width, lines) = (display.width() as u16, scroller.scroll_lines());
+        display.draw_raw_iter(
+            0,
+            fixed_top_lines,
+            width - 1,
+            fixed_top_lines + lines - 1,
+            core::iter::repeat_n(bg, usize::from(width) * usize::from(lines)),
+        )?;
+        Ok(Self {
+            scroller,
+            fg,
+            bg,
+            position: 0,
+            text_line: 0,
+        })
+    }
+
+    /// Lines scrolled since the start of the scroll area
+    pub fn position(&self) -> u16 {
+        self.position
+    }
+
+    /// Scroll up by `num_lines` and draw the next lines of `text` at the bottom.
+    ///
+    /// `text` must hold whole rows of the screen width, at least one.
+    pub fn advance<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>(
+        &mut self,
+        display: &mut Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>,
+        num_lines: u16,
+        text: &[u8],
+    ) -> Result<(), ScrollError>
+    where
+        Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>: Ili9488MemoryWrite<PixelFormat = C>,
+        IFACE: WriteOnlyDataCommand,
+        PixelFormat: Ili9488PixelFormat,
+    {
+        let width = display.width() as u16;
+        let row_bytes = usize::from(width).div_ceil(8);
+        let text_lines = text.len() / row_bytes;
+        if text_lines == 0 {
+            return Err(DisplayError::OutOfBoundsError.into());
+        }
+        let lines = self.scroller.scroll_lines();
+        let num_lines = num_lines.min(lines);
+        let start = self.position;
+        self.position = (self.position + num_lines) % lines;
+        display.set_scroll_position(&mut self.scroller, self.position)?;
+
+        // The lines that left at the top are shown at the bottom now
+        for line in 0..num_lines {
+            let y = self.scroller.fixed_top_lines + (start + line) % lines;
+            let row = self.text_line * row_bytes;
+            display.blit_mono_bitmap(
+                Rectangle::new(Point::new(0, y.into()), Size::new(width.into(), 1)),
+                &text[row..row + row_bytes],
+                self.fg,
+                self.bg,
+            )?;
+            self.text_line = (self.text_line + 1) % text_lines;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use crate::sim;
+    use embedded_graphics_core::pixelcolor::{Rgb666, Rgb888, RgbColor};
+
+    #[test]
+    fn progress_bar_draws_delta() {
+        let mut display = mock::display();
+        let area = Rectangle::new(Point::new(10, 10), Size::new(100, 10));
+        let mut bar = ProgressBar::new(area, 200, Rgb666::GREEN, Rgb666::BLACK);
+        bar.set_value(&mut display, 100).unwrap();
+
+        // 10 more columns of 10 pixels, after the window
+        display.interface.clear();
+        bar.set_value(&mut display, 120).unwrap();
+        assert_eq!(display.interface.data().len(), 8 + 10 * 10 * 3);
+
+        display.interface.clear();
+        bar.set_value(&mut display, 120).unwrap();
+        assert!(display.interface.data().is_empty());
+        assert_eq!(bar.value(), 120);
+    }
+
+    #[test]
+    fn progress_bar_shrinks() {
+        let mut display = sim::display();
+        let area = Rectangle::new(Point::new(0, 0), Size::new(100, 4));
+        let mut bar = ProgressBar::new(area, 100, Rgb666::GREEN, Rgb666::BLUE);
+        bar.set_value(&mut display, 60).unwrap();
+        bar.set_value(&mut display, 30).unwrap();
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(29, 3), Some(Rgb888::GREEN));
+        assert_eq!(sim.pixel(30, 0), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(99, 3), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(100, 0), Some(Rgb888::BLACK));
+    }
+
+    #[test]
+    fn bar_gauge_warning() {
+        let mut display = sim::display();
+        let area = Rectangle::new(Point::new(10, 0), Size::new(5, 50));
+        let mut gauge =
+            BarGauge::new(area, 10, Rgb666::GREEN, Rgb666::BLACK).with_warning(8, Rgb666::RED);
+        gauge.set_value(&mut display, 5).unwrap();
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(10, 49), Some(Rgb888::GREEN));
+        assert_eq!(sim.pixel(14, 25), Some(Rgb888::GREEN));
+        assert_eq!(sim.pixel(14, 24), Some(Rgb888::BLACK));
+
+        gauge.set_value(&mut display, 9).unwrap();
+        let sim = display.interface();
+        assert_eq!(sim.pixel(10, 49), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(10, 5), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(10, 4), Some(Rgb888::BLACK));
+    }
+
+    #[test]
+    fn ticker_draws_entering_lines() {
+        let mut display = sim::display();
+        display.clear_screen(Rgb666::RED).unwrap();
+        let mut ticker = Ticker::new(&mut display, 400, 40, Rgb666::WHITE, Rgb666::BLACK).unwrap();
+        // Three lines of text: first column set, empty, last column set
+        let mut text = [0; 3 * 40];
+        text[0] = 0x80;
+        text[2 * 40 + 39] = 0x01;
+
+        ticker.advance(&mut display, 2, &text).unwrap();
+        ticker.advance(&mut display, 39, &text).unwrap();
+        assert_eq!(ticker.position(), 1);
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(0, 399), Some(Rgb888::RED));
+        // Line 40 of the text, the second again, replaced the first at the top of the area
+        assert_eq!(sim.pixel(0, 400), Some(Rgb888::BLACK));
+        assert_eq!(sim.pixel(0, 401), Some(Rgb888::BLACK));
+        assert_eq!(sim.pixel(319, 402), Some(Rgb888::WHITE));
+        assert_eq!(sim.pixel(0, 403), Some(Rgb888::WHITE));
+        assert_eq!(sim.pixel(1, 403), Some(Rgb888::BLACK));
+        assert_eq!(sim.pixel(0, 439), Some(Rgb888::WHITE));
+        assert_eq!(sim.pixel(0, 440), Some(Rgb888::RED));
+    }
+}
@@ -0,0 +1,365 @@
+use core::iter::Peekable;
+
+use display_interface::WriteOnlyDataCommand;
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::dcs::{Instruction, Nop};
+use crate::{Ili9488, Ili9488PixelPacking, NoDelay, PackedPixels, Result};
+
+/// Handle for writing the pixels of a region in several chunks, created with
+/// [Ili9488::begin_write].
+///
+/// The first chunk is sent with `MemoryWrite`, later chunks with `MemoryWriteContinue`,
+/// so they continue where the previous chunk ended without setting the window again.
+///
+/// With [crate::Rgb111Mode] and [crate::Rgb666Bus16Mode] two pixels share bus words, the
+/// last pixel of a chunk with an odd number of pixels is held back and sent with the next
+/// chunk, or by [PixelWriter::finish].
+///
+/// ```ignore
+/// let mut writer = display.begin_write(Rectangle::new(Point::zero(), Size::new(480, 320)))?;
+/// while let Some(line) = decoder.next_line() {
+///     writer.push(line.iter().copied())?;
+/// }
+/// writer.finish()?;
+/// ```
+pub struct PixelWriter<'a, IFACE, RESET, PixelFormat, const BURST: usize = 64, DELAY = NoDelay>
+where
+    PixelFormat: Ili9488PixelPacking,
+{
+    display: &'a mut Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>,
+    started: bool,
+    /// Odd last pixel of the previous chunk
+    pending: Option<PixelFormat::Color>,
+    /// First pixel of the write, which the odd pixel at its end is padded with
+    first: Option<PixelFormat::Color>,
+}
+
+/// Number of pixels [Ili9488::fill_rect_with_progress] writes between progress reports,
+/// even so pixel formats packing two pixels into a bus word are not split
+pub const FILL_CHUNK_PIXELS: usize = 4096;
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    Ili9488<IFACE, RESET, PixelFormat, BURST, DELAY>
+where
+    IFACE: WriteOnlyDataCommand,
+    PixelFormat: Ili9488PixelPacking,
+{
+    /// Set the window to `region` and start writing its pixels in chunks, see [PixelWriter]
+    pub fn begin_write(
+        &mut self,
+        region: Rectangle,
+    ) -> Result<PixelWriter<'_, IFACE, RESET, PixelFormat, BURST, DELAY>> {
+        self.set_region_window(&region)?;
+        Ok(PixelWriter {
+            display: self,
+            started: false,
+            pending: None,
+            first: None,
+        })
+    }
+
+    /// Start writing `pixels` to `region` in steps driven by [WriteSession::poll],
+    /// so a long write can be interleaved with other work or aborted
+    pub fn write_session<I>(
+        &mut self,
+        region: Rectangle,
+        pixels: I,
+    ) -> Result<WriteSession<'_, IFACE, RESET, PixelFormat, I::IntoIter, BURST, DELAY>>
+    where
+        I: IntoIterator<Item = PixelFormat::Color>,
+    {
+        Ok(WriteSession {
+            writer: self.begin_write(region)?,
+            pixels: pixels.into_iter().peekable(),
+            done: false,
+        })
+    }
+
+    /// Fill `region` with `color` in chunks of [FILL_CHUNK_PIXELS], calling `progress` with the
+    /// percentage done after every chunk.
+    ///
+    /// Large fills take tens of milliseconds, `progress` can feed a watchdog or yield to other
+    /// tasks in between.
+    ///
+    /// ```ignore
+    /// display.fill_rect_with_progress(screen, Rgb666::BLACK, |_| watchdog.feed())?;
+    /// ```
+    pub fn fill_rect_with_progress<F>(
+        &mut self,
+        region: Rectangle,
+        color: PixelFormat::Color,
+        mut progress: F,
+    ) -> Result
+    where
+        F: FnMut(u8),
+    {
+        let total = region.size.width as usize * region.size.height as usize;
+        let mut writer = self.begin_write(region)?;
+        let mut done = 0;
+        while done < total {
+            let chunk = FILL_CHUNK_PIXELS.min(total - done);
+            writer.push(core::iter::repeat_n(color, chunk))?;
+            done += chunk;
+            progress((done * 100 / total) as u8);
+        }
+        writer.finish()
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, const BURST: usize, DELAY>
+    PixelWriter<'_, IFACE, RESET, PixelFormat, BURST, DELAY>
+where
+    IFACE: WriteOnlyDataCommand,
+    PixelFormat: Ili9488PixelPacking,
+{
+    /// Write the next chunk of pixels
+    pub fn push<I>(&mut self, pixels: I) -> Result
+    where
+        I: IntoIterator<Item = PixelFormat::Color>,
+    {
+        self.start_chunk()?;
+        let mut pixels = PackedPixels::continued(self.pending.take().into_iter().chain(pixels));
+        let result = self.display.write_packed(&mut pixels);
+        self.pending = pixels.unpaired();
+        self.first = self.first.or(pixels.first());
+        result
+    }
+
+    /// End the memory write with a NOP command, after the pixel held back by the last chunk
+    pub fn finish(mut self) -> Result {
+        self.flush()?;
+        self.display.command(Instruction::Nop, &[])
+    }
+
+    /// Start a chunk with `MemoryWrite` or `MemoryWriteContinue`
+    fn start_chunk(&mut self) -> Result {
+        let command = if self.started {
+            Instruction::MemoryWriteContinue
+        } else {
+            Instruction::MemoryWrite
+        };
+        self.display.command(command, &[])?;
+        self.started = true;
+        Ok(())
+    }
+
+    /// Write the pixel held back by the last chunk
+    fn flush(&mut self) -> Result {
+        match (self.pending.take(), self.first) {
+            (Some(pixel), Some(first)) => {
+                self.start_chunk()?;
+                self.display
+                    .write_packed(&mut PackedPixels::last(pixel, first))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A long write, e.g. clearing the screen or drawing an image, that an RTOS task can run
+/// a step at a time and abort, created with [Ili9488::write_session].
+///
+/// The memory write is always ended with a NOP command, whether it completes, is aborted,
+/// or the session is dropped, so the controller is ready for the next command.
+///
+/// ```ignore
+/// let screen = display.bounding_box();
+/// let mut session = display.write_session(screen, image.pixels())?;
+/// while !session.poll(1024)? {
+///     if ui_event_pending() {
+///         session.abort()?;
+///         break;
+///     }
+/// }
+/// ```
+pub struct WriteSession<'a, IFACE, RESET, PixelFormat, I, const BURST: usize = 64, DELAY = NoDelay>
+where
+    IFACE: WriteOnlyDataCommand,
+    PixelFormat: Ili9488PixelPacking,
+    I: Iterator,
+{
+    writer: PixelWriter<'a, IFACE, RESET, PixelFormat, BURST, DELAY>,
+    pixels: Peekable<I>,
+    done: bool,
+}
+
+impl<IFACE, RESET, PixelFormat, I, const BURST: usize, DELAY>
+    WriteSession<'_, IFACE, RESET, PixelFormat, I, BURST, DELAY>
+where
+    IFACE: WriteOnlyDataCommand,
+    PixelFormat: Ili9488PixelPacking,
+    I: Iterator<Item = PixelFormat::Color>,
+{
+    /// Write up to `max_pixels` more pixels, returns `true` once all pixels are written.
+    ///
+    /// With [crate::Rgb111Mode] and [crate::Rgb666Bus16Mode] an odd last pixel is held back
+    /// until the next poll, see [PixelWriter].
+    pub fn poll(&mut self, max_pixels: usize) -> Result<bool> {
+        if !self.done && self.pixels.peek().is_some() {
+            self.writer.push(self.pixels.by_ref().take(max_pixels))?;
+        }
+        if !self.done && self.pixels.peek().is_none() {
+            self.writer.flush()?;
+            self.end()?;
+        }
+        Ok(self.done)
+    }
+
+    /// Stop writing, leaving the pixels not written yet as they were
+    pub fn abort(mut self) -> Result {
+        self.end()
+    }
+
+    fn end(&mut self) -> Result {
+        self.done = true;
+        self.writer.display.command(Instruction::Nop, &[])
+    }
+}
+
+impl<IFACE, RESET, PixelFormat, I, const BURST: usize, DELAY> Drop
+    for WriteSession<'_, IFACE, RESET, PixelFormat, I, BURST, DELAY>
+where
+    IFACE: WriteOnlyDataCommand,
+    PixelFormat: Ili9488PixelPacking,
+    I: Iterator,
+{
+    fn drop(&mut self) {
+        if !self.done {
+            // Errors can not be reported here, use abort to see them
+            let _ = self.writer.display.write_command(Nop);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock;
+    use crate::sim;
+    use crate::{Orientation, Rgb111, Rgb111Mode};
+    use embedded_graphics_core::pixelcolor::{Rgb666, Rgb888, RgbColor};
+    use embedded_graphics_core::prelude::{Point, Size};
+    use embedded_graphics_core::primitives::Rectangle;
+    use std::vec::Vec;
+
+    #[test]
+    fn chunks_continue() {
+        let mut display = sim::display();
+
+        let mut writer = display
+            .begin_write(Rectangle::new(Point::new(3, 3), Size::new(2, 2)))
+            .unwrap();
+        writer
+            .push([Rgb666::RED, Rgb666::GREEN, Rgb666::BLUE])
+            .unwrap();
+        writer.push([Rgb666::WHITE]).unwrap();
+        writer.finish().unwrap();
+
+        let sim = display.interface();
+        assert_eq!(sim.pixel(3, 3), Some(Rgb888::RED));
+        assert_eq!(sim.pixel(4, 3), Some(Rgb888::GREEN));
+        assert_eq!(sim.pixel(3, 4), Some(Rgb888::BLUE));
+        assert_eq!(sim.pixel(4, 4), Some(Rgb888::WHITE));
+
+        assert!(display
+            .begin_write(Rectangle::new(Point::new(319, 0), Size::new(2, 1)))
+            .is_err());
+    }
+
+    #[test]
+    fn odd_chunks_of_shared_bytes() {
+        let mut display = sim::display_with(Orientation::Portrait, Rgb111Mode);
+        let colors = [
+            Rgb111::RED,
+            Rgb111::GREEN,
+            Rgb111::BLUE,
+            Rgb111::WHITE,
+            Rgb111::CYAN,
+            Rgb111::YELLOW,
+        ];
+        let expected = [
+            Rgb888::RED,
+            Rgb888::GREEN,
+            Rgb888::BLUE,
+            Rgb888::WHITE,
+            Rgb888::CYAN,
+            Rgb888::YELLOW,
+        ];
+
+        let mut writer = display
+            .begin_write(Rectangle::new(Point::new(10, 10), Size::new(6, 1)))
+            .unwrap();
+        writer.push(colors[..3].iter().copied()).unwrap();
+        writer.push(colors[3..].iter().copied()).unwrap();
+        writer.finish().unwrap();
+        for (x, color) in (10..).zip(expected) {
+            assert_eq!(display.interface().pixel(x, 10), Some(color));
+        }
+
+        // The odd pixel of the last poll is written before the session ends
+        let region = Rectangle::new(Point::new(10, 20), Size::new(5, 1));
+        let mut session = display
+            .write_session(region, colors[..5].iter().copied())
+            .unwrap();
+        while !session.poll(3).unwrap() {}
+        drop(session);
+        for (x, color) in (10..15).zip(expected) {
+            assert_eq!(display.interface().pixel(x, 20), Some(color));
+        }
+    }
+
+    #[test]
+    fn session_abort() {
+        let mut display = mock::display();
+        let region = Rectangle::new(Point::zero(), Size::new(10, 10));
+
+        let mut session = display
+            .write_session(region, core::iter::repeat_n(Rgb666::RED, 100))
+            .unwrap();
+        assert!(!session.poll(40).unwrap());
+        assert!(!session.poll(40).unwrap());
+        session.abort().unwrap();
+        let commands = display.interface.commands();
+        let commands: Vec<u8> = commands.iter().rev().take(3).map(|(c, _)| *c).collect();
+        assert_eq!(commands, [0x00, 0x3c, 0x2c]);
+
+        display.interface.clear();
+        let mut session = display
+            .write_session(region, core::iter::repeat_n(Rgb666::RED, 100))
+            .unwrap();
+        assert!(!session.poll(60).unwrap());
+        assert!(session.poll(60).unwrap());
+        assert!(session.poll(60).unwrap());
+        drop(session);
+        // The window is still set from the aborted session
+        assert_eq!(display.interface.data().len(), 100 * 3);
+        assert_eq!(display.interface.commands().last().unwrap().0, 0x00);
+
+        // Dropping an unfinished session ends the write as well
+        display.interface.clear();
+        let mut session = display
+            .write_session(region, core::iter::repeat_n(Rgb666::RED, 100))
+            .unwrap();
+        session.poll(10).unwrap();
+        drop(session);
+        assert_eq!(display.interface.commands().last().unwrap().0, 0x00);
+    }
+
+    #[test]
+    fn fill_with_progress() {
+        let mut display = sim::display_with(Orientation::Portrait, Rgb111Mode);
+
+        let mut reports = Vec::new();
+        let region = Rectangle::new(Point::new(0, 10), Size::new(320, 30));
+        display
+            .fill_rect_with_progress(region, Rgb111::CYAN, |percent| reports.push(percent))
+            .unwrap();
+
+        // 9600 pixels in chunks of 4096
+        assert_eq!(reports, [42, 85, 100]);
+        let sim = display.interface();
+        assert_eq!(sim.pixel(0, 10), Some(Rgb888::CYAN));
+        assert_eq!(sim.pixel(319, 39), Some(Rgb888::CYAN));
+        assert_eq!(sim.pixel(319, 40), Some(Rgb888::BLACK));
+    }
+}